@@ -250,5 +250,39 @@ pub fn remove(c: &mut Criterion) {
     }
 }
 
-criterion_group!(benches, insert, insert_1_item, get, remove);
+pub fn spill(c: &mut Criterion) {
+    let mut group = c.benchmark_group("map_spill");
+
+    // Insert enough distinct keys to cross the tiny map's capacity of 20
+    for n in 18..=23 {
+        group.bench_with_input(BenchmarkId::new("tiny_map_20", n), &n, |b, &n| {
+            b.iter_batched(
+                || (0..n).map(|_| random::<usize>()).collect::<Vec<_>>(),
+                |keys| {
+                    let mut map = tinymap!(usize => usize; 20);
+                    for i in keys {
+                        map.insert(i, i);
+                    }
+                    map
+                },
+                BatchSize::SmallInput,
+            )
+        });
+        group.bench_with_input(BenchmarkId::new("btree_map", n), &n, |b, &n| {
+            b.iter_batched(
+                || (0..n).map(|_| random::<usize>()).collect::<Vec<_>>(),
+                |keys| {
+                    let mut map = BTreeMap::new();
+                    for i in keys {
+                        map.insert(i, i);
+                    }
+                    map
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+}
+
+criterion_group!(benches, insert, insert_1_item, get, remove, spill);
 criterion_main!(benches);
@@ -0,0 +1,1037 @@
+//! An array-backed hash map that uses open addressing
+
+use core::{
+    borrow::Borrow,
+    fmt,
+    hash::{BuildHasher, Hash, Hasher},
+    iter::FromIterator,
+    marker::PhantomData,
+    mem::{replace, zeroed, ManuallyDrop},
+    ops::Index,
+    ptr, slice,
+};
+
+use crate::{Array, Inner, MapArray};
+
+/**
+The hasher that ArrayHashMap and ArrayHashSet use by default
+
+With the `alloc` feature, this is the standard library's `RandomState`. Without it, it is SipHash
+with fixed keys, which does not protect against keys that are chosen to collide.
+*/
+#[cfg(feature = "alloc")]
+pub type DefaultHashBuilder = std::collections::hash_map::RandomState;
+
+/**
+The hasher that ArrayHashMap and ArrayHashSet use by default
+
+With the `alloc` feature, this is the standard library's `RandomState`. Without it, it is SipHash
+with fixed keys, which does not protect against keys that are chosen to collide.
+*/
+#[cfg(not(feature = "alloc"))]
+#[allow(deprecated)]
+pub type DefaultHashBuilder = core::hash::BuildHasherDefault<core::hash::SipHasher>;
+
+/// The largest array that a hash map or set can use, limited by the bits in its occupancy mask
+pub(crate) const MAX_CAPACITY: usize = 64;
+
+/// Fails to compile for arrays with more slots than the occupancy mask has bits
+pub(crate) struct CheckCapacity<A>(PhantomData<A>);
+
+impl<A: MapArray> CheckCapacity<A> {
+    pub(crate) const MAP: () = assert!(
+        A::CAPACITY <= MAX_CAPACITY,
+        "ArrayHashMap capacity may be at most 64"
+    );
+}
+
+impl<A: Array> CheckCapacity<A> {
+    pub(crate) const SET: () = assert!(
+        A::CAPACITY <= MAX_CAPACITY,
+        "ArrayHashSet capacity may be at most 64"
+    );
+}
+
+/**
+Gets the number of entries that fit in `capacity` slots
+
+An eighth of the slots are kept empty so that probing for a missing key ends quickly.
+*/
+pub(crate) const fn max_load(capacity: usize) -> usize {
+    capacity - capacity / 8
+}
+
+/// Hashes a key with a hasher built by `hasher`
+pub(crate) fn hash<S, Q>(hasher: &S, key: &Q) -> u64
+where
+    S: BuildHasher,
+    Q: Hash + ?Sized,
+{
+    let mut state = hasher.build_hasher();
+    key.hash(&mut state);
+    state.finish()
+}
+
+/// Returns `true` if slot `i` holds an entry
+pub(crate) fn is_occupied(occupied: u64, i: usize) -> bool {
+    occupied >> i & 1 == 1
+}
+
+/// Gets the slot that probing for a hash starts from
+pub(crate) fn home(hash: u64, capacity: usize) -> usize {
+    // Scales the hash onto the slots without a division
+    ((u128::from(hash) * capacity as u128) >> 64) as usize
+}
+
+/**
+Probes the slots for an entry, starting from `home`
+
+Returns the entry's slot if `is_match` finds it. Otherwise, returns the first empty slot,
+or `None` if every slot is full.
+*/
+pub(crate) fn probe<T, F>(
+    slots: &[Inner<T>],
+    occupied: u64,
+    home: usize,
+    mut is_match: F,
+) -> Result<usize, Option<usize>>
+where
+    F: FnMut(&T) -> bool,
+{
+    let mut i = home;
+    for _ in 0..slots.len() {
+        if !is_occupied(occupied, i) {
+            return Err(Some(i));
+        }
+        if is_match(unsafe { slots[i].as_ptr().as_ref() }.unwrap()) {
+            return Ok(i);
+        }
+        i = (i + 1) % slots.len();
+    }
+    Err(None)
+}
+
+/**
+Takes the entry out of slot `i` and moves later entries of its probe sequence back to fill the gap
+
+This keeps every entry reachable from its home slot without leaving tombstones behind.
+*/
+pub(crate) fn take_slot<T, F>(slots: &mut [Inner<T>], occupied: &mut u64, i: usize, home_of: F) -> T
+where
+    F: FnMut(&T) -> usize,
+{
+    take_slot_tracked(slots, occupied, &mut 0, i, home_of)
+}
+
+/// Like [`take_slot`], but also moves the bits of `tracked` along with the entries they belong to
+pub(crate) fn take_slot_tracked<T, F>(
+    slots: &mut [Inner<T>],
+    occupied: &mut u64,
+    tracked: &mut u64,
+    mut i: usize,
+    mut home_of: F,
+) -> T
+where
+    F: FnMut(&T) -> usize,
+{
+    let taken = unsafe { slots[i].as_ptr().read() };
+    *occupied &= !(1 << i);
+    *tracked &= !(1 << i);
+    let mut j = i;
+    loop {
+        j = (j + 1) % slots.len();
+        if !is_occupied(*occupied, j) {
+            break;
+        }
+        let home = home_of(unsafe { slots[j].as_ptr().as_ref() }.unwrap());
+        // An entry has to stay if its home is cyclically within (i, j]
+        let stays = if i <= j {
+            i < home && home <= j
+        } else {
+            i < home || home <= j
+        };
+        if !stays {
+            slots.swap(i, j);
+            *occupied |= 1 << i;
+            *occupied &= !(1 << j);
+            *tracked |= (*tracked >> j & 1) << i;
+            *tracked &= !(1 << j);
+            i = j;
+        }
+    }
+    taken
+}
+
+/**
+An array-backed hash map that uses open addressing
+
+ArrayHashMap wraps an array of key-value pairs like an [`ArrayMap`](../array_map/struct.ArrayMap.html),
+but it places them by the hashes of their keys instead of keeping them sorted. Its keys only need
+to implement `Hash` and `Eq`, and lookups take constant time on average. Its entries are in no
+particular order.
+
+Collisions are resolved with linear probing. The map never uses the heap, and its hasher is chosen
+with the `S` parameter, which defaults to [`DefaultHashBuilder`](type.DefaultHashBuilder.html).
+
+The array's capacity may be at most 64. To keep probing short, the map is full once seven eighths
+of its slots are taken, so arrays of 8 or more slots hold fewer entries than they have slots.
+*/
+pub struct ArrayHashMap<A, S = DefaultHashBuilder>
+where
+    A: MapArray,
+{
+    array: A,
+    // Bit `i` is set if slot `i` holds an entry
+    occupied: u64,
+    len: usize,
+    hasher: S,
+}
+
+impl<A, S> Default for ArrayHashMap<A, S>
+where
+    A: MapArray,
+    S: Default,
+{
+    fn default() -> Self {
+        ArrayHashMap::with_hasher(S::default())
+    }
+}
+
+impl<A, S> Clone for ArrayHashMap<A, S>
+where
+    A: MapArray,
+    A::Key: Clone,
+    A::Value: Clone,
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        let mut array: A = unsafe { zeroed() };
+        for (i, slot) in self.array.as_slice().iter().enumerate() {
+            if is_occupied(self.occupied, i) {
+                let (key, value) = unsafe { slot.as_ptr().as_ref() }.unwrap();
+                array.as_mut_slice()[i] = Inner::new((key.clone(), value.clone()));
+            }
+        }
+        ArrayHashMap {
+            array,
+            occupied: self.occupied,
+            len: self.len,
+            hasher: self.hasher.clone(),
+        }
+    }
+}
+
+impl<A> ArrayHashMap<A>
+where
+    A: MapArray,
+{
+    /**
+    Creates a new empty ArrayHashMap
+
+    # Example
+    ```
+    use tinymap::*;
+
+    let mut map = ArrayHashMap::<[Inner<(i32, &str)>; 10]>::new();
+    ```
+    */
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<A, S> ArrayHashMap<A, S>
+where
+    A: MapArray,
+{
+    /**
+    Creates a new empty ArrayHashMap that hashes its keys with the given hasher
+
+    # Example
+    ```
+    use std::collections::hash_map::RandomState;
+    use tinymap::*;
+
+    let mut map = ArrayHashMap::<[Inner<(i32, &str)>; 10], _>::with_hasher(RandomState::new());
+    map.insert(1, "a");
+    ```
+    */
+    pub fn with_hasher(hasher: S) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = CheckCapacity::<A>::MAP;
+        ArrayHashMap {
+            array: unsafe { zeroed() },
+            occupied: 0,
+            len: 0,
+            hasher,
+        }
+    }
+    /**
+    Returns a reference to the map's hasher
+    */
+    pub fn hasher(&self) -> &S {
+        &self.hasher
+    }
+    /**
+    Clears the map, removing all elements
+
+    # Example
+    ```
+    use tinymap::*;
+
+    let mut a = ArrayHashMap::<[Inner<(i32, &str)>; 10]>::new();
+    a.insert(1, "a");
+    a.clear();
+    assert!(a.is_empty());
+    ```
+    */
+    pub fn clear(&mut self) {
+        let occupied = replace(&mut self.occupied, 0);
+        self.len = 0;
+        for (i, slot) in self.array.as_mut_slice().iter_mut().enumerate() {
+            if is_occupied(occupied, i) {
+                unsafe { slot.as_mut_ptr().drop_in_place() };
+            }
+        }
+    }
+    /**
+    Returns the number of elements in the map
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut a = ArrayHashMap::<[Inner<(i32, &str)>; 10]>::new();
+    assert_eq!(a.len(), 0);
+    a.insert(1, "a");
+    assert_eq!(a.len(), 1);
+    ```
+    */
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    /**
+    Returns `true` if the map contains no elements
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut a = ArrayHashMap::<[Inner<(i32, &str)>; 10]>::new();
+    assert!(a.is_empty());
+    a.insert(1, "a");
+    assert!(!a.is_empty());
+    ```
+    */
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /**
+    Returns the maximum number of elements the map can contain
+
+    This is seven eighths of the array's capacity, rounded up.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut a = ArrayHashMap::<[Inner<(i32, &str)>; 10]>::new();
+    assert_eq!(9, a.capacity());
+    ```
+    */
+    pub fn capacity(&self) -> usize {
+        max_load(A::CAPACITY)
+    }
+    /**
+    Gets an iterator over the entries of the map, in no particular order
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = ArrayHashMap::<[Inner<(i32, &str)>; 10]>::new();
+    map.insert(3, "c");
+    map.insert(2, "b");
+    map.insert(1, "a");
+
+    for (key, value) in map.iter() {
+        println!("{}: {}", key, value);
+    }
+
+    let mut entries = map.iter().collect::<Vec<_>>();
+    entries.sort();
+    assert_eq!(entries, [(&1, &"a"), (&2, &"b"), (&3, &"c")]);
+    ```
+    */
+    pub fn iter(&self) -> Iter<'_, A::Key, A::Value> {
+        Iter {
+            iter: self.array.as_slice().iter(),
+            occupied: self.occupied,
+        }
+    }
+    /**
+    Gets a mutable iterator over the entries of the map, in no particular order
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = ArrayHashMap::<[Inner<(&str, i32)>; 10]>::new();
+    map.insert("a", 1);
+    map.insert("b", 2);
+    map.insert("c", 3);
+
+    // add 10 to the value if the key isn't "a"
+    for (key, value) in map.iter_mut() {
+        if key != &"a" {
+            *value += 10;
+        }
+    }
+
+    assert_eq!(map[&"b"], 12);
+    ```
+    */
+    pub fn iter_mut(&mut self) -> IterMut<'_, A::Key, A::Value> {
+        IterMut {
+            iter: self.array.as_mut_slice().iter_mut(),
+            occupied: self.occupied,
+        }
+    }
+    /**
+    Gets an iterator over the keys of the map, in no particular order
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut a = ArrayHashMap::<[Inner<(i32, &str)>; 10]>::new();
+    a.insert(2, "b");
+    a.insert(1, "a");
+
+    let mut keys = a.keys().copied().collect::<Vec<_>>();
+    keys.sort();
+    assert_eq!(keys, [1, 2]);
+    ```
+    */
+    pub fn keys(&self) -> Keys<'_, A::Key, A::Value> {
+        Keys { iter: self.iter() }
+    }
+    /**
+    Gets an iterator over the values of the map, in no particular order
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut a = ArrayHashMap::<[Inner<(i32, &str)>; 10]>::new();
+    a.insert(1, "hello");
+    a.insert(2, "goodbye");
+
+    let mut values = a.values().copied().collect::<Vec<_>>();
+    values.sort();
+    assert_eq!(values, ["goodbye", "hello"]);
+    ```
+    */
+    pub fn values(&self) -> Values<'_, A::Key, A::Value> {
+        Values { iter: self.iter() }
+    }
+    /**
+    Gets a mutable iterator over the values of the map, in no particular order
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut a = ArrayHashMap::<[Inner<(i32, String)>; 10]>::new();
+    a.insert(1, String::from("hello"));
+    a.insert(2, String::from("goodbye"));
+
+    for value in a.values_mut() {
+        value.push_str("!");
+    }
+
+    assert_eq!(a[&1], "hello!");
+    assert_eq!(a[&2], "goodbye!");
+    ```
+    */
+    pub fn values_mut(&mut self) -> ValuesMut<'_, A::Key, A::Value> {
+        ValuesMut {
+            iter: self.iter_mut(),
+        }
+    }
+    /// Splits the map into a consuming iterator over its entries and its hasher
+    pub(crate) fn into_parts(self) -> (IntoIter<A>, S) {
+        let map = ManuallyDrop::new(self);
+        let entries = IntoIter {
+            array: unsafe { ptr::read(&map.array) },
+            occupied: map.occupied,
+        };
+        (entries, unsafe { ptr::read(&map.hasher) })
+    }
+}
+
+impl<A, S> ArrayHashMap<A, S>
+where
+    A: MapArray,
+    A::Key: Hash + Eq,
+    S: BuildHasher,
+{
+    fn home_of<Q>(&self, key: &Q) -> usize
+    where
+        Q: Hash + ?Sized,
+    {
+        home(hash(&self.hasher, key), A::CAPACITY)
+    }
+    fn find<Q>(&self, key: &Q) -> Result<usize, Option<usize>>
+    where
+        A::Key: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        probe(
+            self.array.as_slice(),
+            self.occupied,
+            self.home_of(key),
+            |(k, _)| k.borrow() == key,
+        )
+    }
+    /**
+    Inserts a key-value pair into the map
+
+    If the map did not have this key present, None is returned.
+
+    If the map did have this key present, the value is updated, and the old value is returned.
+
+    # Panics
+
+    Panics if insertion would cause the map to excede its capacity.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = ArrayHashMap::<[Inner<(i32, &str)>; 10]>::new();
+    assert_eq!(map.insert(37, "a"), None);
+    assert_eq!(map.is_empty(), false);
+
+    map.insert(37, "b");
+    assert_eq!(map.insert(37, "c"), Some("b"));
+    assert_eq!(map[&37], "c");
+    ```
+    */
+    pub fn insert(&mut self, key: A::Key, value: A::Value) -> Option<A::Value> {
+        self.try_insert(key, value)
+            .unwrap_or_else(|_| panic!("Insertion would excede capacity"))
+    }
+    /**
+    Attempts to insert a key-value pair into the map
+
+    If the map did not have this key present, None is returned.
+
+    If the map did have this key present, the value is updated, and the old value is returned.
+
+    # Errors
+
+    If insertion would cause the map to excede its capacity, this function returns an error containing
+    the key-value pair that could not be inserted.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = ArrayHashMap::<[Inner<(i32, &str)>; 3]>::new();
+    assert!(map.try_insert(37, "a").is_ok());
+    assert!(map.try_insert(2, "b").is_ok());
+    assert!(map.try_insert(16, "c").is_ok());
+    assert!(map.try_insert(0, "d").is_err());
+    ```
+    */
+    pub fn try_insert(
+        &mut self,
+        key: A::Key,
+        value: A::Value,
+    ) -> Result<Option<A::Value>, (A::Key, A::Value)> {
+        match self.find(&key) {
+            Ok(i) => {
+                let entry = unsafe { self.array.as_mut_slice()[i].as_mut_ptr().as_mut() }.unwrap();
+                Ok(Some(replace(&mut entry.1, value)))
+            }
+            Err(Some(i)) if self.len < self.capacity() => {
+                self.array.as_mut_slice()[i] = Inner::new((key, value));
+                self.occupied |= 1 << i;
+                self.len += 1;
+                Ok(None)
+            }
+            Err(_) => Err((key, value)),
+        }
+    }
+    /**
+    Returns a reference to the value corresponding to the key
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = ArrayHashMap::<[Inner<(i32, &str)>; 10]>::new();
+    map.insert(1, "a");
+    assert_eq!(map.get(&1), Some(&"a"));
+    assert_eq!(map.get(&2), None);
+    ```
+    */
+    pub fn get<Q>(&self, key: &Q) -> Option<&A::Value>
+    where
+        A::Key: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_key_value(key).map(|(_, value)| value)
+    }
+    /**
+    Returns the key-value pair corresponding to the supplied key
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = ArrayHashMap::<[Inner<(i32, &str)>; 10]>::new();
+    map.insert(1, "a");
+    assert_eq!(map.get_key_value(&1), Some((&1, &"a")));
+    assert_eq!(map.get_key_value(&2), None);
+    ```
+    */
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&A::Key, &A::Value)>
+    where
+        A::Key: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(key).ok().map(|i| {
+            let entry = unsafe { self.array.as_slice()[i].as_ptr().as_ref() }.unwrap();
+            (&entry.0, &entry.1)
+        })
+    }
+    /**
+    Returns `true` if the map contains a value for the specified key
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = ArrayHashMap::<[Inner<(i32, &str)>; 10]>::new();
+    map.insert(1, "a");
+    assert_eq!(map.contains_key(&1), true);
+    assert_eq!(map.contains_key(&2), false);
+    ```
+    */
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        A::Key: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(key).is_ok()
+    }
+    /**
+    Returns a mutable reference to the value corresponding to the key
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = ArrayHashMap::<[Inner<(i32, &str)>; 10]>::new();
+    map.insert(1, "a");
+    if let Some(x) = map.get_mut(&1) {
+        *x = "b";
+    }
+    assert_eq!(map[&1], "b");
+    ```
+    */
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut A::Value>
+    where
+        A::Key: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let i = self.find(key).ok()?;
+        Some(
+            &mut unsafe { self.array.as_mut_slice()[i].as_mut_ptr().as_mut() }
+                .unwrap()
+                .1,
+        )
+    }
+    /**
+    Removes a key from the map, returning the value at the key if the key was previously in the map
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = ArrayHashMap::<[Inner<(i32, &str)>; 10]>::new();
+    map.insert(1, "a");
+    assert_eq!(map.remove(&1), Some("a"));
+    assert_eq!(map.remove(&1), None);
+    ```
+    */
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<A::Value>
+    where
+        A::Key: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+    /**
+    Removes a key from the map, returning the stored key and value if the key was previously in the map
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = ArrayHashMap::<[Inner<(i32, &str)>; 10]>::new();
+    map.insert(1, "a");
+    assert_eq!(map.remove_entry(&1), Some((1, "a")));
+    assert_eq!(map.remove_entry(&1), None);
+    ```
+    */
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(A::Key, A::Value)>
+    where
+        A::Key: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let i = self.find(key).ok()?;
+        self.len -= 1;
+        let hasher = &self.hasher;
+        Some(take_slot(
+            self.array.as_mut_slice(),
+            &mut self.occupied,
+            i,
+            |(k, _)| home(hash(hasher, k), A::CAPACITY),
+        ))
+    }
+    /**
+    Retains only the elements specified by the predicate
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map: ArrayHashMap<[Inner<(i32, i32)>; 10]> = (0..8).map(|x| (x, x * 10)).collect();
+    map.retain(|&k, _| k % 2 == 0);
+    assert_eq!(map.len(), 4);
+    assert!(map.keys().all(|k| k % 2 == 0));
+    ```
+    */
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&A::Key, &mut A::Value) -> bool,
+    {
+        // Removing an entry can move unvisited entries into earlier slots, so track them by bit
+        let mut unvisited = self.occupied;
+        while unvisited != 0 {
+            let i = unvisited.trailing_zeros() as usize;
+            unvisited &= !(1 << i);
+            let (key, value) =
+                unsafe { self.array.as_mut_slice()[i].as_mut_ptr().as_mut() }.unwrap();
+            if !f(key, value) {
+                self.len -= 1;
+                let hasher = &self.hasher;
+                take_slot_tracked(
+                    self.array.as_mut_slice(),
+                    &mut self.occupied,
+                    &mut unvisited,
+                    i,
+                    |(k, _)| home(hash(hasher, k), A::CAPACITY),
+                );
+            }
+        }
+    }
+}
+
+impl<A, S, Q> Index<&Q> for ArrayHashMap<A, S>
+where
+    A: MapArray,
+    A::Key: Hash + Eq + Borrow<Q>,
+    Q: Hash + Eq + ?Sized,
+    S: BuildHasher,
+{
+    type Output = A::Value;
+    fn index(&self, key: &Q) -> &Self::Output {
+        self.get(key)
+            .unwrap_or_else(|| panic!("No entry found for key"))
+    }
+}
+
+impl<A, S> fmt::Debug for ArrayHashMap<A, S>
+where
+    A: MapArray,
+    A::Key: fmt::Debug,
+    A::Value: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<A, S> PartialEq for ArrayHashMap<A, S>
+where
+    A: MapArray,
+    A::Key: Hash + Eq,
+    A::Value: PartialEq,
+    S: BuildHasher,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len
+            && self
+                .iter()
+                .all(|(key, value)| other.get(key) == Some(value))
+    }
+}
+
+impl<A, S> Eq for ArrayHashMap<A, S>
+where
+    A: MapArray,
+    A::Key: Hash + Eq,
+    A::Value: Eq,
+    S: BuildHasher,
+{
+}
+
+impl<'a, A, S> IntoIterator for &'a ArrayHashMap<A, S>
+where
+    A: MapArray,
+{
+    type Item = (&'a A::Key, &'a A::Value);
+    type IntoIter = Iter<'a, A::Key, A::Value>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, A, S> IntoIterator for &'a mut ArrayHashMap<A, S>
+where
+    A: MapArray,
+{
+    type Item = (&'a A::Key, &'a mut A::Value);
+    type IntoIter = IterMut<'a, A::Key, A::Value>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<A, S> IntoIterator for ArrayHashMap<A, S>
+where
+    A: MapArray,
+{
+    type Item = (A::Key, A::Value);
+    type IntoIter = IntoIter<A>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_parts().0
+    }
+}
+
+/// Elements from the iterator beyond the map's capacity will be discarded.
+impl<A, S> FromIterator<(A::Key, A::Value)> for ArrayHashMap<A, S>
+where
+    A: MapArray,
+    A::Key: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (A::Key, A::Value)>,
+    {
+        let mut map = ArrayHashMap::default();
+        map.extend(iter);
+        map
+    }
+}
+
+/// Elements from the iterator beyond the map's capacity will be discarded.
+impl<A, S> Extend<(A::Key, A::Value)> for ArrayHashMap<A, S>
+where
+    A: MapArray,
+    A::Key: Hash + Eq,
+    S: BuildHasher,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = (A::Key, A::Value)>,
+    {
+        for (k, v) in iter {
+            if self.try_insert(k, v).is_err() {
+                break;
+            }
+        }
+    }
+}
+
+impl<A, S> Drop for ArrayHashMap<A, S>
+where
+    A: MapArray,
+{
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+/// A consuming iterator over the key-value pairs in an ArrayHashMap
+pub struct IntoIter<A>
+where
+    A: MapArray,
+{
+    array: A,
+    occupied: u64,
+}
+
+impl<A> Iterator for IntoIter<A>
+where
+    A: MapArray,
+{
+    type Item = (A::Key, A::Value);
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.occupied == 0 {
+            return None;
+        }
+        let i = self.occupied.trailing_zeros() as usize;
+        self.occupied &= !(1 << i);
+        Some(unsafe { self.array.as_slice()[i].as_ptr().read() })
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.occupied.count_ones() as usize;
+        (len, Some(len))
+    }
+}
+
+impl<A> Drop for IntoIter<A>
+where
+    A: MapArray,
+{
+    fn drop(&mut self) {
+        self.for_each(drop);
+    }
+}
+
+/// An iterator over references to the key-value pairs in an ArrayHashMap
+pub struct Iter<'a, K, V> {
+    iter: slice::Iter<'a, Inner<(K, V)>>,
+    occupied: u64,
+}
+
+impl<'a, K, V> Clone for Iter<'a, K, V> {
+    fn clone(&self) -> Self {
+        Iter {
+            iter: self.iter.clone(),
+            occupied: self.occupied,
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.occupied != 0 {
+            let entry = self.iter.next()?;
+            let occupied = self.occupied & 1 == 1;
+            self.occupied >>= 1;
+            if occupied {
+                let pair = unsafe { entry.as_ptr().as_ref() }.unwrap();
+                return Some((&pair.0, &pair.1));
+            }
+        }
+        None
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.occupied.count_ones() as usize;
+        (len, Some(len))
+    }
+}
+
+/// An iterator over references to keys and mutable references to values in an ArrayHashMap
+pub struct IterMut<'a, K, V> {
+    iter: slice::IterMut<'a, Inner<(K, V)>>,
+    occupied: u64,
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V>
+where
+    K: 'a,
+    V: 'a,
+{
+    type Item = (&'a K, &'a mut V);
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.occupied != 0 {
+            let entry = self.iter.next()?;
+            let occupied = self.occupied & 1 == 1;
+            self.occupied >>= 1;
+            if occupied {
+                let pair = unsafe { entry.as_mut_ptr().as_mut() }.unwrap();
+                return Some((&pair.0, &mut pair.1));
+            }
+        }
+        None
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.occupied.count_ones() as usize;
+        (len, Some(len))
+    }
+}
+
+/// An iterator over references to the keys in an ArrayHashMap
+pub struct Keys<'a, K, V> {
+    iter: Iter<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for Keys<'a, K, V> {
+    type Item = &'a K;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(key, _)| key)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+/// An iterator over references to the values in an ArrayHashMap
+pub struct Values<'a, K, V> {
+    iter: Iter<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(_, value)| value)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+/// An iterator over mutable references to the values in an ArrayHashMap
+pub struct ValuesMut<'a, K, V> {
+    iter: IterMut<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for ValuesMut<'a, K, V>
+where
+    K: 'a,
+    V: 'a,
+{
+    type Item = &'a mut V;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(_, value)| value)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
@@ -0,0 +1,610 @@
+//! An array-backed hash set that uses open addressing
+
+use core::{
+    borrow::Borrow,
+    fmt,
+    hash::{BuildHasher, Hash},
+    iter::FromIterator,
+    mem::{replace, zeroed, ManuallyDrop},
+    ptr, slice,
+};
+
+use crate::{
+    array_hash_map::{
+        hash, home, is_occupied, max_load, probe, take_slot, take_slot_tracked, CheckCapacity,
+        DefaultHashBuilder,
+    },
+    Array, Inner,
+};
+
+/**
+An array-backed hash set that uses open addressing
+
+ArrayHashSet wraps an array of values like an [`ArraySet`](../array_set/struct.ArraySet.html),
+but it places them by their hashes instead of keeping them sorted. Its values only need
+to implement `Hash` and `Eq`, and lookups take constant time on average. Its values are in no
+particular order.
+
+Collisions are resolved with linear probing. The set never uses the heap, and its hasher is chosen
+with the `S` parameter, which defaults to [`DefaultHashBuilder`](../array_hash_map/type.DefaultHashBuilder.html).
+
+The array's capacity may be at most 64. To keep probing short, the set is full once seven eighths
+of its slots are taken, so arrays of 8 or more slots hold fewer values than they have slots.
+*/
+pub struct ArrayHashSet<A, S = DefaultHashBuilder>
+where
+    A: Array,
+{
+    array: A,
+    // Bit `i` is set if slot `i` holds a value
+    occupied: u64,
+    len: usize,
+    hasher: S,
+}
+
+impl<A, S> Default for ArrayHashSet<A, S>
+where
+    A: Array,
+    S: Default,
+{
+    fn default() -> Self {
+        ArrayHashSet::with_hasher(S::default())
+    }
+}
+
+impl<A, S> Clone for ArrayHashSet<A, S>
+where
+    A: Array,
+    A::Item: Clone,
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        let mut array: A = unsafe { zeroed() };
+        for (i, slot) in self.array.as_slice().iter().enumerate() {
+            if is_occupied(self.occupied, i) {
+                let value = unsafe { slot.as_ptr().as_ref() }.unwrap();
+                array.as_mut_slice()[i] = Inner::new(value.clone());
+            }
+        }
+        ArrayHashSet {
+            array,
+            occupied: self.occupied,
+            len: self.len,
+            hasher: self.hasher.clone(),
+        }
+    }
+}
+
+impl<A> ArrayHashSet<A>
+where
+    A: Array,
+{
+    /**
+    Creates a new empty ArrayHashSet
+
+    # Example
+    ```
+    use tinymap::*;
+
+    let mut set = ArrayHashSet::<[Inner<i32>; 10]>::new();
+    ```
+    */
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<A, S> ArrayHashSet<A, S>
+where
+    A: Array,
+{
+    /**
+    Creates a new empty ArrayHashSet that hashes its values with the given hasher
+
+    # Example
+    ```
+    use std::collections::hash_map::RandomState;
+    use tinymap::*;
+
+    let mut set = ArrayHashSet::<[Inner<i32>; 10], _>::with_hasher(RandomState::new());
+    set.insert(1);
+    ```
+    */
+    pub fn with_hasher(hasher: S) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = CheckCapacity::<A>::SET;
+        ArrayHashSet {
+            array: unsafe { zeroed() },
+            occupied: 0,
+            len: 0,
+            hasher,
+        }
+    }
+    /**
+    Returns a reference to the set's hasher
+    */
+    pub fn hasher(&self) -> &S {
+        &self.hasher
+    }
+    /**
+    Clears the set, removing all elements
+
+    # Example
+    ```
+    use tinymap::*;
+
+    let mut v = ArrayHashSet::<[Inner<i32>; 10]>::new();
+    v.insert(1);
+    v.clear();
+    assert!(v.is_empty());
+    ```
+    */
+    pub fn clear(&mut self) {
+        let occupied = replace(&mut self.occupied, 0);
+        self.len = 0;
+        for (i, slot) in self.array.as_mut_slice().iter_mut().enumerate() {
+            if is_occupied(occupied, i) {
+                unsafe { slot.as_mut_ptr().drop_in_place() };
+            }
+        }
+    }
+    /**
+    Returns the number of elements in the set
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut v = ArrayHashSet::<[Inner<i32>; 10]>::new();
+    assert_eq!(v.len(), 0);
+    v.insert(1);
+    assert_eq!(v.len(), 1);
+    ```
+    */
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    /**
+    Returns `true` if the set contains no elements
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut v = ArrayHashSet::<[Inner<i32>; 10]>::new();
+    assert!(v.is_empty());
+    v.insert(1);
+    assert!(!v.is_empty());
+    ```
+    */
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /**
+    Returns the maximum number of elements the set can contain
+
+    This is seven eighths of the array's capacity, rounded up.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut v = ArrayHashSet::<[Inner<i32>; 10]>::new();
+    assert_eq!(9, v.capacity());
+    ```
+    */
+    pub fn capacity(&self) -> usize {
+        max_load(A::CAPACITY)
+    }
+    /**
+    Gets an iterator over the values of the set, in no particular order
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut set = ArrayHashSet::<[Inner<i32>; 10]>::new();
+    set.insert(3);
+    set.insert(1);
+    set.insert(2);
+
+    let mut values = set.iter().copied().collect::<Vec<_>>();
+    values.sort();
+    assert_eq!(values, [1, 2, 3]);
+    ```
+    */
+    pub fn iter(&self) -> Iter<'_, A::Item> {
+        Iter {
+            iter: self.array.as_slice().iter(),
+            occupied: self.occupied,
+        }
+    }
+}
+
+impl<A, S> ArrayHashSet<A, S>
+where
+    A: Array,
+    A::Item: Hash + Eq,
+    S: BuildHasher,
+{
+    fn find<Q>(&self, value: &Q) -> Result<usize, Option<usize>>
+    where
+        A::Item: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        probe(
+            self.array.as_slice(),
+            self.occupied,
+            home(hash(&self.hasher, value), A::CAPACITY),
+            |v| v.borrow() == value,
+        )
+    }
+    /**
+    Adds a value to the set
+
+    If the set did not have this value present, `true` is returned.
+
+    If the set did have this value present, `false` is returned.
+
+    # Panics
+
+    Panics if insertion would cause the set to excede its capacity.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut set = ArrayHashSet::<[Inner<i32>; 10]>::new();
+
+    assert_eq!(set.insert(2), true);
+    assert_eq!(set.insert(2), false);
+    assert_eq!(set.len(), 1);
+    ```
+    */
+    pub fn insert(&mut self, value: A::Item) -> bool {
+        self.try_insert(value)
+            .unwrap_or_else(|_| panic!("Insertion would excede capacity"))
+    }
+    /**
+    Attempts to add a value to the set
+
+    If the set did not have this value present, `true` is returned.
+
+    If the set did have this value present, `false` is returned.
+
+    # Errors
+
+    If insertion would cause the set to excede its capacity, this function returns an error containing
+    the value that could not be inserted.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut set = ArrayHashSet::<[Inner<i32>; 3]>::new();
+    assert!(set.try_insert(37).is_ok());
+    assert!(set.try_insert(2).is_ok());
+    assert!(set.try_insert(16).is_ok());
+    assert!(set.try_insert(0).is_err());
+    ```
+    */
+    pub fn try_insert(&mut self, value: A::Item) -> Result<bool, A::Item> {
+        match self.find(&value) {
+            Ok(_) => Ok(false),
+            Err(Some(i)) if self.len < self.capacity() => {
+                self.array.as_mut_slice()[i] = Inner::new(value);
+                self.occupied |= 1 << i;
+                self.len += 1;
+                Ok(true)
+            }
+            Err(_) => Err(value),
+        }
+    }
+    /**
+    Returns `true` if the set contains a value
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut set = ArrayHashSet::<[Inner<i32>; 10]>::new();
+    set.insert(1);
+    assert_eq!(set.contains(&1), true);
+    assert_eq!(set.contains(&4), false);
+    ```
+    */
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        A::Item: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(value).is_ok()
+    }
+    /**
+    Returns a reference to the value in the set, if any, that is equal to the given value
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut set = ArrayHashSet::<[Inner<i32>; 10]>::new();
+    set.insert(1);
+    assert_eq!(set.get(&1), Some(&1));
+    assert_eq!(set.get(&4), None);
+    ```
+    */
+    pub fn get<Q>(&self, value: &Q) -> Option<&A::Item>
+    where
+        A::Item: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let i = self.find(value).ok()?;
+        unsafe { self.array.as_slice()[i].as_ptr().as_ref() }
+    }
+    /**
+    Removes a value from the set. Returns whether the value was present in the set.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut set = ArrayHashSet::<[Inner<i32>; 10]>::new();
+    set.insert(2);
+    assert_eq!(set.remove(&2), true);
+    assert_eq!(set.remove(&2), false);
+    ```
+    */
+    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    where
+        A::Item: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.take(value).is_some()
+    }
+    /**
+    Removes and returns the value in the set, if any, that is equal to the given one
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut set = ArrayHashSet::<[Inner<i32>; 10]>::new();
+    set.insert(2);
+    assert_eq!(set.take(&2), Some(2));
+    assert_eq!(set.take(&2), None);
+    ```
+    */
+    pub fn take<Q>(&mut self, value: &Q) -> Option<A::Item>
+    where
+        A::Item: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let i = self.find(value).ok()?;
+        self.len -= 1;
+        let hasher = &self.hasher;
+        Some(take_slot(
+            self.array.as_mut_slice(),
+            &mut self.occupied,
+            i,
+            |v| home(hash(hasher, v), A::CAPACITY),
+        ))
+    }
+    /**
+    Retains only the elements specified by the predicate
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut set: ArrayHashSet<[Inner<i32>; 10]> = (0..8).collect();
+    set.retain(|&k| k % 2 == 0);
+    assert_eq!(set.len(), 4);
+    assert!(set.iter().all(|k| k % 2 == 0));
+    ```
+    */
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&A::Item) -> bool,
+    {
+        // Removing a value can move unvisited values into earlier slots, so track them by bit
+        let mut unvisited = self.occupied;
+        while unvisited != 0 {
+            let i = unvisited.trailing_zeros() as usize;
+            unvisited &= !(1 << i);
+            if !f(unsafe { self.array.as_slice()[i].as_ptr().as_ref() }.unwrap()) {
+                self.len -= 1;
+                let hasher = &self.hasher;
+                take_slot_tracked(
+                    self.array.as_mut_slice(),
+                    &mut self.occupied,
+                    &mut unvisited,
+                    i,
+                    |v| home(hash(hasher, v), A::CAPACITY),
+                );
+            }
+        }
+    }
+}
+
+impl<A, S> fmt::Debug for ArrayHashSet<A, S>
+where
+    A: Array,
+    A::Item: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<A, S> PartialEq for ArrayHashSet<A, S>
+where
+    A: Array,
+    A::Item: Hash + Eq,
+    S: BuildHasher,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().all(|value| other.contains(value))
+    }
+}
+
+impl<A, S> Eq for ArrayHashSet<A, S>
+where
+    A: Array,
+    A::Item: Hash + Eq,
+    S: BuildHasher,
+{
+}
+
+impl<'a, A, S> IntoIterator for &'a ArrayHashSet<A, S>
+where
+    A: Array,
+{
+    type Item = &'a A::Item;
+    type IntoIter = Iter<'a, A::Item>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<A, S> IntoIterator for ArrayHashSet<A, S>
+where
+    A: Array,
+{
+    type Item = A::Item;
+    type IntoIter = IntoIter<A>;
+    fn into_iter(self) -> Self::IntoIter {
+        let set = ManuallyDrop::new(self);
+        drop(unsafe { ptr::read(&set.hasher) });
+        IntoIter {
+            array: unsafe { ptr::read(&set.array) },
+            occupied: set.occupied,
+        }
+    }
+}
+
+/// Elements from the iterator beyond the set's capacity will be discarded.
+impl<A, S> FromIterator<A::Item> for ArrayHashSet<A, S>
+where
+    A: Array,
+    A::Item: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = A::Item>,
+    {
+        let mut set = ArrayHashSet::default();
+        set.extend(iter);
+        set
+    }
+}
+
+/// Elements from the iterator beyond the set's capacity will be discarded.
+impl<A, S> Extend<A::Item> for ArrayHashSet<A, S>
+where
+    A: Array,
+    A::Item: Hash + Eq,
+    S: BuildHasher,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = A::Item>,
+    {
+        for value in iter {
+            if self.try_insert(value).is_err() {
+                break;
+            }
+        }
+    }
+}
+
+impl<A, S> Drop for ArrayHashSet<A, S>
+where
+    A: Array,
+{
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+/// A consuming iterator over the values in an ArrayHashSet
+pub struct IntoIter<A>
+where
+    A: Array,
+{
+    array: A,
+    occupied: u64,
+}
+
+impl<A> Iterator for IntoIter<A>
+where
+    A: Array,
+{
+    type Item = A::Item;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.occupied == 0 {
+            return None;
+        }
+        let i = self.occupied.trailing_zeros() as usize;
+        self.occupied &= !(1 << i);
+        Some(unsafe { self.array.as_slice()[i].as_ptr().read() })
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.occupied.count_ones() as usize;
+        (len, Some(len))
+    }
+}
+
+impl<A> Drop for IntoIter<A>
+where
+    A: Array,
+{
+    fn drop(&mut self) {
+        self.for_each(drop);
+    }
+}
+
+/// An iterator over references to the values in an ArrayHashSet
+pub struct Iter<'a, T> {
+    iter: slice::Iter<'a, Inner<T>>,
+    occupied: u64,
+}
+
+impl<'a, T> Clone for Iter<'a, T> {
+    fn clone(&self) -> Self {
+        Iter {
+            iter: self.iter.clone(),
+            occupied: self.occupied,
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.occupied != 0 {
+            let value = self.iter.next()?;
+            let occupied = self.occupied & 1 == 1;
+            self.occupied >>= 1;
+            if occupied {
+                return unsafe { value.as_ptr().as_ref() };
+            }
+        }
+        None
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.occupied.count_ones() as usize;
+        (len, Some(len))
+    }
+}
@@ -1,46 +1,66 @@
 //! An array-backed, map-like data structure
 
 use core::{
-    borrow::Borrow,
+    cmp::Ordering,
+    convert::Infallible,
     fmt,
     iter::FromIterator,
-    mem::{replace, swap, zeroed},
-    ops::Index,
+    mem::{replace, swap, zeroed, ManuallyDrop},
+    ops::{
+        Bound::{self, Excluded, Included, Unbounded},
+        Deref, DerefMut, Index,
+    },
+    ptr, slice,
 };
 
-use crate::{Inner, MapArray};
+use crate::{
+    compare::{Compare, Natural},
+    diff::Diff,
+    join::{Join, JoinItem, LeftJoin, OuterJoin},
+    CapacityError, EmptyStorage, Inner, MapArray, MapStorage,
+};
+
+#[cfg(feature = "alloc")]
+use crate::{Array, Spill, SpillHeap};
 
 /**
 An array-backed, map-like data structure
 
 ArrayMap wraps an array of key-value pairs and supports operation similar to a BTreeMap or HashMap.
 It has a fixed capacity, but it keeps track of how many pairs have been inserted and removed.
+
+Keys are kept sorted by the comparator `C`, which defaults to the keys' `Ord` implementation.
+See the [`compare`](../compare/index.html) module for details.
+
+The entries can also be kept in a borrowed slice, a boxed slice or a growing vector instead of an array.
+See [`SliceMap`](type.SliceMap.html), [`BoxedArrayMap`](type.BoxedArrayMap.html),
+[`VecMap`](type.VecMap.html) and [`TinyVecMap`](type.TinyVecMap.html).
 */
-pub struct ArrayMap<A>
+pub struct ArrayMap<A, C = Natural>
 where
-    A: MapArray,
+    A: MapStorage,
 {
     array: A,
     len: usize,
+    cmp: C,
 }
 
-impl<A> Default for ArrayMap<A>
+impl<A, C> Default for ArrayMap<A, C>
 where
     A: MapArray,
+    C: Default,
 {
     fn default() -> Self {
-        ArrayMap {
-            array: unsafe { zeroed() },
-            len: 0,
-        }
+        ArrayMap::with_comparator(C::default())
     }
 }
 
-impl<A> Clone for ArrayMap<A>
+impl<A, C> Clone for ArrayMap<A, C>
 where
     A: MapArray,
     A::Key: Clone,
     A::Value: Clone,
+    C: Clone,
 {
     fn clone(&self) -> Self {
         let mut array: A = unsafe { zeroed() };
@@ -48,7 +68,11 @@ where
         for (i, (k, v)) in self.iter().enumerate() {
             array.as_mut_slice()[i] = Inner::new((k.clone(), v.clone()));
         }
-        ArrayMap { array, len }
+        ArrayMap {
+            array,
+            len,
+            cmp: self.cmp.clone(),
+        }
     }
 }
 
@@ -57,230 +81,1700 @@ where
     A: MapArray,
 {
     /**
-    Returns the number of elements in the map
+    Creates a new empty ArrayMap
 
     # Example
-
     ```
     use tinymap::*;
 
-    let mut a = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
-    assert_eq!(a.len(), 0);
-    a.insert(1, "a");
-    assert_eq!(a.len(), 1);
+    let mut map = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
+
+    // entries can now be inserted into the empty map
+    map.insert(1, "a");
     ```
     */
-    pub fn len(&self) -> usize {
-        self.len
+    pub fn new() -> Self {
+        Self::default()
     }
-    /**
-    Returns `true` if the map contains no elements
+}
 
-    # Example
+/**
+An ArrayMap that keeps its entries in a borrowed slice
 
-    ```
-    use tinymap::*;
+This allows the storage to live outside of the map, such as in a buffer in a specific linker section.
+The slice's capacity is its length, and any values already in it are ignored.
 
-    let mut a = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
-    assert!(a.is_empty());
-    a.insert(1, "a");
-    assert!(!a.is_empty());
-    ```
-    */
-    pub fn is_empty(&self) -> bool {
-        self.len == 0
+A SliceMap supports the same operations as an ArrayMap, except for those that have to create
+new storage, like `clone`, `split_off` and `into_iter`.
+
+# Example
+
+```
+use tinymap::*;
+
+let mut storage = [Inner::<(i32, &str)>::uninit(); 4];
+let mut map = SliceMap::new_in(&mut storage);
+map.insert(2, "b");
+map.insert(1, "a");
+
+assert_eq!(map.capacity(), 4);
+assert_eq!(map.keys().copied().collect::<Vec<_>>(), [1, 2]);
+```
+*/
+pub type SliceMap<'a, K, V, C = Natural> = ArrayMap<&'a mut [Inner<(K, V)>], C>;
+
+impl<'a, K, V> SliceMap<'a, K, V> {
+    /// Creates a new empty SliceMap that keeps its entries in the given slice
+    pub fn new_in(storage: &'a mut [Inner<(K, V)>]) -> Self {
+        SliceMap::with_comparator_in(storage, Natural)
     }
-    /**
-    Creates a new empty ArrayMap
+}
 
-    # Example
-    ```
-    use tinymap::*;
+impl<'a, K, V, C> SliceMap<'a, K, V, C> {
+    /// Creates a new empty SliceMap that keeps its entries in the given slice and orders its keys with the given comparator
+    pub fn with_comparator_in(storage: &'a mut [Inner<(K, V)>], cmp: C) -> Self {
+        ArrayMap {
+            array: storage,
+            len: 0,
+            cmp,
+        }
+    }
+}
 
-    let mut map = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
+/**
+An ArrayMap that keeps its entries in a boxed slice with a capacity chosen at runtime
 
-    // entries can now be inserted into the empty map
-    map.insert(1, "a");
-    ```
-    */
+The storage is allocated once, when the map is created, and never grows.
+Like an ArrayMap, inserting beyond the capacity panics, or returns an error with `try_insert`.
+
+A BoxedArrayMap supports the same operations as an ArrayMap.
+
+# Example
+
+```
+use tinymap::*;
+
+let mut map = BoxedArrayMap::with_capacity(2);
+map.insert(2, "b");
+map.insert(1, "a");
+
+assert_eq!(map.capacity(), 2);
+assert_eq!(map.try_insert(3, "c"), Err((3, "c")));
+assert_eq!(map.into_iter().collect::<Vec<_>>(), [(1, "a"), (2, "b")]);
+```
+*/
+#[cfg(feature = "alloc")]
+pub type BoxedArrayMap<K, V, C = Natural> = ArrayMap<Box<[Inner<(K, V)>]>, C>;
+
+#[cfg(feature = "alloc")]
+impl<K, V> BoxedArrayMap<K, V> {
+    /// Creates a new empty BoxedArrayMap that can hold `capacity` entries
+    pub fn with_capacity(capacity: usize) -> Self {
+        BoxedArrayMap::with_capacity_and_comparator(capacity, Natural)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K, V, C> BoxedArrayMap<K, V, C> {
+    /// Creates a new empty BoxedArrayMap that can hold `capacity` entries and orders its keys with the given comparator
+    pub fn with_capacity_and_comparator(capacity: usize, cmp: C) -> Self {
+        ArrayMap {
+            array: {
+                let mut slots = Vec::with_capacity(capacity);
+                slots.resize_with(capacity, Inner::uninit);
+                slots.into_boxed_slice()
+            },
+            len: 0,
+            cmp,
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K, V, C> Clone for BoxedArrayMap<K, V, C>
+where
+    K: Clone,
+    V: Clone,
+    C: Clone,
+{
+    fn clone(&self) -> Self {
+        let mut map =
+            BoxedArrayMap::with_capacity_and_comparator(self.capacity(), self.cmp.clone());
+        for (key, value) in self.iter() {
+            map.array[map.len] = Inner::new((key.clone(), value.clone()));
+            map.len += 1;
+        }
+        map
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K, V, C> IntoIterator for BoxedArrayMap<K, V, C> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+    fn into_iter(mut self) -> Self::IntoIter {
+        let array = replace(&mut self.array, Box::new([]));
+        let len = replace(&mut self.len, 0);
+        let mut vec = array.into_vec();
+        vec.truncate(len);
+        IntoIter {
+            iter: vec.into_iter(),
+        }
+    }
+}
+
+/**
+An ArrayMap that keeps its entries in a `Vec` and grows instead of running out of capacity
+
+A VecMap is a sorted vector of key-value pairs that is searched with a binary search.
+It suits maps that are too big for an array but small enough that shifting entries on insertion
+is cheaper than the allocations and pointer chasing of a BTreeMap.
+
+Inserting into a VecMap never fails, so `try_insert` always succeeds. Otherwise it supports
+the same operations as an ArrayMap.
+
+# Example
+
+```
+use tinymap::*;
+
+let mut map = VecMap::new();
+for i in (0..100).rev() {
+    map.insert(i, i * 2);
+}
+
+assert_eq!(map.len(), 100);
+assert_eq!(map.get(&21), Some(&42));
+assert_eq!(map.keys().take(3).copied().collect::<Vec<_>>(), [0, 1, 2]);
+```
+*/
+#[cfg(feature = "alloc")]
+pub type VecMap<K, V, C = Natural> = ArrayMap<Vec<Inner<(K, V)>>, C>;
+
+#[cfg(feature = "alloc")]
+impl<K, V> VecMap<K, V> {
+    /// Creates a new empty VecMap
     pub fn new() -> Self {
         Self::default()
     }
-    /**
-    Clears the map, removing all elements
+    /// Creates a new empty VecMap with room for at least `capacity` entries
+    pub fn with_capacity(capacity: usize) -> Self {
+        VecMap::with_capacity_and_comparator(capacity, Natural)
+    }
+}
 
-    # Example
-    ```
-    use tinymap::*;
+#[cfg(feature = "alloc")]
+impl<K, V, C> VecMap<K, V, C> {
+    /// Creates a new empty VecMap with room for at least `capacity` entries that orders its keys with the given comparator
+    pub fn with_capacity_and_comparator(capacity: usize, cmp: C) -> Self {
+        let mut array = Vec::new();
+        array.try_grow(capacity);
+        ArrayMap { array, len: 0, cmp }
+    }
+    /// Reserves room for at least `additional` more entries
+    pub fn reserve(&mut self, additional: usize) {
+        self.array.try_grow(self.len + additional);
+    }
+    /// Shrinks the capacity of the map as much as possible
+    pub fn shrink_to_fit(&mut self) {
+        self.array.truncate(self.len);
+        self.array.shrink_to_fit();
+    }
+    /// Moves the entries of another map into a new VecMap with the same comparator, leaving the other map empty
+    pub(crate) fn take_from<A>(map: &mut ArrayMap<A, C>) -> Self
+    where
+        A: MapArray<Key = K, Value = V>,
+        C: Clone,
+    {
+        let mut vec = VecMap::with_capacity_and_comparator(map.len, map.cmp.clone());
+        for i in 0..map.len {
+            swap(
+                &mut map.array.as_mut_slice()[i],
+                &mut vec.array.as_mut_slice()[i],
+            );
+        }
+        vec.len = replace(&mut map.len, 0);
+        vec
+    }
+}
 
-    let mut a = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
-    a.insert(1, "a");
-    a.clear();
-    assert!(a.is_empty());
-    ```
-    */
-    pub fn clear(&mut self) {
-        *self = Self::new();
+#[cfg(feature = "alloc")]
+impl<K, V, C> Default for VecMap<K, V, C>
+where
+    C: Default,
+{
+    fn default() -> Self {
+        VecMap::with_capacity_and_comparator(0, C::default())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K, V, C> Clone for VecMap<K, V, C>
+where
+    K: Clone,
+    V: Clone,
+    C: Clone,
+{
+    fn clone(&self) -> Self {
+        let mut map = VecMap::with_capacity_and_comparator(self.len, self.cmp.clone());
+        for (key, value) in self.iter() {
+            map.array[map.len] = Inner::new((key.clone(), value.clone()));
+            map.len += 1;
+        }
+        map
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K, V, C> IntoIterator for VecMap<K, V, C> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+    fn into_iter(mut self) -> Self::IntoIter {
+        let mut vec = core::mem::take(&mut self.array);
+        vec.truncate(replace(&mut self.len, 0));
+        IntoIter {
+            iter: vec.into_iter(),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K, V, C> FromIterator<(K, V)> for VecMap<K, V, C>
+where
+    C: Compare<K> + Default,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let mut map = VecMap::default();
+        map.extend(iter);
+        map
+    }
+}
+
+/**
+An ArrayMap that keeps its entries in an inline array and moves them into a `Vec` when it grows beyond it
+
+This is an alternative to a [`TinyMap`](../tiny_map/enum.TinyMap.html), which moves into a BTreeMap.
+Because the entries stay in a single sorted slice, a TinyVecMap supports the same operations as an ArrayMap,
+whether or not it has spilled. Like a VecMap, inserting into it never fails.
+
+# Example
+
+```
+use tinymap::*;
+
+let mut map = TinyVecMap::<[Inner<(i32, &str)>; 2]>::new();
+map.insert(2, "b");
+map.insert(1, "a");
+assert!(!map.is_spilled());
+
+map.insert(3, "c");
+assert!(map.is_spilled());
+assert_eq!(map.into_iter().collect::<Vec<_>>(), [(1, "a"), (2, "b"), (3, "c")]);
+```
+*/
+#[cfg(feature = "alloc")]
+pub type TinyVecMap<A, C = Natural, H = Vec<Inner<<A as crate::Storage>::Item>>> =
+    ArrayMap<Spill<A, H>, C>;
+
+#[cfg(feature = "alloc")]
+impl<K, V, A> TinyVecMap<A>
+where
+    A: Array<Item = (K, V)>,
+{
+    /// Creates a new empty TinyVecMap
+    pub fn new() -> Self {
+        Self::default()
     }
+}
+
+#[cfg(feature = "alloc")]
+impl<K, V, A, C, H> TinyVecMap<A, C, H>
+where
+    A: Array<Item = (K, V)>,
+    H: SpillHeap<Item = (K, V)>,
+{
     /**
-    Returns the maximum number of elements the map can contain
+    Creates a new empty TinyVecMap that starts on the heap
 
-    # Example
+    This skips moving the entries from the array to the heap when the map is expected to grow beyond its capacity.
 
+    # Example
     ```
     use tinymap::*;
 
-    let mut a = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
-    assert_eq!(10, a.capacity());
+    let mut map = TinyVecMap::<[Inner<(i32, &str)>; 2]>::with_heap();
+    assert!(map.is_spilled());
+    map.insert(1, "a");
+    assert!(map.is_spilled());
     ```
     */
-    pub fn capacity(&self) -> usize {
-        A::CAPACITY
-    }
-    fn find<Q>(&self, key: &Q) -> Result<usize, usize>
+    pub fn with_heap() -> Self
     where
-        A::Key: Borrow<Q>,
-        Q: Ord + ?Sized,
+        H::Alloc: Default,
+        C: Default,
     {
-        self.array.as_slice()[..self.len].binary_search_by_key(&key.borrow(), |entry| {
-            unsafe { entry.as_ptr().as_ref() }.unwrap().0.borrow()
-        })
+        ArrayMap {
+            array: Spill::Heap(H::new_in(H::Alloc::default())),
+            len: 0,
+            cmp: C::default(),
+        }
     }
     /**
-    Returns true if the map contains a value for the specified key
+    Creates a new empty TinyVecMap that moves its entries into the given allocator when it grows beyond its array
 
     # Example
-
     ```
-    use tinymap::*;
+    #![feature(allocator_api)]
+    use std::alloc::System;
+    use tinymap::{compare::Natural, *};
 
-    let mut map = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
+    let mut map = TinyVecMap::<[Inner<(i32, &str)>; 2], Natural, Vec<Inner<(i32, &str)>, System>>::new_in(System);
     map.insert(1, "a");
-    assert_eq!(map.contains_key(&1), true);
-    assert_eq!(map.contains_key(&2), false);
+    assert!(!map.is_spilled());
+    map.extend(vec![(1, "a"), (2, "b"), (3, "c")]);
+    assert!(map.is_spilled());
     ```
     */
-    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    #[cfg(feature = "allocator_api")]
+    pub fn new_in(alloc: H::Alloc) -> Self
     where
-        A::Key: Borrow<Q>,
-        Q: Ord + ?Sized,
+        C: Default,
     {
-        self.find(key).is_ok()
+        ArrayMap {
+            array: Spill::new_in(alloc),
+            len: 0,
+            cmp: C::default(),
+        }
     }
     /**
-    Returns a reference to the value corresponding to the key
+    Creates a new empty TinyVecMap that starts on the heap in the given allocator
 
     # Example
-
     ```
-    use tinymap::*;
+    #![feature(allocator_api)]
+    use std::alloc::System;
+    use tinymap::{compare::Natural, *};
 
-    let mut map = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
+    let mut map = TinyVecMap::<[Inner<(i32, &str)>; 2], Natural, Vec<Inner<(i32, &str)>, System>>::with_heap_in(System);
+    assert!(map.is_spilled());
     map.insert(1, "a");
-    assert_eq!(map.get(&1), Some(&"a"));
-    assert_eq!(map.get(&2), None);
+    assert_eq!(map.len(), 1);
     ```
     */
-    pub fn get<Q>(&self, key: &Q) -> Option<&A::Value>
+    #[cfg(feature = "allocator_api")]
+    pub fn with_heap_in(alloc: H::Alloc) -> Self
     where
-        A::Key: Borrow<Q>,
-        Q: Ord + ?Sized,
+        C: Default,
     {
-        if let Ok(i) = self.find(key) {
-            Some(
-                &unsafe { self.array.as_slice()[i].as_ptr().as_ref() }
-                    .unwrap()
-                    .1,
-            )
-        } else {
-            None
+        ArrayMap {
+            array: Spill::Heap(H::new_in(alloc)),
+            len: 0,
+            cmp: C::default(),
         }
     }
     /**
-    Returns a mutable reference to the value corresponding to the key
+    Returns `true` if the entries have moved into a `Vec`
 
     # Example
 
     ```
     use tinymap::*;
 
-    let mut map = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
+    let mut map = TinyVecMap::<[Inner<(i32, &str)>; 2]>::new();
     map.insert(1, "a");
-    if let Some(x) = map.get_mut(&1) {
-        *x = "b";
-    }
-    assert_eq!(map[&1], "b");
+    assert!(!map.is_spilled());
+    map.extend(vec![(1, "a"), (2, "b"), (3, "c")]);
+    assert!(map.is_spilled());
     ```
     */
-    pub fn get_mut<'a, Q>(&'a mut self, key: &Q) -> Option<&'a mut A::Value>
-    where
-        A::Key: Borrow<Q> + 'a,
-        Q: Ord + ?Sized,
-    {
-        if let Ok(i) = self.find(key) {
-            Some(
-                &mut unsafe { self.array.as_mut_slice()[i].as_mut_ptr().as_mut() }
-                    .unwrap()
-                    .1,
-            )
-        } else {
-            None
-        }
+    pub fn is_spilled(&self) -> bool {
+        self.array.is_spilled()
     }
     /**
-    Gets an iterator over the entries of the map, sorted by key
+    Moves the entries into a `Vec` if they are not there already
 
     # Example
 
     ```
     use tinymap::*;
 
-    let mut map = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
-    map.insert(3, "c");
-    map.insert(2, "b");
+    let mut map = TinyVecMap::<[Inner<(i32, &str)>; 2]>::new();
+    map.insert(1, "a");
+    map.spill();
+    assert!(map.is_spilled());
+    assert_eq!(map.len(), 1);
+    ```
+    */
+    pub fn spill(&mut self) {
+        self.array.spill(A::CAPACITY);
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K, V, A, C, H> Default for TinyVecMap<A, C, H>
+where
+    A: Array<Item = (K, V)>,
+    H: SpillHeap<Item = (K, V)>,
+    H::Alloc: Default,
+    C: Default,
+{
+    fn default() -> Self {
+        ArrayMap {
+            array: Spill::default(),
+            len: 0,
+            cmp: C::default(),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K, V, A, C, H> Clone for TinyVecMap<A, C, H>
+where
+    A: Array<Item = (K, V)>,
+    H: SpillHeap<Item = (K, V)>,
+    K: Clone,
+    V: Clone,
+    C: Clone,
+{
+    fn clone(&self) -> Self {
+        let mut map = ArrayMap {
+            array: self.array.empty_like(),
+            len: 0,
+            cmp: self.cmp.clone(),
+        };
+        map.array.try_grow(self.len);
+        for (key, value) in self.iter() {
+            map.array.as_mut_slice()[map.len] = Inner::new((key.clone(), value.clone()));
+            map.len += 1;
+        }
+        map
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K, V, A, C, H> IntoIterator for TinyVecMap<A, C, H>
+where
+    A: Array<Item = (K, V)>,
+    H: SpillHeap<Item = (K, V)>,
+{
+    type Item = (K, V);
+    type IntoIter = IntoEntries<Spill<A, H>>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_entries()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K, V, A, C, H> FromIterator<(K, V)> for TinyVecMap<A, C, H>
+where
+    A: Array<Item = (K, V)>,
+    H: SpillHeap<Item = (K, V)>,
+    H::Alloc: Default,
+    C: Compare<K> + Default,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let mut map = TinyVecMap::default();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<A, C> ArrayMap<A, C>
+where
+    A: MapStorage,
+{
+    /**
+    Returns the number of elements in the map
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut a = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
+    assert_eq!(a.len(), 0);
+    a.insert(1, "a");
+    assert_eq!(a.len(), 1);
+    ```
+    */
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    /**
+    Returns `true` if the map contains no elements
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut a = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
+    assert!(a.is_empty());
+    a.insert(1, "a");
+    assert!(!a.is_empty());
+    ```
+    */
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /**
+    Creates a new empty ArrayMap that orders its keys with the given comparator
+
+    # Example
+    ```
+    use tinymap::{compare::{Natural, Reverse}, *};
+
+    let mut map = ArrayMap::<[Inner<(i32, &str)>; 10], _>::with_comparator(Reverse(Natural));
+    map.insert(1, "a");
+    map.insert(2, "b");
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [2, 1]);
+    ```
+    */
+    pub fn with_comparator(cmp: C) -> Self
+    where
+        A: MapArray,
+    {
+        ArrayMap {
+            array: unsafe { zeroed() },
+            len: 0,
+            cmp,
+        }
+    }
+    /**
+    Returns a reference to the map's comparator
+    */
+    pub fn comparator(&self) -> &C {
+        &self.cmp
+    }
+    #[cfg(feature = "alloc")]
+    pub(crate) fn storage(&self) -> &A {
+        &self.array
+    }
+    /**
+    Clears the map, removing all elements
+
+    # Example
+    ```
+    use tinymap::*;
+
+    let mut a = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
+    a.insert(1, "a");
+    a.clear();
+    assert!(a.is_empty());
+    ```
+    */
+    pub fn clear(&mut self) {
+        let len = replace(&mut self.len, 0);
+        for entry in &mut self.array.as_mut_slice()[..len] {
+            unsafe { entry.as_mut_ptr().drop_in_place() };
+        }
+    }
+    /**
+    Returns the maximum number of elements the map can contain
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut a = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
+    assert_eq!(10, a.capacity());
+    ```
+    */
+    pub fn capacity(&self) -> usize {
+        self.array.capacity()
+    }
+    fn find<Q>(&self, key: &Q) -> Result<usize, usize>
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Key>,
+    {
+        self.as_slice()
+            .binary_search_by(|(k, _)| self.cmp.compare(key, k).reverse())
+    }
+    /**
+    Returns true if the map contains a value for the specified key
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
+    map.insert(1, "a");
+    assert_eq!(map.contains_key(&1), true);
+    assert_eq!(map.contains_key(&2), false);
+    ```
+    */
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Key>,
+    {
+        self.find(key).is_ok()
+    }
+    /**
+    Returns a reference to the value corresponding to the key
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
+    map.insert(1, "a");
+    assert_eq!(map.get(&1), Some(&"a"));
+    assert_eq!(map.get(&2), None);
+    ```
+    */
+    pub fn get<Q>(&self, key: &Q) -> Option<&A::Value>
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Key>,
+    {
+        if let Ok(i) = self.find(key) {
+            Some(
+                &unsafe { self.array.as_slice()[i].as_ptr().as_ref() }
+                    .unwrap()
+                    .1,
+            )
+        } else {
+            None
+        }
+    }
+    /**
+    Returns the stored key and a reference to the value corresponding to the key
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
+    map.insert(1, "a");
+    assert_eq!(map.get_key_value(&1), Some((&1, &"a")));
+    assert_eq!(map.get_key_value(&2), None);
+    ```
+    */
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&A::Key, &A::Value)>
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Key>,
+    {
+        if let Ok(i) = self.find(key) {
+            self.get_index(i)
+        } else {
+            None
+        }
+    }
+    /**
+    Returns a mutable reference to the value corresponding to the key
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
+    map.insert(1, "a");
+    if let Some(x) = map.get_mut(&1) {
+        *x = "b";
+    }
+    assert_eq!(map[&1], "b");
+    ```
+    */
+    pub fn get_mut<'a, Q>(&'a mut self, key: &Q) -> Option<&'a mut A::Value>
+    where
+        A::Key: 'a,
+        Q: ?Sized,
+        C: Compare<Q, A::Key>,
+    {
+        if let Ok(i) = self.find(key) {
+            Some(
+                &mut unsafe { self.array.as_mut_slice()[i].as_mut_ptr().as_mut() }
+                    .unwrap()
+                    .1,
+            )
+        } else {
+            None
+        }
+    }
+    /**
+    Returns mutable references to the values corresponding to several keys at once
+
+    Returns `None` if any of the keys is missing or if two of them refer to the same entry.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut balances = ArrayMap::<[Inner<(&str, u32)>; 10]>::new();
+    balances.insert("alice", 100);
+    balances.insert("bob", 20);
+
+    if let Some([from, to]) = balances.get_many_mut([&"alice", &"bob"]) {
+        *from -= 30;
+        *to += 30;
+    }
+    assert_eq!(balances["alice"], 70);
+    assert_eq!(balances["bob"], 50);
+
+    assert!(balances.get_many_mut([&"alice", &"alice"]).is_none());
+    assert!(balances.get_many_mut([&"alice", &"carol"]).is_none());
+    ```
+    */
+    pub fn get_many_mut<Q, const N: usize>(&mut self, keys: [&Q; N]) -> Option<[&mut A::Value; N]>
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Key>,
+    {
+        let indices = self.find_many(keys)?;
+        for (i, index) in indices.iter().enumerate() {
+            if indices[..i].contains(index) {
+                return None;
+            }
+        }
+        Some(unsafe { self.values_at_unchecked(indices) })
+    }
+    /**
+    Returns mutable references to the values corresponding to several keys at once,
+    without checking that the keys refer to different entries
+
+    Returns `None` if any of the keys is missing.
+
+    # Safety
+
+    Calling this with two keys that refer to the same entry is undefined behavior,
+    even if the returned references are not used.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = ArrayMap::<[Inner<(i32, i32)>; 10]>::new();
+    map.insert(1, 10);
+    map.insert(2, 20);
+
+    let [a, b] = unsafe { map.get_many_unchecked_mut([&1, &2]) }.unwrap();
+    std::mem::swap(a, b);
+    assert_eq!(map[&1], 20);
+    ```
+    */
+    pub unsafe fn get_many_unchecked_mut<Q, const N: usize>(
+        &mut self,
+        keys: [&Q; N],
+    ) -> Option<[&mut A::Value; N]>
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Key>,
+    {
+        let indices = self.find_many(keys)?;
+        Some(self.values_at_unchecked(indices))
+    }
+    fn find_many<Q, const N: usize>(&self, keys: [&Q; N]) -> Option<[usize; N]>
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Key>,
+    {
+        let mut indices = [0; N];
+        for (index, key) in indices.iter_mut().zip(keys.iter()) {
+            *index = self.find(*key).ok()?;
+        }
+        Some(indices)
+    }
+    /// The indices must be distinct and less than the map's length
+    unsafe fn values_at_unchecked<const N: usize>(
+        &mut self,
+        indices: [usize; N],
+    ) -> [&mut A::Value; N] {
+        let entries = self.array.as_mut_slice().as_mut_ptr();
+        indices.map(|i| &mut (*(*entries.add(i)).as_mut_ptr()).1)
+    }
+    /**
+    Gets an iterator over the entries of the map, sorted by key
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
+    map.insert(3, "c");
+    map.insert(2, "b");
     map.insert(1, "a");
 
     for (key, value) in map.iter() {
         println!("{}: {}", key, value);
     }
 
-    let (first_key, first_value) = map.iter().next().unwrap();
-    assert_eq!((*first_key, *first_value), (1, "a"));
+    let (first_key, first_value) = map.iter().next().unwrap();
+    assert_eq!((*first_key, *first_value), (1, "a"));
+    ```
+    */
+    pub fn iter(&self) -> Iter<'_, A::Key, A::Value> {
+        Iter {
+            iter: self.array.as_slice()[..self.len].iter(),
+        }
+    }
+    /**
+    Gets a mutable iterator over the entries of the map, sorted by key
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = ArrayMap::<[Inner<(&str, i32)>; 10]>::new();
+    map.insert("a", 1);
+    map.insert("b", 2);
+    map.insert("c", 3);
+
+    // add 10 to the value if the key isn't "a"
+    for (key, value) in map.iter_mut() {
+        if key != &"a" {
+            *value += 10;
+        }
+    }
+    ```
+    */
+    pub fn iter_mut(&mut self) -> IterMut<'_, A::Key, A::Value> {
+        IterMut {
+            iter: self.array.as_mut_slice()[..self.len].iter_mut(),
+        }
+    }
+    /**
+    Gets an iterator over the keys of the map, sorted
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut a = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
+    a.insert(2, "b");
+    a.insert(1, "a");
+
+    let keys: Vec<_> = a.keys().cloned().collect();
+    assert_eq!(keys, [1, 2]);
+    ```
+    */
+    pub fn keys(&self) -> Keys<'_, A::Key, A::Value> {
+        Keys {
+            iter: self.array.as_slice()[..self.len].iter(),
+        }
+    }
+    /**
+    Gets an iterator over the values of the map, sorted
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut a = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
+    a.insert(1, "hello");
+    a.insert(2, "goodbye");
+
+    let values: Vec<&str> = a.values().cloned().collect();
+    assert_eq!(values, ["hello", "goodbye"]);
+    ```
+    */
+    pub fn values(&self) -> Values<'_, A::Key, A::Value> {
+        Values {
+            iter: self.array.as_slice()[..self.len].iter(),
+        }
+    }
+    /**
+    Gets a mutable iterator over the values of the map, sorted
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut a = ArrayMap::<[Inner<(i32, String)>; 10]>::new();
+    a.insert(1, String::from("hello"));
+    a.insert(2, String::from("goodbye"));
+
+    for value in a.values_mut() {
+        value.push_str("!");
+    }
+
+    let values: Vec<String> = a.values().cloned().collect();
+    assert_eq!(values, [String::from("hello!"),
+                        String::from("goodbye!")]);
+    ```
+    */
+    pub fn values_mut(&mut self) -> ValuesMut<'_, A::Key, A::Value> {
+        ValuesMut {
+            iter: self.array.as_mut_slice()[..self.len].iter_mut(),
+        }
+    }
+    /**
+    Creates a consuming iterator over the keys of the map, sorted
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut a = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
+    a.insert(2, "b");
+    a.insert(1, "a");
+
+    let keys: Vec<i32> = a.into_keys().collect();
+    assert_eq!(keys, [1, 2]);
+    ```
+    */
+    pub fn into_keys(self) -> IntoKeys<A> {
+        IntoKeys {
+            iter: self.into_entries(),
+        }
+    }
+    /**
+    Creates a consuming iterator over the values of the map, sorted by key
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut a = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
+    a.insert(1, "hello");
+    a.insert(2, "goodbye");
+
+    let values: Vec<&str> = a.into_values().collect();
+    assert_eq!(values, ["hello", "goodbye"]);
+    ```
+    */
+    pub fn into_values(self) -> IntoValues<A> {
+        IntoValues {
+            iter: self.into_entries(),
+        }
+    }
+    /**
+    Gets the map's entries as a slice of key-value pairs, sorted by key
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
+    map.insert(2, "b");
+    map.insert(1, "a");
+    assert_eq!(map.as_slice(), &[(1, "a"), (2, "b")]);
+    ```
+    */
+    pub fn as_slice(&self) -> &[(A::Key, A::Value)] {
+        unsafe {
+            slice::from_raw_parts(
+                self.array.as_slice().as_ptr() as *const (A::Key, A::Value),
+                self.len,
+            )
+        }
+    }
+    /**
+    Returns a reference to the key-value pair at the given position in the map's sort order
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
+    map.insert(3, "c");
+    map.insert(1, "a");
+    map.insert(2, "b");
+    assert_eq!(map.get_index(0), Some((&1, &"a")));
+    assert_eq!(map.get_index(2), Some((&3, &"c")));
+    assert_eq!(map.get_index(3), None);
+    ```
+    */
+    pub fn get_index(&self, index: usize) -> Option<(&A::Key, &A::Value)> {
+        if index < self.len {
+            let pair = unsafe { self.array.as_slice()[index].as_ptr().as_ref() }.unwrap();
+            Some((&pair.0, &pair.1))
+        } else {
+            None
+        }
+    }
+    /**
+    Returns a reference to the key and a mutable reference to the value at the given position
+    in the map's sort order
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
+    map.insert(2, "b");
+    map.insert(1, "a");
+    if let Some((_, value)) = map.get_index_mut(1) {
+        *value = "c";
+    }
+    assert_eq!(map[&2], "c");
+    ```
+    */
+    pub fn get_index_mut(&mut self, index: usize) -> Option<(&A::Key, &mut A::Value)> {
+        if index < self.len {
+            let pair = unsafe { self.array.as_mut_slice()[index].as_mut_ptr().as_mut() }.unwrap();
+            Some((&pair.0, &mut pair.1))
+        } else {
+            None
+        }
+    }
+    /**
+    Removes the key-value pair at the given position in the map's sort order and returns it
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
+    map.insert(2, "b");
+    map.insert(1, "a");
+    assert_eq!(map.remove_index(0), Some((1, "a")));
+    assert_eq!(map.remove_index(1), None);
+    assert_eq!(map.len(), 1);
+    ```
+    */
+    pub fn remove_index(&mut self, index: usize) -> Option<(A::Key, A::Value)> {
+        if index < self.len {
+            Some(self.remove_at(index))
+        } else {
+            None
+        }
+    }
+    fn remove_at(&mut self, i: usize) -> (A::Key, A::Value) {
+        let slice = self.array.as_mut_slice();
+        let mut entry = Inner::uninit();
+        swap(&mut entry, &mut slice[i]);
+        for j in (i + 1)..self.len {
+            slice.swap(j - 1, j);
+        }
+        self.len -= 1;
+        unsafe { entry.assume_init() }
+    }
+    /**
+    Returns the position of the key in the map's sort order, if it is present
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
+    map.insert(10, "a");
+    map.insert(20, "b");
+    assert_eq!(map.index_of(&20), Some(1));
+    assert_eq!(map.index_of(&15), None);
+    ```
+    */
+    pub fn index_of<Q>(&self, key: &Q) -> Option<usize>
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Key>,
+    {
+        self.find(key).ok()
+    }
+    /**
+    Returns the number of keys in the map that are less than the given key
+
+    The key does not have to be present in the map.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
+    map.insert(10, "a");
+    map.insert(20, "b");
+    map.insert(30, "c");
+    assert_eq!(map.rank(&5), 0);
+    assert_eq!(map.rank(&20), 1);
+    assert_eq!(map.rank(&25), 2);
+    ```
+    */
+    pub fn rank<Q>(&self, key: &Q) -> usize
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Key>,
+    {
+        match self.find(key) {
+            Ok(i) | Err(i) => i,
+        }
+    }
+    /**
+    Returns the position of the first entry for which the predicate returns `false`
+
+    The map is assumed to be partitioned by the predicate, meaning that it returns `true`
+    for every entry before some position and `false` for every entry from that position onward.
+    This is usually the case for predicates that only look at the key.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
+    map.insert(1, "a");
+    map.insert(4, "b");
+    map.insert(9, "c");
+    assert_eq!(map.partition_point(|key, _| key * key < 20), 2);
+    ```
+    */
+    pub fn partition_point<P>(&self, mut pred: P) -> usize
+    where
+        P: FnMut(&A::Key, &A::Value) -> bool,
+    {
+        self.array.as_slice()[..self.len].partition_point(|entry| {
+            let pair = unsafe { entry.as_ptr().as_ref() }.unwrap();
+            pred(&pair.0, &pair.1)
+        })
+    }
+    fn lower_bound_index<Q>(&self, bound: Bound<&Q>) -> usize
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Key>,
+    {
+        match bound {
+            Included(key) => match self.find(key) {
+                Ok(i) | Err(i) => i,
+            },
+            Excluded(key) => match self.find(key) {
+                Ok(i) => i + 1,
+                Err(i) => i,
+            },
+            Unbounded => 0,
+        }
+    }
+    fn upper_bound_index<Q>(&self, bound: Bound<&Q>) -> usize
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Key>,
+    {
+        match bound {
+            Included(key) => match self.find(key) {
+                Ok(i) => i + 1,
+                Err(i) => i,
+            },
+            Excluded(key) => match self.find(key) {
+                Ok(i) | Err(i) => i,
+            },
+            Unbounded => self.len,
+        }
+    }
+    /**
+    Returns a cursor pointing at the gap before the smallest key above the given bound
+
+    Passing `Bound::Included(x)` points the cursor at the gap before the smallest key
+    greater than or equal to `x`, and `Bound::Excluded(x)` at the gap before the smallest key
+    greater than `x`. `Bound::Unbounded` points the cursor at the gap before the smallest key in the map.
+
+    # Example
+
+    ```
+    use core::ops::Bound;
+    use tinymap::*;
+
+    let mut map = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
+    map.insert(1, "a");
+    map.insert(2, "b");
+    map.insert(3, "c");
+
+    let cursor = map.lower_bound(Bound::Included(&2));
+    assert_eq!(cursor.peek_prev(), Some((&1, &"a")));
+    assert_eq!(cursor.peek_next(), Some((&2, &"b")));
+
+    let cursor = map.lower_bound(Bound::Excluded(&2));
+    assert_eq!(cursor.peek_prev(), Some((&2, &"b")));
+    assert_eq!(cursor.peek_next(), Some((&3, &"c")));
+    ```
+    */
+    pub fn lower_bound<Q>(&self, bound: Bound<&Q>) -> Cursor<'_, A::Key, A::Value>
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Key>,
+    {
+        Cursor {
+            entries: self.as_slice(),
+            index: self.lower_bound_index(bound),
+        }
+    }
+    /**
+    Returns a mutable cursor pointing at the gap before the smallest key above the given bound
+
+    See [`lower_bound`](#method.lower_bound) for how the bound is interpreted.
+
+    # Example
+
+    ```
+    use core::ops::Bound;
+    use tinymap::*;
+
+    let mut map = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
+    map.insert(1, "a");
+    map.insert(2, "b");
+    map.insert(3, "c");
+
+    let mut cursor = map.lower_bound_mut(Bound::Included(&2));
+    assert_eq!(cursor.remove_next(), Some((2, "b")));
+    assert_eq!(cursor.peek_next(), Some((&3, &mut "c")));
+    assert_eq!(map.len(), 2);
+    ```
+    */
+    pub fn lower_bound_mut<Q>(&mut self, bound: Bound<&Q>) -> CursorMut<'_, A, C>
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Key>,
+    {
+        let index = self.lower_bound_index(bound);
+        CursorMut { map: self, index }
+    }
+    /**
+    Returns a cursor pointing at the gap after the greatest key below the given bound
+
+    Passing `Bound::Included(x)` points the cursor at the gap after the greatest key
+    less than or equal to `x`, and `Bound::Excluded(x)` at the gap after the greatest key
+    less than `x`. `Bound::Unbounded` points the cursor at the gap after the greatest key in the map.
+
+    # Example
+
+    ```
+    use core::ops::Bound;
+    use tinymap::*;
+
+    let mut map = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
+    map.insert(1, "a");
+    map.insert(2, "b");
+    map.insert(3, "c");
+
+    let cursor = map.upper_bound(Bound::Included(&2));
+    assert_eq!(cursor.peek_prev(), Some((&2, &"b")));
+    assert_eq!(cursor.peek_next(), Some((&3, &"c")));
+
+    let cursor = map.upper_bound(Bound::Excluded(&2));
+    assert_eq!(cursor.peek_prev(), Some((&1, &"a")));
+    assert_eq!(cursor.peek_next(), Some((&2, &"b")));
+    ```
+    */
+    pub fn upper_bound<Q>(&self, bound: Bound<&Q>) -> Cursor<'_, A::Key, A::Value>
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Key>,
+    {
+        Cursor {
+            entries: self.as_slice(),
+            index: self.upper_bound_index(bound),
+        }
+    }
+    /**
+    Returns a mutable cursor pointing at the gap after the greatest key below the given bound
+
+    See [`upper_bound`](#method.upper_bound) for how the bound is interpreted.
+
+    # Example
+
+    ```
+    use core::ops::Bound;
+    use tinymap::*;
+
+    let mut map = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
+    map.insert(1, "a");
+    map.insert(3, "c");
+
+    let mut cursor = map.upper_bound_mut(Bound::Included(&1));
+    assert!(cursor.insert_before(0, "z").is_err());
+    assert!(cursor.insert_before(2, "b").is_ok());
+    assert_eq!(cursor.peek_prev(), Some((&2, &mut "b")));
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [1, 2, 3]);
     ```
     */
-    pub fn iter(&self) -> Iter<'_, A::Key, A::Value> {
-        Iter {
-            iter: self.array.as_slice()[..self.len].iter(),
+    pub fn upper_bound_mut<Q>(&mut self, bound: Bound<&Q>) -> CursorMut<'_, A, C>
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Key>,
+    {
+        let index = self.upper_bound_index(bound);
+        CursorMut { map: self, index }
+    }
+    /**
+    Moves the entries into a map with a different array type, panicking if they do not fit
+
+    The entries are moved in order, without any searching.
+
+    # Panics
+
+    Panics if the map has more entries than the new array's capacity
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = ArrayMap::<[Inner<(i32, &str)>; 2]>::new();
+    map.insert(1, "a");
+    map.insert(2, "b");
+
+    let mut bigger = map.resize_into::<[Inner<(i32, &str)>; 4]>();
+    bigger.insert(3, "c");
+    assert_eq!(bigger.len(), 3);
+    ```
+    */
+    pub fn resize_into<B>(self) -> ArrayMap<B, C>
+    where
+        B: MapArray<Key = A::Key, Value = A::Value>,
+    {
+        match self.try_convert() {
+            Ok(map) => map,
+            Err(_) => panic!("Conversion would excede capacity"),
+        }
+    }
+    /**
+    Moves the entries into a map with a different array type
+
+    Returns the map unchanged if its entries do not fit in the new array.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = ArrayMap::<[Inner<(i32, &str)>; 4]>::new();
+    map.insert(1, "a");
+    map.insert(2, "b");
+    map.insert(3, "c");
+
+    let map = map.try_convert::<[Inner<(i32, &str)>; 2]>().unwrap_err();
+    assert_eq!(map.len(), 3);
+    let map = map.try_convert::<[Inner<(i32, &str)>; 3]>().ok().unwrap();
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [1, 2, 3]);
+    ```
+    */
+    pub fn try_convert<B>(self) -> Result<ArrayMap<B, C>, Self>
+    where
+        B: MapArray<Key = A::Key, Value = A::Value>,
+    {
+        if self.len > B::CAPACITY {
+            return Err(self);
+        }
+        let mut map = ManuallyDrop::new(self);
+        let mut new = ArrayMap::<B, C>::with_comparator(unsafe { ptr::read(&map.cmp) });
+        unsafe {
+            ptr::copy_nonoverlapping(
+                map.array.as_slice().as_ptr(),
+                new.array.as_mut_slice().as_mut_ptr(),
+                map.len,
+            );
+            ptr::drop_in_place(&mut map.array);
+        }
+        new.len = map.len;
+        Ok(new)
+    }
+    fn into_entries(self) -> IntoEntries<A> {
+        self.into_parts().0
+    }
+    fn into_parts(self) -> (IntoEntries<A>, C) {
+        let map = ManuallyDrop::new(self);
+        let entries = IntoEntries {
+            array: unsafe { ptr::read(&map.array) },
+            index: 0,
+            len: map.len,
+        };
+        (entries, unsafe { ptr::read(&map.cmp) })
+    }
+}
+
+impl<A, C> ArrayMap<A, C>
+where
+    A: MapArray + Copy,
+    C: Copy,
+{
+    /**
+    Get a copy of this map
+
+    Because of the way its internals work, ArrayMap must implement `Drop`, so it cannot implement `Copy`.
+
+    However, that does not make a copy any less trivial with the right array type, hence this function.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = ArrayMap::<[Inner<(&str, i32)>; 10]>::new();
+    map.insert("a", 1);
+    map.insert("b", 2);
+    map.insert("c", 3);
+
+    let copy = map.copy();
+    assert_eq!(3, copy.len());
+    ```
+    */
+    pub fn copy(&self) -> Self {
+        ArrayMap {
+            array: self.array,
+            len: self.len,
+            cmp: self.cmp,
+        }
+    }
+}
+
+impl<A, C> ArrayMap<A, C>
+where
+    A: MapStorage,
+    C: Compare<A::Key>,
+{
+    /**
+    Inserts a key-value pair into the map
+
+    If the map did not have this key present, None is returned.
+
+    If the map did have this key present, the key and value are updated, and the old value is returned.
+
+    # Panics
+
+    Panics if insertion would cause the map to excede its capacity.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
+    assert_eq!(map.insert(37, "a"), None);
+    assert_eq!(map.is_empty(), false);
+
+    map.insert(37, "b");
+    assert_eq!(map.insert(37, "c"), Some("b"));
+    assert_eq!(map[&37], "c");
+    ```
+    */
+    pub fn insert(&mut self, key: A::Key, value: A::Value) -> Option<A::Value> {
+        self.try_insert(key, value)
+            .unwrap_or_else(|_| panic!("Insertion would excede capacity"))
+    }
+    /**
+    Attempts to insert a key-value pair into the map
+
+    If the map did not have this key present, None is returned.
+
+    If the map did have this key present, the key and value are updated, and the old value is returned.
+
+    # Errors
+
+    If insertion would cause the map to excede its capacity, this function returns an error containing
+    the key-value pair that could not be inserted.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = ArrayMap::<[Inner<(i32, &str)>; 3]>::new();
+    assert!(map.try_insert(37, "a").is_ok());
+    assert!(map.try_insert(2, "b").is_ok());
+    assert!(map.try_insert(16, "c").is_ok());
+    assert!(map.try_insert(0, "d").is_err());
+    ```
+    */
+    pub fn try_insert(
+        &mut self,
+        key: A::Key,
+        value: A::Value,
+    ) -> Result<Option<A::Value>, (A::Key, A::Value)> {
+        self.try_insert_index(key, value, None)
+    }
+    pub(crate) fn try_insert_index(
+        &mut self,
+        key: A::Key,
+        value: A::Value,
+        index: Option<usize>,
+    ) -> Result<Option<A::Value>, (A::Key, A::Value)> {
+        if !self.array.try_grow(self.len + 1) {
+            return Err((key, value));
+        }
+        let i = if let Some(index) = index {
+            Err(index)
+        } else {
+            self.find(&key)
+        };
+        match i {
+            Ok(i) => {
+                let mut entry = Inner::new((key, value));
+                swap(&mut entry, &mut self.array.as_mut_slice()[i]);
+                Ok(Some(unsafe { entry.assume_init() }.1))
+            }
+            Err(i) => {
+                let slice = self.array.as_mut_slice();
+                for j in ((i + 1)..=self.len).rev() {
+                    slice.swap(j - 1, j);
+                }
+                let mut entry = Inner::new((key, value));
+                swap(&mut entry, &mut slice[i]);
+                self.len += 1;
+                Ok(None)
+            }
+        }
+    }
+    /**
+    Removes a key from the map, returning the value at the key if the key was previously in the map
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
+    map.insert(1, "a");
+    assert_eq!(map.remove(&1), Some("a"));
+    assert_eq!(map.remove(&1), None);
+    ```
+    */
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<A::Value>
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Key>,
+    {
+        if let Ok(i) = self.find(key) {
+            Some(self.remove_at(i).1)
+        } else {
+            None
+        }
+    }
+    /**
+    Removes a key from the map, returning the stored key and value if the key was previously in the map
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
+    map.insert(1, "a");
+    assert_eq!(map.remove_entry(&1), Some((1, "a")));
+    assert_eq!(map.remove_entry(&1), None);
+    ```
+    */
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(A::Key, A::Value)>
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Key>,
+    {
+        if let Ok(i) = self.find(key) {
+            Some(self.remove_at(i))
+        } else {
+            None
+        }
+    }
+    /**
+    Returns a guard that gives mutable access to the stored key corresponding to the key
+
+    When the guard is dropped, the entry is moved to wherever its key now sorts. If the key
+    has become equal to another key in the map, the entry with the changed key replaces the
+    other entry, as [`insert`](#method.insert) would. Use [`KeyMut::finish`](struct.KeyMut.html#method.finish)
+    to get the entry back instead.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
+    map.insert(1, "a");
+    map.insert(2, "b");
+    map.insert(3, "c");
+
+    if let Some(mut key) = map.get_key_mut(&1) {
+        *key = 5;
+    }
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [2, 3, 5]);
+    assert_eq!(map[&5], "a");
+
+    ```
+    */
+    pub fn get_key_mut<Q>(&mut self, key: &Q) -> Option<KeyMut<'_, A, C>>
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Key>,
+    {
+        let index = self.find(key).ok()?;
+        Some(KeyMut { map: self, index })
+    }
+    /// Moves the entry at the given index to where its key sorts, returning its new index and
+    /// the index of the other entry as an error if their keys have become equal
+    fn restore_order(&mut self, mut i: usize) -> Result<(), (usize, usize)> {
+        while i > 0 && self.cmp.compare(self.key_at(i), self.key_at(i - 1)) == Ordering::Less {
+            self.array.as_mut_slice().swap(i - 1, i);
+            i -= 1;
+        }
+        while i + 1 < self.len
+            && self.cmp.compare(self.key_at(i), self.key_at(i + 1)) == Ordering::Greater
+        {
+            self.array.as_mut_slice().swap(i, i + 1);
+            i += 1;
+        }
+        if i > 0 && self.cmp.compare(self.key_at(i - 1), self.key_at(i)) == Ordering::Equal {
+            Err((i, i - 1))
+        } else if i + 1 < self.len
+            && self.cmp.compare(self.key_at(i), self.key_at(i + 1)) == Ordering::Equal
+        {
+            Err((i, i + 1))
+        } else {
+            Ok(())
         }
     }
+    fn key_at(&self, i: usize) -> &A::Key {
+        &self.as_slice()[i].0
+    }
     /**
-    Gets a mutable iterator over the entries of the map, sorted by key
+    Gets the given key's corresponding entry in the map for in-place manipulation
 
     # Example
 
     ```
     use tinymap::*;
 
-    let mut map = ArrayMap::<[Inner<(&str, i32)>; 10]>::new();
-    map.insert("a", 1);
-    map.insert("b", 2);
-    map.insert("c", 3);
+    let mut count = ArrayMap::<[Inner<(&str, i32)>; 10]>::new();
 
-    // add 10 to the value if the key isn't "a"
-    for (key, value) in map.iter_mut() {
-        if key != &"a" {
-            *value += 10;
-        }
+    // count the number of occurrences of letters in the vec
+    for x in vec!["a","b","a","c","a","b"] {
+        *count.entry(x).or_insert(0) += 1;
     }
+
+    assert_eq!(count["a"], 3);
     ```
     */
-    pub fn iter_mut(&mut self) -> IterMut<'_, A::Key, A::Value> {
-        IterMut {
-            iter: self.array.as_mut_slice()[..self.len].iter_mut(),
+    pub fn entry(&mut self, key: A::Key) -> Entry<'_, A, C> {
+        match self.find(&key) {
+            Ok(i) => Entry::Occupied(OccupiedEntry {
+                map: self,
+                index: i,
+            }),
+            Err(i) => Entry::Vacant(VacantEntry {
+                map: self,
+                index: i,
+                key,
+            }),
         }
     }
     /**
-    Gets an iterator over the keys of the map, sorted
+    Splits the map in two at the given key
+
+    Returns a map containing every entry with a key greater than or equal to the given key.
+    The entries with smaller keys stay in this map.
 
     # Example
 
@@ -288,20 +1782,52 @@ where
     use tinymap::*;
 
     let mut a = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
-    a.insert(2, "b");
     a.insert(1, "a");
+    a.insert(2, "b");
+    a.insert(3, "c");
+    a.insert(17, "d");
+    a.insert(41, "e");
 
-    let keys: Vec<_> = a.keys().cloned().collect();
-    assert_eq!(keys, [1, 2]);
+    let b = a.split_off(&3);
+
+    assert_eq!(a.len(), 2);
+    assert_eq!(b.len(), 3);
+    assert_eq!(b[&17], "d");
     ```
     */
-    pub fn keys(&self) -> Keys<'_, A::Key, A::Value> {
-        Keys {
-            iter: self.array.as_slice()[..self.len].iter(),
+    pub fn split_off<Q>(&mut self, key: &Q) -> Self
+    where
+        A: EmptyStorage,
+        Q: ?Sized,
+        C: Compare<Q, A::Key> + Clone,
+    {
+        let at = self.rank(key);
+        let mut other = ArrayMap {
+            array: self.array.empty_like(),
+            len: 0,
+            cmp: self.cmp.clone(),
+        };
+        if !other.array.try_grow(self.len - at) {
+            panic!("Split would excede capacity");
+        }
+        for i in at..self.len {
+            swap(
+                &mut self.array.as_mut_slice()[i],
+                &mut other.array.as_mut_slice()[i - at],
+            );
         }
+        other.len = self.len - at;
+        self.len = at;
+        other
     }
     /**
-    Gets an iterator over the values of the map, sorted
+    Moves all entries from `other` into this map, leaving `other` empty
+
+    If a key from `other` is already present in this map, the entry from `other` replaces it.
+
+    # Panics
+
+    Panics if appending would cause the map to excede its capacity.
 
     # Example
 
@@ -309,188 +1835,335 @@ where
     use tinymap::*;
 
     let mut a = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
-    a.insert(1, "hello");
-    a.insert(2, "goodbye");
+    a.insert(1, "a");
+    a.insert(2, "b");
+    a.insert(3, "c");
 
-    let values: Vec<&str> = a.values().cloned().collect();
-    assert_eq!(values, ["hello", "goodbye"]);
+    let mut b = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
+    b.insert(3, "d");
+    b.insert(4, "e");
+    b.insert(5, "f");
+
+    a.append(&mut b);
+
+    assert_eq!(a.len(), 5);
+    assert_eq!(b.len(), 0);
+    assert_eq!(a[&3], "d");
     ```
     */
-    pub fn values(&self) -> Values<'_, A::Key, A::Value> {
-        Values {
-            iter: self.array.as_slice()[..self.len].iter(),
-        }
+    pub fn append(&mut self, other: &mut Self) {
+        self.try_append(other)
+            .unwrap_or_else(|_| panic!("Append would excede capacity"))
     }
     /**
-    Gets a mutable iterator over the values of the map, sorted
+    Attempts to move all entries from `other` into this map, leaving `other` empty
+
+    If a key from `other` is already present in this map, the entry from `other` replaces it.
+
+    The two maps are merged in two linear passes: the first counts the merged entries, so that
+    nothing is moved if they do not fit.
+
+    # Errors
+
+    If appending would cause the map to excede its capacity, this function returns an error
+    and leaves both maps unchanged.
 
     # Example
 
     ```
     use tinymap::*;
 
-    let mut a = ArrayMap::<[Inner<(i32, String)>; 10]>::new();
-    a.insert(1, String::from("hello"));
-    a.insert(2, String::from("goodbye"));
+    let mut a = ArrayMap::<[Inner<(i32, &str)>; 3]>::new();
+    a.insert(1, "a");
+    a.insert(2, "b");
 
-    for value in a.values_mut() {
-        value.push_str("!");
-    }
+    let mut b = ArrayMap::<[Inner<(i32, &str)>; 3]>::new();
+    b.insert(2, "c");
+    b.insert(3, "d");
+    assert!(a.try_append(&mut b).is_ok());
+    assert_eq!(a.len(), 3);
 
-    let values: Vec<String> = a.values().cloned().collect();
-    assert_eq!(values, [String::from("hello!"),
-                        String::from("goodbye!")]);
+    b.insert(4, "e");
+    assert_eq!(a.try_append(&mut b), Err(CapacityError));
+    assert_eq!(b.len(), 1);
     ```
     */
-    pub fn values_mut(&mut self) -> ValuesMut<'_, A::Key, A::Value> {
-        ValuesMut {
-            iter: self.array.as_mut_slice()[..self.len].iter_mut(),
-        }
+    pub fn try_append(&mut self, other: &mut Self) -> Result<(), CapacityError> {
+        self.merge(other, |_, theirs| theirs)
     }
-}
-
-impl<A> ArrayMap<A>
-where
-    A: MapArray + Copy,
-{
     /**
-    Get a copy of this map
+    Moves all entries from `other` into this map, combining the values of keys present in both
 
-    Because of the way its internals work, ArrayMap must implement `Drop`, so it cannot implement `Copy`.
+    `f` is called with the key and the values from this map and from `other`, in that order,
+    and its result becomes the key's new value. The key from this map is kept.
 
-    However, that does not make a copy any less trivial with the right array type, hence this function.
+    The two maps are merged in two linear passes: the first counts the merged entries, so that
+    nothing is moved if they do not fit.
+
+    # Panics
+
+    Panics if merging would cause the map to excede its capacity. In that case, this map is
+    left unchanged.
 
     # Example
 
     ```
     use tinymap::*;
 
-    let mut map = ArrayMap::<[Inner<(&str, i32)>; 10]>::new();
-    map.insert("a", 1);
-    map.insert("b", 2);
-    map.insert("c", 3);
+    let mut a = ArrayMap::<[Inner<(&str, i32)>; 10]>::new();
+    a.insert("apples", 3);
+    a.insert("pears", 1);
 
-    let copy = map.copy();
-    assert_eq!(3, copy.len());
+    let mut b = ArrayMap::<[Inner<(&str, i32)>; 10]>::new();
+    b.insert("pears", 4);
+    b.insert("plums", 2);
+
+    a.merge_with(b, |_, x, y| x + y);
+
+    assert_eq!(a["apples"], 3);
+    assert_eq!(a["pears"], 5);
+    assert_eq!(a["plums"], 2);
     ```
     */
-    pub fn copy(&self) -> Self {
-        ArrayMap {
-            array: self.array,
-            len: self.len,
+    pub fn merge_with<F>(&mut self, mut other: Self, mut f: F)
+    where
+        F: FnMut(&A::Key, A::Value, A::Value) -> A::Value,
+    {
+        self.merge(&mut other, |(key, ours), (_, theirs)| {
+            let value = f(&key, ours, theirs);
+            (key, value)
+        })
+        .unwrap_or_else(|_| panic!("Merge would excede capacity"))
+    }
+    pub(crate) fn merge<F>(&mut self, other: &mut Self, mut combine: F) -> Result<(), CapacityError>
+    where
+        F: FnMut((A::Key, A::Value), (A::Key, A::Value)) -> (A::Key, A::Value),
+    {
+        let total = self.merged_len(other);
+        if !self.array.try_grow(total) {
+            return Err(CapacityError);
+        }
+        // Leak rather than double-drop if a comparison or drop panics
+        let mut i = replace(&mut self.len, 0);
+        let mut j = replace(&mut other.len, 0);
+        let mut k = total;
+        let ours = self.array.as_mut_slice();
+        let theirs = other.array.as_mut_slice();
+        while j > 0 {
+            k -= 1;
+            let order = if i > 0 {
+                let a = unsafe { ours[i - 1].as_ptr().as_ref() }.unwrap();
+                let b = unsafe { theirs[j - 1].as_ptr().as_ref() }.unwrap();
+                self.cmp.compare(&a.0, &b.0)
+            } else {
+                Ordering::Less
+            };
+            match order {
+                Ordering::Greater => {
+                    ours.swap(i - 1, k);
+                    i -= 1;
+                }
+                Ordering::Less => {
+                    swap(&mut ours[k], &mut theirs[j - 1]);
+                    j -= 1;
+                }
+                Ordering::Equal => {
+                    let a = replace(&mut ours[i - 1], Inner::uninit());
+                    let b = replace(&mut theirs[j - 1], Inner::uninit());
+                    ours[k] = Inner::new(combine(unsafe { a.assume_init() }, unsafe {
+                        b.assume_init()
+                    }));
+                    i -= 1;
+                    j -= 1;
+                }
+            }
+        }
+        self.len = total;
+        Ok(())
+    }
+    fn merged_len(&self, other: &Self) -> usize {
+        let (ours, theirs) = (self.as_slice(), other.as_slice());
+        let (mut i, mut j, mut len) = (0, 0, 0);
+        while i < ours.len() && j < theirs.len() {
+            match self.cmp.compare(&ours[i].0, &theirs[j].0) {
+                Ordering::Less => i += 1,
+                Ordering::Greater => j += 1,
+                Ordering::Equal => {
+                    i += 1;
+                    j += 1;
+                }
+            }
+            len += 1;
         }
+        len + (ours.len() - i) + (theirs.len() - j)
     }
-}
-
-impl<A> ArrayMap<A>
-where
-    A: MapArray,
-    A::Key: Ord,
-{
     /**
-    Inserts a key-value pair into the map
+    Gets an iterator over the differences between this map and `other`, sorted by key
 
-    If the map did not have this key present, None is returned.
+    Keys only in `other` are [`Added`](../diff/enum.DiffItem.html#variant.Added), keys only in this map
+    are [`Removed`](../diff/enum.DiffItem.html#variant.Removed), and keys in both maps with
+    different values are [`Changed`](../diff/enum.DiffItem.html#variant.Changed).
 
-    If the map did have this key present, the key and value are updated, and the old value is returned.
+    Both maps are walked in a single linear pass.
 
-    # Panics
+    # Example
 
-    Panics if insertion would cause the map to excede its capacity.
+    ```
+    use tinymap::{diff::DiffItem, *};
+
+    let mut old = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
+    old.insert(1, "a");
+    old.insert(2, "b");
+    old.insert(3, "c");
+
+    let mut new = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
+    new.insert(2, "b");
+    new.insert(3, "d");
+    new.insert(4, "e");
+
+    let changes: Vec<_> = old.diff(&new).collect();
+    assert_eq!(changes, [
+        DiffItem::Removed(&1, &"a"),
+        DiffItem::Changed(&3, &"c", &"d"),
+        DiffItem::Added(&4, &"e"),
+    ]);
+    ```
+    */
+    pub fn diff<'a>(&'a self, other: &'a Self) -> Diff<Iter<'a, A::Key, A::Value>, &'a C>
+    where
+        A::Value: PartialEq,
+    {
+        Diff::new(self.iter(), other.iter(), &self.cmp)
+    }
+    /**
+    Gets an iterator over the keys present in both this map and `other`, along with their values from each
+
+    `other` can be anything that iterates over references to a map or set in ascending key order,
+    such as another map or set from this crate. Both sides are walked in a single linear pass.
+    See the [`join`](../join/index.html) module for details.
 
     # Example
 
     ```
     use tinymap::*;
 
-    let mut map = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
-    assert_eq!(map.insert(37, "a"), None);
-    assert_eq!(map.is_empty(), false);
+    let mut prices = ArrayMap::<[Inner<(&str, u32)>; 4]>::new();
+    prices.insert("apple", 3);
+    prices.insert("pear", 4);
+    prices.insert("plum", 2);
 
-    map.insert(37, "b");
-    assert_eq!(map.insert(37, "c"), Some("b"));
-    assert_eq!(map[&37], "c");
+    let mut stock = ArrayMap::<[Inner<(&str, u32)>; 4]>::new();
+    stock.insert("pear", 10);
+    stock.insert("plum", 0);
+    stock.insert("quince", 5);
+
+    let joined: Vec<_> = prices.join(&stock).collect();
+    assert_eq!(joined, [(&"pear", &4, &10), (&"plum", &2, &0)]);
     ```
     */
-    pub fn insert(&mut self, key: A::Key, value: A::Value) -> Option<A::Value> {
-        self.try_insert(key, value)
-            .unwrap_or_else(|_| panic!("Insertion would excede capacity"))
+    pub fn join<'a, I>(&'a self, other: I) -> Join<Iter<'a, A::Key, A::Value>, I::IntoIter, &'a C>
+    where
+        I: IntoIterator,
+        I::Item: JoinItem<Key = &'a A::Key>,
+    {
+        Join::new(self.iter(), other.into_iter(), &self.cmp)
     }
     /**
-    Attempts to insert a key-value pair into the map
+    Gets an iterator over the keys in this map, along with their values from this map and, if present, from `other`
 
-    If the map did not have this key present, None is returned.
-
-    If the map did have this key present, the key and value are updated, and the old value is returned.
-
-    # Errors
-
-    If insertion would cause the map to excede its capacity, this function returns an error containing
-    the key-value pair that could not be inserted.
+    `other` can be anything that iterates over references to a map or set in ascending key order,
+    such as another map or set from this crate. Both sides are walked in a single linear pass.
+    See the [`join`](../join/index.html) module for details.
 
     # Example
 
     ```
     use tinymap::*;
 
-    let mut map = ArrayMap::<[Inner<(i32, &str)>; 3]>::new();
-    assert!(map.try_insert(37, "a").is_ok());
-    assert!(map.try_insert(2, "b").is_ok());
-    assert!(map.try_insert(16, "c").is_ok());
-    assert!(map.try_insert(0, "d").is_err());
+    let mut prices = ArrayMap::<[Inner<(&str, u32)>; 4]>::new();
+    prices.insert("apple", 3);
+    prices.insert("pear", 4);
+    prices.insert("plum", 2);
+
+    let mut stock = ArrayMap::<[Inner<(&str, u32)>; 4]>::new();
+    stock.insert("pear", 10);
+    stock.insert("plum", 0);
+    stock.insert("quince", 5);
+
+    let joined: Vec<_> = prices.left_join(&stock).collect();
+    assert_eq!(joined, [
+        (&"apple", &3, None),
+        (&"pear", &4, Some(&10)),
+        (&"plum", &2, Some(&0)),
+    ]);
     ```
     */
-    pub fn try_insert(
-        &mut self,
-        key: A::Key,
-        value: A::Value,
-    ) -> Result<Option<A::Value>, (A::Key, A::Value)> {
-        self.try_insert_index(key, value, None)
-    }
-    fn try_insert_index(
-        &mut self,
-        key: A::Key,
-        value: A::Value,
-        index: Option<usize>,
-    ) -> Result<Option<A::Value>, (A::Key, A::Value)> {
-        if self.len == A::CAPACITY {
-            return Err((key, value));
-        }
-        let i = if let Some(index) = index {
-            Err(index)
-        } else {
-            self.find(&key)
-        };
-        match i {
-            Ok(i) => {
-                let mut entry = Inner::new((key, value));
-                swap(&mut entry, &mut self.array.as_mut_slice()[i]);
-                Ok(Some(unsafe { entry.assume_init() }.1))
-            }
-            Err(i) => {
-                let slice = self.array.as_mut_slice();
-                for j in ((i + 1)..=self.len).rev() {
-                    slice.swap(j - 1, j);
-                }
-                let mut entry = Inner::new((key, value));
-                swap(&mut entry, &mut slice[i]);
-                self.len += 1;
-                Ok(None)
-            }
-        }
+    pub fn left_join<'a, I>(
+        &'a self,
+        other: I,
+    ) -> LeftJoin<Iter<'a, A::Key, A::Value>, I::IntoIter, &'a C>
+    where
+        I: IntoIterator,
+        I::Item: JoinItem<Key = &'a A::Key>,
+    {
+        LeftJoin::new(self.iter(), other.into_iter(), &self.cmp)
     }
-    fn remove_index(&mut self, i: usize) -> (A::Key, A::Value) {
-        let slice = self.array.as_mut_slice();
-        let mut entry = Inner::uninit();
-        swap(&mut entry, &mut slice[i]);
-        for j in (i + 1)..self.len {
-            slice.swap(j - 1, j);
-        }
-        self.len -= 1;
-        unsafe { entry.assume_init() }
+    /**
+    Gets an iterator over the keys in either this map or `other`, along with the values from whichever sides have them
+
+    `other` can be anything that iterates over references to a map or set in ascending key order,
+    such as another map or set from this crate. Both sides are walked in a single linear pass.
+    See the [`join`](../join/index.html) module for details.
+
+    # Example
+
+    ```
+    use tinymap::{join::EitherOrBoth, *};
+
+    let mut prices = ArrayMap::<[Inner<(&str, u32)>; 4]>::new();
+    prices.insert("apple", 3);
+    prices.insert("pear", 4);
+    prices.insert("plum", 2);
+
+    let mut stock = ArrayMap::<[Inner<(&str, u32)>; 4]>::new();
+    stock.insert("pear", 10);
+    stock.insert("plum", 0);
+    stock.insert("quince", 5);
+
+    let mut discontinued = ArraySet::<[Inner<&str>; 4]>::new();
+    discontinued.insert("apple");
+    discontinued.insert("fig");
+
+    // Sets join as if they had `()` values
+    let joined: Vec<_> = prices.outer_join(&discontinued).collect();
+    assert_eq!(joined, [
+        (&"apple", EitherOrBoth::Both(&3, ())),
+        (&"fig", EitherOrBoth::Right(())),
+        (&"pear", EitherOrBoth::Left(&4)),
+        (&"plum", EitherOrBoth::Left(&2)),
+    ]);
+    ```
+    */
+    pub fn outer_join<'a, I>(
+        &'a self,
+        other: I,
+    ) -> OuterJoin<Iter<'a, A::Key, A::Value>, I::IntoIter, &'a C>
+    where
+        I: IntoIterator,
+        I::Item: JoinItem<Key = &'a A::Key>,
+    {
+        OuterJoin::new(self.iter(), other.into_iter(), &self.cmp)
     }
+}
+
+impl<K, V, C, const N: usize> ArrayMap<[Inner<(K, V)>; N], C>
+where
+    [Inner<(K, V)>; N]: MapArray<Key = K, Value = V>,
+{
     /**
-    Removes a key from the map, returning the value at the key if the key was previously in the map
+    Converts the map into one with a different value type by calling a function on every entry
+
+    The keys and their order are kept, so no searching is needed.
 
     # Example
 
@@ -499,59 +2172,104 @@ where
 
     let mut map = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
     map.insert(1, "a");
-    assert_eq!(map.remove(&1), Some("a"));
-    assert_eq!(map.remove(&1), None);
+    map.insert(2, "bb");
+
+    let lens = map.map_values(|_, v| v.len());
+    assert_eq!(lens.iter().collect::<Vec<_>>(), [(&1, &1), (&2, &2)]);
     ```
     */
-    pub fn remove<Q>(&mut self, key: &Q) -> Option<A::Value>
+    pub fn map_values<U, F>(self, mut f: F) -> ArrayMap<[Inner<(K, U)>; N], C>
     where
-        A::Key: Borrow<Q>,
-        Q: Ord + ?Sized,
+        [Inner<(K, U)>; N]: MapArray<Key = K, Value = U>,
+        F: FnMut(&K, V) -> U,
     {
-        if let Ok(i) = self.find(key) {
-            Some(self.remove_index(i).1)
-        } else {
-            None
+        match self.transform_values(|k, v| Ok::<_, Infallible>(Some(f(k, v)))) {
+            Ok(map) => map,
+            Err(e) => match e {},
         }
     }
     /**
-    Gets the given key's corresponding entry in the map for in-place manipulation
+    Converts the map into one with a different value type by calling a fallible function on every entry
+
+    Stops at the first error and returns it.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
+    map.insert(1, "1");
+    map.insert(2, "2");
+
+    let parsed = map.copy().try_map_values(|_, v| v.parse::<u8>()).unwrap();
+    assert_eq!(parsed[&2], 2);
+
+    map.insert(3, "x");
+    assert!(map.try_map_values(|_, v| v.parse::<u8>()).is_err());
+    ```
+    */
+    pub fn try_map_values<U, E, F>(self, mut f: F) -> Result<ArrayMap<[Inner<(K, U)>; N], C>, E>
+    where
+        [Inner<(K, U)>; N]: MapArray<Key = K, Value = U>,
+        F: FnMut(&K, V) -> Result<U, E>,
+    {
+        self.transform_values(|k, v| f(k, v).map(Some))
+    }
+    /**
+    Converts the map into one with a different value type, keeping only the entries
+    for which the function returns `Some`
 
     # Example
 
     ```
     use tinymap::*;
 
-    let mut count = ArrayMap::<[Inner<(&str, i32)>; 10]>::new();
-
-    // count the number of occurrences of letters in the vec
-    for x in vec!["a","b","a","c","a","b"] {
-        *count.entry(x).or_insert(0) += 1;
-    }
+    let mut map = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
+    map.insert(1, "1");
+    map.insert(2, "x");
+    map.insert(3, "3");
 
-    assert_eq!(count["a"], 3);
+    let parsed = map.filter_map_values(|_, v| v.parse::<u8>().ok());
+    assert_eq!(parsed.iter().collect::<Vec<_>>(), [(&1, &1), (&3, &3)]);
     ```
     */
-    pub fn entry(&mut self, key: A::Key) -> Entry<'_, A> {
-        match self.find(&key) {
-            Ok(i) => Entry::Occupied(OccupiedEntry {
-                map: self,
-                index: i,
-            }),
-            Err(i) => Entry::Vacant(VacantEntry {
-                map: self,
-                index: i,
-                key,
-            }),
+    pub fn filter_map_values<U, F>(self, mut f: F) -> ArrayMap<[Inner<(K, U)>; N], C>
+    where
+        [Inner<(K, U)>; N]: MapArray<Key = K, Value = U>,
+        F: FnMut(&K, V) -> Option<U>,
+    {
+        match self.transform_values(|k, v| Ok::<_, Infallible>(f(k, v))) {
+            Ok(map) => map,
+            Err(e) => match e {},
+        }
+    }
+    pub(crate) fn transform_values<U, E, F>(
+        self,
+        mut f: F,
+    ) -> Result<ArrayMap<[Inner<(K, U)>; N], C>, E>
+    where
+        [Inner<(K, U)>; N]: MapArray<Key = K, Value = U>,
+        F: FnMut(&K, V) -> Result<Option<U>, E>,
+    {
+        let (entries, cmp) = self.into_parts();
+        let mut map = ArrayMap::<[Inner<(K, U)>; N], C>::with_comparator(cmp);
+        for (key, value) in entries {
+            if let Some(value) = f(&key, value)? {
+                // The keys are already sorted and the capacity is the same
+                map.array.as_mut_slice()[map.len] = Inner::new((key, value));
+                map.len += 1;
+            }
         }
+        Ok(map)
     }
 }
 
-impl<A, Q> Index<&Q> for ArrayMap<A>
+impl<A, C, Q> Index<&Q> for ArrayMap<A, C>
 where
-    A: MapArray,
-    A::Key: Borrow<Q>,
-    Q: Ord + ?Sized,
+    A: MapStorage,
+    Q: ?Sized,
+    C: Compare<Q, A::Key>,
 {
     type Output = A::Value;
     fn index(&self, key: &Q) -> &Self::Output {
@@ -560,41 +2278,68 @@ where
     }
 }
 
-impl<A> fmt::Debug for ArrayMap<A>
+impl<A, C> fmt::Debug for ArrayMap<A, C>
 where
-    A: MapArray,
+    A: MapStorage,
     A::Key: fmt::Debug,
     A::Value: fmt::Debug,
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_map()
-            .entries(self.iter().map(|(k, v)| (k, v)))
-            .finish()
+        f.debug_map().entries(self.iter()).finish()
     }
 }
 
-impl<A> From<A> for ArrayMap<A>
+impl<A, C> From<A> for ArrayMap<A, C>
 where
     A: MapArray,
-    A::Key: Ord,
+    C: Compare<A::Key> + Default,
 {
     fn from(mut array: A) -> Self {
+        let cmp = C::default();
         array.as_mut_slice().sort_unstable_by(|a, b| {
-            unsafe { a.as_ptr().as_ref() }
-                .unwrap()
-                .0
-                .cmp(&unsafe { b.as_ptr().as_ref() }.unwrap().0)
+            cmp.compare(
+                &unsafe { a.as_ptr().as_ref() }.unwrap().0,
+                &unsafe { b.as_ptr().as_ref() }.unwrap().0,
+            )
         });
         ArrayMap {
             array,
             len: A::CAPACITY,
+            cmp,
         }
     }
 }
 
-impl<A> PartialEq for ArrayMap<A>
+/// The entries are moved into the vector in the map's order.
+#[cfg(feature = "alloc")]
+impl<A, C> From<ArrayMap<A, C>> for Vec<(A::Key, A::Value)>
+where
+    A: MapArray,
+{
+    fn from(mut map: ArrayMap<A, C>) -> Self {
+        let array = replace(&mut map.array, unsafe { zeroed() });
+        let len = replace(&mut map.len, 0);
+        let mut vec = ManuallyDrop::new(array.into_boxed_slice().into_vec());
+        // `Inner<T>` has the same layout as `T`, and the first `len` entries are initialized
+        unsafe { Vec::from_raw_parts(vec.as_mut_ptr() as *mut _, len, vec.capacity()) }
+    }
+}
+
+/// The tree is ordered by the keys' `Ord` implementation, whatever the map's comparator.
+#[cfg(feature = "alloc")]
+impl<A, C> From<ArrayMap<A, C>> for std::collections::BTreeMap<A::Key, A::Value>
 where
     A: MapArray,
+    A::Key: Ord,
+{
+    fn from(map: ArrayMap<A, C>) -> Self {
+        map.into_iter().collect()
+    }
+}
+
+impl<A, C> PartialEq for ArrayMap<A, C>
+where
+    A: MapStorage,
     A::Key: PartialEq,
     A::Value: PartialEq,
 {
@@ -603,17 +2348,17 @@ where
     }
 }
 
-impl<A> Eq for ArrayMap<A>
+impl<A, C> Eq for ArrayMap<A, C>
 where
-    A: MapArray,
+    A: MapStorage,
     A::Key: Eq,
     A::Value: Eq,
 {
 }
 
-impl<'a, A> IntoIterator for &'a ArrayMap<A>
+impl<'a, A, C> IntoIterator for &'a ArrayMap<A, C>
 where
-    A: MapArray,
+    A: MapStorage,
 {
     type Item = (&'a A::Key, &'a A::Value);
     type IntoIter = Iter<'a, A::Key, A::Value>;
@@ -622,9 +2367,9 @@ where
     }
 }
 
-impl<'a, A> IntoIterator for &'a mut ArrayMap<A>
+impl<'a, A, C> IntoIterator for &'a mut ArrayMap<A, C>
 where
-    A: MapArray,
+    A: MapStorage,
 {
     type Item = (&'a A::Key, &'a mut A::Value);
     type IntoIter = IterMut<'a, A::Key, A::Value>;
@@ -634,16 +2379,17 @@ where
 }
 
 #[cfg(feature = "alloc")]
-impl<A> IntoIterator for ArrayMap<A>
+impl<A, C> IntoIterator for ArrayMap<A, C>
 where
     A: MapArray,
 {
     type Item = (A::Key, A::Value);
     type IntoIter = IntoIter<A::Key, A::Value>;
     fn into_iter(mut self) -> Self::IntoIter {
-        let array = core::mem::replace(&mut self.array, unsafe { zeroed() });
+        let array = replace(&mut self.array, unsafe { zeroed() });
+        let len = replace(&mut self.len, 0);
         let mut vec = array.into_boxed_slice().into_vec();
-        vec.truncate(self.len);
+        vec.truncate(len);
         IntoIter {
             iter: vec.into_iter(),
         }
@@ -651,10 +2397,10 @@ where
 }
 
 /// Elements from the iterator beyond the map's capacity will be discarded.
-impl<A> FromIterator<(A::Key, A::Value)> for ArrayMap<A>
+impl<A, C> FromIterator<(A::Key, A::Value)> for ArrayMap<A, C>
 where
     A: MapArray,
-    A::Key: Ord,
+    C: Compare<A::Key> + Default,
 {
     fn from_iter<I>(iter: I) -> Self
     where
@@ -669,10 +2415,10 @@ where
 }
 
 /// Elements from the iterator beyond the map's capacity will be discarded.
-impl<A> Extend<(A::Key, A::Value)> for ArrayMap<A>
+impl<A, C> Extend<(A::Key, A::Value)> for ArrayMap<A, C>
 where
-    A: MapArray,
-    A::Key: Ord,
+    A: MapStorage,
+    C: Compare<A::Key>,
 {
     fn extend<I>(&mut self, iter: I)
     where
@@ -686,9 +2432,9 @@ where
     }
 }
 
-impl<A> Drop for ArrayMap<A>
+impl<A, C> Drop for ArrayMap<A, C>
 where
-    A: MapArray,
+    A: MapStorage,
 {
     fn drop(&mut self) {
         for i in 0..self.len {
@@ -713,6 +2459,89 @@ impl<K, V> Iterator for IntoIter<K, V> {
     }
 }
 
+#[cfg(feature = "alloc")]
+impl<K, V> Drop for IntoIter<K, V> {
+    fn drop(&mut self) {
+        self.for_each(drop);
+    }
+}
+
+/// A consuming iterator over the key-value pairs of an ArrayMap that does not need to allocate
+///
+/// This is the iterator that a TinyVecMap turns into, whatever storage it has spilled into.
+pub struct IntoEntries<A>
+where
+    A: MapStorage,
+{
+    array: A,
+    index: usize,
+    len: usize,
+}
+
+impl<A> Iterator for IntoEntries<A>
+where
+    A: MapStorage,
+{
+    type Item = (A::Key, A::Value);
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index == self.len {
+            return None;
+        }
+        let entry = replace(&mut self.array.as_mut_slice()[self.index], Inner::uninit());
+        self.index += 1;
+        Some(unsafe { entry.assume_init() })
+    }
+}
+
+impl<A> Drop for IntoEntries<A>
+where
+    A: MapStorage,
+{
+    fn drop(&mut self) {
+        for i in self.index..self.len {
+            unsafe {
+                self.array.as_mut_slice()[i].as_mut_ptr().drop_in_place();
+            }
+        }
+    }
+}
+
+/// A consuming iterator over the keys in an ArrayMap
+pub struct IntoKeys<A>
+where
+    A: MapStorage,
+{
+    iter: IntoEntries<A>,
+}
+
+impl<A> Iterator for IntoKeys<A>
+where
+    A: MapStorage,
+{
+    type Item = A::Key;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(k, _)| k)
+    }
+}
+
+/// A consuming iterator over the values in an ArrayMap
+pub struct IntoValues<A>
+where
+    A: MapStorage,
+{
+    iter: IntoEntries<A>,
+}
+
+impl<A> Iterator for IntoValues<A>
+where
+    A: MapStorage,
+{
+    type Item = A::Value;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(_, v)| v)
+    }
+}
+
 /// An iterator over references to the key-value pairs in an ArrayMap
 pub struct Iter<'a, K, V> {
     iter: core::slice::Iter<'a, Inner<(K, V)>>,
@@ -798,21 +2627,212 @@ where
     }
 }
 
+/**
+A cursor over an ArrayMap
+
+A cursor points at a gap between two entries of the map, or at the gap before the first entry
+or after the last one. It can move in either direction.
+*/
+pub struct Cursor<'a, K, V> {
+    pub(crate) entries: &'a [(K, V)],
+    pub(crate) index: usize,
+}
+
+impl<'a, K, V> Clone for Cursor<'a, K, V> {
+    fn clone(&self) -> Self {
+        Cursor {
+            entries: self.entries,
+            index: self.index,
+        }
+    }
+}
+
+impl<'a, K, V> Cursor<'a, K, V> {
+    /**
+    Advances the cursor to the next gap, returning the entry that it moved over
+
+    Returns `None` if the cursor is already at the end of the map.
+    */
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        let item = self.peek_next()?;
+        self.index += 1;
+        Some(item)
+    }
+    /**
+    Moves the cursor to the previous gap, returning the entry that it moved over
+
+    Returns `None` if the cursor is already at the start of the map.
+    */
+    pub fn prev(&mut self) -> Option<(&'a K, &'a V)> {
+        let item = self.peek_prev()?;
+        self.index -= 1;
+        Some(item)
+    }
+    /**
+    Returns the entry after the cursor without moving it
+    */
+    pub fn peek_next(&self) -> Option<(&'a K, &'a V)> {
+        self.entries.get(self.index).map(|(k, v)| (k, v))
+    }
+    /**
+    Returns the entry before the cursor without moving it
+    */
+    pub fn peek_prev(&self) -> Option<(&'a K, &'a V)> {
+        let index = self.index.checked_sub(1)?;
+        self.entries.get(index).map(|(k, v)| (k, v))
+    }
+}
+
+/**
+A cursor over an ArrayMap that allows mutating values and inserting or removing entries
+
+A cursor points at a gap between two entries of the map, or at the gap before the first entry
+or after the last one. It can move in either direction.
+*/
+pub struct CursorMut<'a, A, C = Natural>
+where
+    A: MapStorage,
+{
+    map: &'a mut ArrayMap<A, C>,
+    index: usize,
+}
+
+impl<'a, A, C> CursorMut<'a, A, C>
+where
+    A: MapStorage,
+{
+    /**
+    Advances the cursor to the next gap, returning the entry that it moved over
+
+    Returns `None` if the cursor is already at the end of the map.
+    */
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<(&A::Key, &mut A::Value)> {
+        let item = self.map.get_index_mut(self.index)?;
+        self.index += 1;
+        Some(item)
+    }
+    /**
+    Moves the cursor to the previous gap, returning the entry that it moved over
+
+    Returns `None` if the cursor is already at the start of the map.
+    */
+    pub fn prev(&mut self) -> Option<(&A::Key, &mut A::Value)> {
+        let index = self.index.checked_sub(1)?;
+        self.index = index;
+        self.map.get_index_mut(index)
+    }
+    /**
+    Returns the entry after the cursor without moving it
+    */
+    pub fn peek_next(&mut self) -> Option<(&A::Key, &mut A::Value)> {
+        self.map.get_index_mut(self.index)
+    }
+    /**
+    Returns the entry before the cursor without moving it
+    */
+    pub fn peek_prev(&mut self) -> Option<(&A::Key, &mut A::Value)> {
+        let index = self.index.checked_sub(1)?;
+        self.map.get_index_mut(index)
+    }
+    /**
+    Returns a read-only cursor pointing at the same gap
+    */
+    pub fn as_cursor(&self) -> Cursor<'_, A::Key, A::Value> {
+        Cursor {
+            entries: self.map.as_slice(),
+            index: self.index,
+        }
+    }
+    /**
+    Removes the entry after the cursor and returns it
+
+    The cursor stays in the same gap.
+    */
+    pub fn remove_next(&mut self) -> Option<(A::Key, A::Value)> {
+        self.map.remove_index(self.index)
+    }
+    /**
+    Removes the entry before the cursor and returns it
+
+    The cursor moves back to the gap that the removed entry used to follow.
+    */
+    pub fn remove_prev(&mut self) -> Option<(A::Key, A::Value)> {
+        let index = self.index.checked_sub(1)?;
+        self.index = index;
+        self.map.remove_index(index)
+    }
+}
+
+impl<'a, A, C> CursorMut<'a, A, C>
+where
+    A: MapStorage,
+    C: Compare<A::Key>,
+{
+    fn fits(&self, key: &A::Key) -> bool {
+        let cursor = self.as_cursor();
+        let cmp = &self.map.cmp;
+        cursor
+            .peek_prev()
+            .map_or(Ordering::Less, |(prev, _)| cmp.compare(prev, key))
+            == Ordering::Less
+            && cursor
+                .peek_next()
+                .map_or(Ordering::Less, |(next, _)| cmp.compare(key, next))
+                == Ordering::Less
+    }
+    /**
+    Inserts a key-value pair into the gap the cursor points at, leaving the cursor before it
+
+    # Errors
+
+    If the key does not sort strictly between the entries around the cursor, or if insertion
+    would cause the map to excede its capacity, this function returns an error containing
+    the key-value pair that could not be inserted.
+    */
+    pub fn insert_after(&mut self, key: A::Key, value: A::Value) -> Result<(), (A::Key, A::Value)> {
+        if !self.fits(&key) {
+            return Err((key, value));
+        }
+        self.map.try_insert_index(key, value, Some(self.index))?;
+        Ok(())
+    }
+    /**
+    Inserts a key-value pair into the gap the cursor points at, leaving the cursor after it
+
+    # Errors
+
+    If the key does not sort strictly between the entries around the cursor, or if insertion
+    would cause the map to excede its capacity, this function returns an error containing
+    the key-value pair that could not be inserted.
+    */
+    pub fn insert_before(
+        &mut self,
+        key: A::Key,
+        value: A::Value,
+    ) -> Result<(), (A::Key, A::Value)> {
+        self.insert_after(key, value)?;
+        self.index += 1;
+        Ok(())
+    }
+}
+
 /// A view into a single entry in a map, which may either be vacant or occupied.
-pub enum Entry<'a, A>
+pub enum Entry<'a, A, C = Natural>
 where
-    A: MapArray,
+    A: MapStorage,
 {
     /// A vacant entry.
-    Vacant(VacantEntry<'a, A>),
+    Vacant(VacantEntry<'a, A, C>),
     /// An occupied entry.
-    Occupied(OccupiedEntry<'a, A>),
+    Occupied(OccupiedEntry<'a, A, C>),
 }
 
-impl<'a, A> Entry<'a, A>
+impl<'a, A, C> Entry<'a, A, C>
 where
-    A: MapArray,
-    A::Key: Ord,
+    A: MapStorage,
+    C: Compare<A::Key>,
 {
     /**
     Ensures a value is in the entry by inserting the default if empty,
@@ -972,7 +2992,7 @@ where
     assert_eq!(map["poneyland"], 43);
     ```
     */
-    pub fn and_modify<F>(mut self, f: F) -> Entry<'a, A>
+    pub fn and_modify<F>(mut self, f: F) -> Entry<'a, A, C>
     where
         F: FnOnce(&mut A::Value),
     {
@@ -983,10 +3003,10 @@ where
     }
 }
 
-impl<'a, A> Entry<'a, A>
+impl<'a, A, C> Entry<'a, A, C>
 where
-    A: MapArray,
-    A::Key: Ord,
+    A: MapStorage,
+    C: Compare<A::Key>,
     A::Value: Default,
 {
     /**
@@ -1011,19 +3031,19 @@ where
 }
 
 /// An entry in an ArrayMap that is vacant
-pub struct VacantEntry<'a, A>
+pub struct VacantEntry<'a, A, C = Natural>
 where
-    A: MapArray,
+    A: MapStorage,
 {
-    map: &'a mut ArrayMap<A>,
+    map: &'a mut ArrayMap<A, C>,
     index: usize,
     key: A::Key,
 }
 
-impl<'a, A> VacantEntry<'a, A>
+impl<'a, A, C> VacantEntry<'a, A, C>
 where
-    A: MapArray + 'a,
-    A::Key: Ord,
+    A: MapStorage + 'a,
+    C: Compare<A::Key>,
 {
     /**
     Gets a reference to the key that would be used when inserting a value through the VacantEntry.
@@ -1037,6 +3057,11 @@ where
     pub fn into_key(self) -> A::Key {
         self.key
     }
+    /// The length of the map that the entry is in
+    #[cfg(feature = "stats")]
+    pub(crate) fn map_len(&self) -> usize {
+        self.map.len()
+    }
     /**
     Sets the value of the entry with the VacantEntry's key and returns a mutable reference to it.
 
@@ -1076,18 +3101,18 @@ where
 }
 
 /// An entry in an ArrayMap that is occupied
-pub struct OccupiedEntry<'a, A>
+pub struct OccupiedEntry<'a, A, C = Natural>
 where
-    A: MapArray,
+    A: MapStorage,
 {
-    map: &'a mut ArrayMap<A>,
+    map: &'a mut ArrayMap<A, C>,
     index: usize,
 }
 
-impl<'a, A> OccupiedEntry<'a, A>
+impl<'a, A, C> OccupiedEntry<'a, A, C>
 where
-    A: MapArray + 'a,
-    A::Key: Ord,
+    A: MapStorage + 'a,
+    C: Compare<A::Key>,
 {
     fn inner(&self) -> &Inner<(A::Key, A::Value)> {
         &self.map.array.as_slice()[self.index]
@@ -1137,6 +3162,100 @@ where
     Takes the key-value pair of the entry out of the map, and returns it.
     */
     pub fn remove_entry(self) -> (A::Key, A::Value) {
-        self.map.remove_index(self.index)
+        self.map.remove_at(self.index)
+    }
+}
+
+/**
+A guard that gives mutable access to a key in an ArrayMap
+
+Created with [`ArrayMap::get_key_mut`](struct.ArrayMap.html#method.get_key_mut).
+When the guard is dropped, the entry is moved to wherever its key now sorts, replacing
+any other entry whose key it has become equal to.
+*/
+pub struct KeyMut<'a, A, C = Natural>
+where
+    A: MapStorage,
+    C: Compare<A::Key>,
+{
+    map: &'a mut ArrayMap<A, C>,
+    index: usize,
+}
+
+impl<'a, A, C> KeyMut<'a, A, C>
+where
+    A: MapStorage,
+    C: Compare<A::Key>,
+{
+    /**
+    Moves the entry to wherever its key now sorts
+
+    # Errors
+
+    If the key has become equal to another key in the map, the entry with the changed key is
+    removed from the map and returned, leaving the other entry in place.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = ArrayMap::<[Inner<(i32, &str)>; 10]>::new();
+    map.insert(1, "a");
+    map.insert(2, "b");
+
+    let mut key = map.get_key_mut(&1).unwrap();
+    *key = 2;
+    assert_eq!(key.finish(), Err((2, "a")));
+    assert_eq!(map.len(), 1);
+    assert_eq!(map[&2], "b");
+    ```
+    */
+    pub fn finish(self) -> Result<(), (A::Key, A::Value)> {
+        let mut this = ManuallyDrop::new(self);
+        let index = this.index;
+        match this.map.restore_order(index) {
+            Ok(()) => Ok(()),
+            Err((i, _)) => Err(this.map.remove_at(i)),
+        }
+    }
+}
+
+impl<'a, A, C> Deref for KeyMut<'a, A, C>
+where
+    A: MapStorage,
+    C: Compare<A::Key>,
+{
+    type Target = A::Key;
+    fn deref(&self) -> &Self::Target {
+        self.map.key_at(self.index)
+    }
+}
+
+impl<'a, A, C> DerefMut for KeyMut<'a, A, C>
+where
+    A: MapStorage,
+    C: Compare<A::Key>,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut unsafe {
+            self.map.array.as_mut_slice()[self.index]
+                .as_mut_ptr()
+                .as_mut()
+        }
+        .unwrap()
+        .0
+    }
+}
+
+impl<'a, A, C> Drop for KeyMut<'a, A, C>
+where
+    A: MapStorage,
+    C: Compare<A::Key>,
+{
+    fn drop(&mut self) {
+        if let Err((_, other)) = self.map.restore_order(self.index) {
+            self.map.remove_at(other);
+        }
     }
 }
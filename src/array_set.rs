@@ -1,358 +1,1588 @@
 //! An array-backed, set-like data structure
 
 use core::{
-    borrow::Borrow,
+    cmp::Ordering,
     fmt,
     iter::FromIterator,
-    mem::{replace, swap, zeroed},
+    mem::{replace, swap, zeroed, ManuallyDrop},
+    ops::{Deref, DerefMut},
+    ptr, slice,
 };
 
-use crate::{Array, Inner};
+use crate::{
+    compare::{Compare, Natural},
+    join::{Join, JoinItem, LeftJoin, OuterJoin},
+    Array, CapacityError, EmptyStorage, Inner, Storage,
+};
+
+#[cfg(feature = "alloc")]
+use crate::{Spill, SpillHeap};
 
 /**
 An array-backed, set-like data structure
 
 ArraySet wraps an array of values and supports operation similar to a BTreeSet or HashSet.
 It has a fixed capacity, but it keeps track of how many values have been inserted and removed.
+
+Values are kept sorted by the comparator `C`, which defaults to the values' `Ord` implementation.
+See the [`compare`](../compare/index.html) module for details.
+
+The values can also be kept in a borrowed slice, a boxed slice or a growing vector instead of an array.
+See [`SliceSet`](type.SliceSet.html), [`BoxedArraySet`](type.BoxedArraySet.html),
+[`VecSet`](type.VecSet.html) and [`TinyVecSet`](type.TinyVecSet.html).
 */
-pub struct ArraySet<A>
+pub struct ArraySet<A, C = Natural>
 where
-    A: Array,
+    A: Storage,
 {
     array: A,
     len: usize,
+    cmp: C,
 }
 
-impl<A> Default for ArraySet<A>
+impl<A, C> Default for ArraySet<A, C>
 where
     A: Array,
+    C: Default,
 {
     fn default() -> Self {
+        ArraySet::with_comparator(C::default())
+    }
+}
+
+impl<A, C> Clone for ArraySet<A, C>
+where
+    A: Array,
+    A::Item: Clone,
+    C: Clone,
+{
+    fn clone(&self) -> Self {
+        let mut array: A = unsafe { zeroed() };
+        let len = self.len;
+        for (i, item) in self.iter().enumerate() {
+            array.as_mut_slice()[i] = Inner::new(item.clone());
+        }
         ArraySet {
-            array: unsafe { zeroed() },
+            array,
+            len,
+            cmp: self.cmp.clone(),
+        }
+    }
+}
+
+impl<A> ArraySet<A>
+where
+    A: Array,
+{
+    /**
+    Creates a new empty ArraySet
+
+    # Example
+    ```
+    use tinymap::*;
+
+    let mut set = ArraySet::<[Inner<i32>; 10]>::new();
+    ```
+    */
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/**
+An ArraySet that keeps its values in a borrowed slice
+
+This allows the storage to live outside of the set, such as in a buffer in a specific linker section.
+The slice's capacity is its length, and any values already in it are ignored.
+
+A SliceSet supports the same operations as an ArraySet, except for those that have to create
+new storage, like `clone`, `split_off` and `into_iter`.
+
+# Example
+
+```
+use tinymap::*;
+
+let mut storage: [Inner<String>; 4] = std::array::from_fn(|_| Inner::uninit());
+let mut set = SliceSet::new_in(&mut storage);
+set.insert("b".to_owned());
+set.insert("a".to_owned());
+
+assert_eq!(set.capacity(), 4);
+assert_eq!(set.iter().collect::<Vec<_>>(), ["a", "b"]);
+```
+*/
+pub type SliceSet<'a, T, C = Natural> = ArraySet<&'a mut [Inner<T>], C>;
+
+impl<'a, T> SliceSet<'a, T> {
+    /// Creates a new empty SliceSet that keeps its values in the given slice
+    pub fn new_in(storage: &'a mut [Inner<T>]) -> Self {
+        SliceSet::with_comparator_in(storage, Natural)
+    }
+}
+
+impl<'a, T, C> SliceSet<'a, T, C> {
+    /// Creates a new empty SliceSet that keeps its values in the given slice and orders them with the given comparator
+    pub fn with_comparator_in(storage: &'a mut [Inner<T>], cmp: C) -> Self {
+        ArraySet {
+            array: storage,
+            len: 0,
+            cmp,
+        }
+    }
+}
+
+/**
+An ArraySet that keeps its values in a boxed slice with a capacity chosen at runtime
+
+The storage is allocated once, when the set is created, and never grows.
+Like an ArraySet, inserting beyond the capacity panics, or returns an error with `try_insert`.
+
+A BoxedArraySet supports the same operations as an ArraySet.
+
+# Example
+
+```
+use tinymap::*;
+
+let mut set = BoxedArraySet::with_capacity(2);
+set.insert(2);
+set.insert(1);
+
+assert_eq!(set.capacity(), 2);
+assert_eq!(set.try_insert(3), Err(3));
+assert_eq!(set.into_iter().collect::<Vec<_>>(), [1, 2]);
+```
+*/
+#[cfg(feature = "alloc")]
+pub type BoxedArraySet<T, C = Natural> = ArraySet<Box<[Inner<T>]>, C>;
+
+#[cfg(feature = "alloc")]
+impl<T> BoxedArraySet<T> {
+    /// Creates a new empty BoxedArraySet that can hold `capacity` values
+    pub fn with_capacity(capacity: usize) -> Self {
+        BoxedArraySet::with_capacity_and_comparator(capacity, Natural)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T, C> BoxedArraySet<T, C> {
+    /// Creates a new empty BoxedArraySet that can hold `capacity` values and orders them with the given comparator
+    pub fn with_capacity_and_comparator(capacity: usize, cmp: C) -> Self {
+        ArraySet {
+            array: {
+                let mut slots = Vec::with_capacity(capacity);
+                slots.resize_with(capacity, Inner::uninit);
+                slots.into_boxed_slice()
+            },
+            len: 0,
+            cmp,
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T, C> Clone for BoxedArraySet<T, C>
+where
+    T: Clone,
+    C: Clone,
+{
+    fn clone(&self) -> Self {
+        let mut set =
+            BoxedArraySet::with_capacity_and_comparator(self.capacity(), self.cmp.clone());
+        for value in self.iter() {
+            set.array[set.len] = Inner::new(value.clone());
+            set.len += 1;
+        }
+        set
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T, C> IntoIterator for BoxedArraySet<T, C> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+    fn into_iter(mut self) -> Self::IntoIter {
+        let array = replace(&mut self.array, Box::new([]));
+        let len = replace(&mut self.len, 0);
+        let mut vec = array.into_vec();
+        vec.truncate(len);
+        IntoIter {
+            iter: vec.into_iter(),
+        }
+    }
+}
+
+/**
+An ArraySet that keeps its values in a `Vec` and grows instead of running out of capacity
+
+A VecSet is a sorted vector of values that is searched with a binary search.
+It suits sets that are too big for an array but small enough that shifting values on insertion
+is cheaper than the allocations and pointer chasing of a BTreeSet.
+
+Inserting into a VecSet never fails, so `try_insert` always succeeds. Otherwise it supports
+the same operations as an ArraySet.
+
+# Example
+
+```
+use tinymap::*;
+
+let mut set = VecSet::new();
+for i in (0..100).rev() {
+    set.insert(i);
+}
+
+assert_eq!(set.len(), 100);
+assert!(set.contains(&42));
+assert_eq!(set.iter().take(3).copied().collect::<Vec<_>>(), [0, 1, 2]);
+```
+*/
+#[cfg(feature = "alloc")]
+pub type VecSet<T, C = Natural> = ArraySet<Vec<Inner<T>>, C>;
+
+#[cfg(feature = "alloc")]
+impl<T> VecSet<T> {
+    /// Creates a new empty VecSet
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Creates a new empty VecSet with room for at least `capacity` values
+    pub fn with_capacity(capacity: usize) -> Self {
+        VecSet::with_capacity_and_comparator(capacity, Natural)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T, C> VecSet<T, C> {
+    /// Creates a new empty VecSet with room for at least `capacity` values that orders them with the given comparator
+    pub fn with_capacity_and_comparator(capacity: usize, cmp: C) -> Self {
+        let mut array = Vec::new();
+        array.try_grow(capacity);
+        ArraySet { array, len: 0, cmp }
+    }
+    /// Reserves room for at least `additional` more values
+    pub fn reserve(&mut self, additional: usize) {
+        self.array.try_grow(self.len + additional);
+    }
+    /// Shrinks the capacity of the set as much as possible
+    pub fn shrink_to_fit(&mut self) {
+        self.array.truncate(self.len);
+        self.array.shrink_to_fit();
+    }
+    /// Moves the values of another set into a new VecSet with the same comparator, leaving the other set empty
+    pub(crate) fn take_from<A>(set: &mut ArraySet<A, C>) -> Self
+    where
+        A: Storage<Item = T>,
+        C: Clone,
+    {
+        let mut vec = VecSet::with_capacity_and_comparator(set.len, set.cmp.clone());
+        for i in 0..set.len {
+            swap(
+                &mut set.array.as_mut_slice()[i],
+                &mut vec.array.as_mut_slice()[i],
+            );
+        }
+        vec.len = replace(&mut set.len, 0);
+        vec
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T, C> Default for VecSet<T, C>
+where
+    C: Default,
+{
+    fn default() -> Self {
+        VecSet::with_capacity_and_comparator(0, C::default())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T, C> Clone for VecSet<T, C>
+where
+    T: Clone,
+    C: Clone,
+{
+    fn clone(&self) -> Self {
+        let mut set = VecSet::with_capacity_and_comparator(self.len, self.cmp.clone());
+        for value in self.iter() {
+            set.array[set.len] = Inner::new(value.clone());
+            set.len += 1;
+        }
+        set
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T, C> IntoIterator for VecSet<T, C> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+    fn into_iter(mut self) -> Self::IntoIter {
+        let mut vec = core::mem::take(&mut self.array);
+        vec.truncate(replace(&mut self.len, 0));
+        IntoIter {
+            iter: vec.into_iter(),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T, C> FromIterator<T> for VecSet<T, C>
+where
+    C: Compare<T> + Default,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut set = VecSet::default();
+        set.extend(iter);
+        set
+    }
+}
+
+/**
+An ArraySet that keeps its values in an inline array and moves them into a `Vec` when it grows beyond it
+
+This is an alternative to a [`TinySet`](../tiny_set/enum.TinySet.html), which moves into a BTreeSet.
+Because the values stay in a single sorted slice, a TinyVecSet supports the same operations as an ArraySet,
+whether or not it has spilled. Like a VecSet, inserting into it never fails.
+
+# Example
+
+```
+use tinymap::*;
+
+let mut set = TinyVecSet::<[Inner<i32>; 2]>::new();
+set.insert(2);
+set.insert(1);
+assert!(!set.is_spilled());
+
+set.insert(3);
+assert!(set.is_spilled());
+assert_eq!(set.into_iter().collect::<Vec<_>>(), [1, 2, 3]);
+```
+*/
+#[cfg(feature = "alloc")]
+pub type TinyVecSet<A, C = Natural, H = Vec<Inner<<A as Storage>::Item>>> =
+    ArraySet<Spill<A, H>, C>;
+
+#[cfg(feature = "alloc")]
+impl<A> TinyVecSet<A>
+where
+    A: Array,
+{
+    /// Creates a new empty TinyVecSet
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<A, C, H> TinyVecSet<A, C, H>
+where
+    A: Array,
+    H: SpillHeap<Item = A::Item>,
+{
+    /**
+    Creates a new empty TinyVecSet that starts on the heap
+
+    This skips moving the values from the array to the heap when the set is expected to grow beyond its capacity.
+
+    # Example
+    ```
+    use tinymap::*;
+
+    let mut set = TinyVecSet::<[Inner<i32>; 2]>::with_heap();
+    assert!(set.is_spilled());
+    set.insert(1);
+    assert!(set.is_spilled());
+    ```
+    */
+    pub fn with_heap() -> Self
+    where
+        H::Alloc: Default,
+        C: Default,
+    {
+        ArraySet {
+            array: Spill::Heap(H::new_in(H::Alloc::default())),
+            len: 0,
+            cmp: C::default(),
+        }
+    }
+    /**
+    Creates a new empty TinyVecSet that moves its values into the given allocator when it grows beyond its array
+
+    # Example
+    ```
+    #![feature(allocator_api)]
+    use std::alloc::System;
+    use tinymap::{compare::Natural, *};
+
+    let mut set = TinyVecSet::<[Inner<i32>; 2], Natural, Vec<Inner<i32>, System>>::new_in(System);
+    set.insert(1);
+    assert!(!set.is_spilled());
+    set.extend(vec![1, 2, 3]);
+    assert!(set.is_spilled());
+    ```
+    */
+    #[cfg(feature = "allocator_api")]
+    pub fn new_in(alloc: H::Alloc) -> Self
+    where
+        C: Default,
+    {
+        ArraySet {
+            array: Spill::new_in(alloc),
+            len: 0,
+            cmp: C::default(),
+        }
+    }
+    /**
+    Creates a new empty TinyVecSet that starts on the heap in the given allocator
+
+    # Example
+    ```
+    #![feature(allocator_api)]
+    use std::alloc::System;
+    use tinymap::{compare::Natural, *};
+
+    let mut set = TinyVecSet::<[Inner<i32>; 2], Natural, Vec<Inner<i32>, System>>::with_heap_in(System);
+    assert!(set.is_spilled());
+    set.insert(1);
+    assert_eq!(set.len(), 1);
+    ```
+    */
+    #[cfg(feature = "allocator_api")]
+    pub fn with_heap_in(alloc: H::Alloc) -> Self
+    where
+        C: Default,
+    {
+        ArraySet {
+            array: Spill::Heap(H::new_in(alloc)),
             len: 0,
+            cmp: C::default(),
+        }
+    }
+    /**
+    Returns `true` if the values have moved into a `Vec`
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut set = TinyVecSet::<[Inner<i32>; 2]>::new();
+    set.insert(1);
+    assert!(!set.is_spilled());
+    set.extend(vec![1, 2, 3]);
+    assert!(set.is_spilled());
+    ```
+    */
+    pub fn is_spilled(&self) -> bool {
+        self.array.is_spilled()
+    }
+    /**
+    Moves the values into a `Vec` if they are not there already
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut set = TinyVecSet::<[Inner<i32>; 2]>::new();
+    set.insert(1);
+    set.spill();
+    assert!(set.is_spilled());
+    assert_eq!(set.len(), 1);
+    ```
+    */
+    pub fn spill(&mut self) {
+        self.array.spill(A::CAPACITY);
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<A, C, H> Default for TinyVecSet<A, C, H>
+where
+    A: Array,
+    H: SpillHeap<Item = A::Item>,
+    H::Alloc: Default,
+    C: Default,
+{
+    fn default() -> Self {
+        ArraySet {
+            array: Spill::default(),
+            len: 0,
+            cmp: C::default(),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<A, C, H> Clone for TinyVecSet<A, C, H>
+where
+    A: Array,
+    A::Item: Clone,
+    H: SpillHeap<Item = A::Item>,
+    C: Clone,
+{
+    fn clone(&self) -> Self {
+        let mut set = ArraySet {
+            array: self.array.empty_like(),
+            len: 0,
+            cmp: self.cmp.clone(),
+        };
+        set.array.try_grow(self.len);
+        for value in self.iter() {
+            set.array.as_mut_slice()[set.len] = Inner::new(value.clone());
+            set.len += 1;
+        }
+        set
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<A, C, H> IntoIterator for TinyVecSet<A, C, H>
+where
+    A: Array,
+    H: SpillHeap<Item = A::Item>,
+{
+    type Item = A::Item;
+    type IntoIter = IntoItems<Spill<A, H>>;
+    fn into_iter(self) -> Self::IntoIter {
+        let set = ManuallyDrop::new(self);
+        IntoItems {
+            array: unsafe { ptr::read(&set.array) },
+            index: 0,
+            len: set.len,
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<A, C, H> FromIterator<A::Item> for TinyVecSet<A, C, H>
+where
+    A: Array,
+    H: SpillHeap<Item = A::Item>,
+    H::Alloc: Default,
+    C: Compare<A::Item> + Default,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = A::Item>,
+    {
+        let mut set = TinyVecSet::default();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<A, C> ArraySet<A, C>
+where
+    A: Storage,
+{
+    /**
+    Creates a new empty ArraySet that orders its values with the given comparator
+
+    # Example
+    ```
+    use tinymap::{compare::{Natural, Reverse}, *};
+
+    let mut set = ArraySet::<[Inner<i32>; 10], _>::with_comparator(Reverse(Natural));
+    set.insert(1);
+    set.insert(2);
+    assert_eq!(set.iter().copied().collect::<Vec<_>>(), [2, 1]);
+    ```
+    */
+    pub fn with_comparator(cmp: C) -> Self
+    where
+        A: Array,
+    {
+        ArraySet {
+            array: unsafe { zeroed() },
+            len: 0,
+            cmp,
+        }
+    }
+    /**
+    Returns a reference to the set's comparator
+    */
+    pub fn comparator(&self) -> &C {
+        &self.cmp
+    }
+    #[cfg(feature = "alloc")]
+    pub(crate) fn storage(&self) -> &A {
+        &self.array
+    }
+    /**
+    Clears the set, removing all elements
+
+    # Example
+    ```
+    use tinymap::*;
+
+    let mut v = ArraySet::<[Inner<i32>; 10]>::new();
+    v.insert(1);
+    v.clear();
+    assert!(v.is_empty());
+    ```
+    */
+    pub fn clear(&mut self) {
+        let len = replace(&mut self.len, 0);
+        for value in &mut self.array.as_mut_slice()[..len] {
+            unsafe { value.as_mut_ptr().drop_in_place() };
+        }
+    }
+    /**
+    Returns the number of elements in the set
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut v = ArraySet::<[Inner<i32>; 10]>::new();
+    assert_eq!(v.len(), 0);
+    v.insert(1);
+    assert_eq!(v.len(), 1);
+    ```
+    */
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    /**
+    Returns `true` if the set contains no elements
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut v = ArraySet::<[Inner<i32>; 10]>::new();
+    assert!(v.is_empty());
+    v.insert(1);
+    assert!(!v.is_empty());
+    ```
+    */
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /**
+    Returns the maximum number of elements the set can contain
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut a = ArraySet::<[Inner<i32>; 10]>::new();
+    assert_eq!(10, a.capacity());
+    ```
+    */
+    pub fn capacity(&self) -> usize {
+        self.array.capacity()
+    }
+    /**
+    Gets an iterator over the entries of the set, sorted
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let set: ArraySet<[Inner<i32>; 3]> = [3, 1, 2].iter().copied().collect();
+    let mut set_iter = set.iter();
+    assert_eq!(set_iter.next(), Some(&1));
+    assert_eq!(set_iter.next(), Some(&2));
+    assert_eq!(set_iter.next(), Some(&3));
+    assert_eq!(set_iter.next(), None);
+    ```
+    */
+    pub fn iter(&self) -> Iter<'_, A::Item> {
+        Iter {
+            iter: self.array.as_slice()[..self.len].iter(),
+        }
+    }
+    fn find<Q>(&self, value: &Q) -> Result<usize, usize>
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Item>,
+    {
+        self.as_slice()
+            .binary_search_by(|v| self.cmp.compare(value, v).reverse())
+    }
+    /**
+    Returns true if the set contains a value for the specified value
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let set: ArraySet<[Inner<_>; 3]> = [1, 2, 3].iter().copied().collect();
+    assert_eq!(set.contains(&1), true);
+    assert_eq!(set.contains(&4), false);
+    ```
+    */
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Item>,
+    {
+        self.find(value).is_ok()
+    }
+    /**
+    Returns a reference to the value if it is present
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut set: ArraySet<[Inner<_>; 3]> = [1, 2, 3].iter().copied().collect();
+    assert_eq!(set.get(&2), Some(&2));
+    assert_eq!(set.get(&4), None);
+    ```
+    */
+    pub fn get<Q>(&self, value: &Q) -> Option<&A::Item>
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Item>,
+    {
+        if let Ok(i) = self.find(value) {
+            Some(unsafe { self.array.as_slice()[i].as_ptr().as_ref() }.unwrap())
+        } else {
+            None
+        }
+    }
+    /**
+    Gets the set's values as a sorted slice
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let set: ArraySet<[Inner<_>; 3]> = [3, 1, 2].iter().copied().collect();
+    assert_eq!(set.as_slice(), &[1, 2, 3]);
+    ```
+    */
+    pub fn as_slice(&self) -> &[A::Item] {
+        unsafe { slice::from_raw_parts(self.array.as_slice().as_ptr() as *const A::Item, self.len) }
+    }
+    /**
+    Returns a reference to the value at the given position in the set's sort order
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let set: ArraySet<[Inner<_>; 3]> = [3, 1, 2].iter().copied().collect();
+    assert_eq!(set.get_index(0), Some(&1));
+    assert_eq!(set.get_index(2), Some(&3));
+    assert_eq!(set.get_index(3), None);
+    ```
+    */
+    pub fn get_index(&self, index: usize) -> Option<&A::Item> {
+        if index < self.len {
+            Some(unsafe { self.array.as_slice()[index].as_ptr().as_ref() }.unwrap())
+        } else {
+            None
+        }
+    }
+    /**
+    Removes the value at the given position in the set's sort order and returns it
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut set: ArraySet<[Inner<_>; 3]> = [3, 1, 2].iter().copied().collect();
+    assert_eq!(set.remove_index(1), Some(2));
+    assert_eq!(set.remove_index(2), None);
+    assert_eq!(set.len(), 2);
+    ```
+    */
+    pub fn remove_index(&mut self, index: usize) -> Option<A::Item> {
+        if index < self.len {
+            Some(self.remove_at(index))
+        } else {
+            None
+        }
+    }
+    fn remove_at(&mut self, i: usize) -> A::Item {
+        let slice = self.array.as_mut_slice();
+        let value = replace(&mut slice[i], Inner::uninit());
+        for j in (i + 1)..self.len {
+            slice.swap(j - 1, j);
+        }
+        self.len -= 1;
+        unsafe { value.assume_init() }
+    }
+    /**
+    Returns the position of the value in the set's sort order, if it is present
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let set: ArraySet<[Inner<_>; 3]> = [10, 20, 30].iter().copied().collect();
+    assert_eq!(set.index_of(&20), Some(1));
+    assert_eq!(set.index_of(&15), None);
+    ```
+    */
+    pub fn index_of<Q>(&self, value: &Q) -> Option<usize>
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Item>,
+    {
+        self.find(value).ok()
+    }
+    /**
+    Returns the number of values in the set that are less than the given value
+
+    The value does not have to be present in the set.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let set: ArraySet<[Inner<_>; 3]> = [10, 20, 30].iter().copied().collect();
+    assert_eq!(set.rank(&5), 0);
+    assert_eq!(set.rank(&20), 1);
+    assert_eq!(set.rank(&25), 2);
+    ```
+    */
+    pub fn rank<Q>(&self, value: &Q) -> usize
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Item>,
+    {
+        match self.find(value) {
+            Ok(i) | Err(i) => i,
+        }
+    }
+    /**
+    Returns the position of the first value for which the predicate returns `false`
+
+    The set is assumed to be partitioned by the predicate, meaning that it returns `true`
+    for every value before some position and `false` for every value from that position onward.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let set: ArraySet<[Inner<_>; 3]> = [1, 4, 9].iter().copied().collect();
+    assert_eq!(set.partition_point(|x| x * x < 20), 2);
+    ```
+    */
+    pub fn partition_point<P>(&self, mut pred: P) -> usize
+    where
+        P: FnMut(&A::Item) -> bool,
+    {
+        self.array.as_slice()[..self.len]
+            .partition_point(|value| pred(unsafe { value.as_ptr().as_ref() }.unwrap()))
+    }
+    /**
+    Moves the values into a set with a different array type, panicking if they do not fit
+
+    The values are moved in order, without any searching.
+
+    # Panics
+
+    Panics if the set has more values than the new array's capacity
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let set: ArraySet<[Inner<_>; 2]> = [1, 2].iter().copied().collect();
+
+    let mut bigger = set.resize_into::<[Inner<i32>; 4]>();
+    bigger.insert(3);
+    assert_eq!(bigger.len(), 3);
+    ```
+    */
+    pub fn resize_into<B>(self) -> ArraySet<B, C>
+    where
+        B: Array<Item = A::Item>,
+    {
+        match self.try_convert() {
+            Ok(set) => set,
+            Err(_) => panic!("Conversion would excede capacity"),
+        }
+    }
+    /**
+    Moves the values into a set with a different array type
+
+    Returns the set unchanged if its values do not fit in the new array.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let set: ArraySet<[Inner<_>; 4]> = [1, 2, 3].iter().copied().collect();
+
+    let set = set.try_convert::<[Inner<i32>; 2]>().unwrap_err();
+    assert_eq!(set.len(), 3);
+    let set = set.try_convert::<[Inner<i32>; 3]>().ok().unwrap();
+    assert_eq!(set.iter().copied().collect::<Vec<_>>(), [1, 2, 3]);
+    ```
+    */
+    pub fn try_convert<B>(self) -> Result<ArraySet<B, C>, Self>
+    where
+        B: Array<Item = A::Item>,
+    {
+        if self.len > B::CAPACITY {
+            return Err(self);
         }
+        let mut set = ManuallyDrop::new(self);
+        let mut new = ArraySet::<B, C>::with_comparator(unsafe { ptr::read(&set.cmp) });
+        unsafe {
+            ptr::copy_nonoverlapping(
+                set.array.as_slice().as_ptr(),
+                new.array.as_mut_slice().as_mut_ptr(),
+                set.len,
+            );
+            ptr::drop_in_place(&mut set.array);
+        }
+        new.len = set.len;
+        Ok(new)
     }
 }
 
-impl<A> Clone for ArraySet<A>
+impl<A, C> ArraySet<A, C>
 where
-    A: Array,
-    A::Item: Clone,
+    A: Array + Copy,
+    C: Copy,
 {
-    fn clone(&self) -> Self {
-        let mut array: A = unsafe { zeroed() };
-        let len = self.len;
-        for (i, item) in self.iter().enumerate() {
-            array.as_mut_slice()[i] = Inner::new(item.clone());
+    /**
+    Get a copy of this set
+
+    Because of the way its internals work, ArraySet must implement `Drop`, so it cannot implement `Copy`.
+
+    However, that does not make a copy any less trivial with the right array type, hence this function.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut set = ArraySet::<[Inner<i32>; 10]>::new();
+    set.insert(1);
+    set.insert(2);
+    set.insert(3);
+
+    let copy = set.copy();
+    assert_eq!(3, copy.len());
+    ```
+    */
+    pub fn copy(&self) -> Self {
+        ArraySet {
+            array: self.array,
+            len: self.len,
+            cmp: self.cmp,
         }
-        ArraySet { array, len }
     }
 }
 
-impl<A> ArraySet<A>
+impl<A, C> ArraySet<A, C>
 where
-    A: Array,
+    A: Storage,
+    C: Compare<A::Item>,
 {
     /**
-    Creates a new empty ArraySet
+    Inserts an value into the set
+
+    If the set did not have this value present, true is returned.
+
+    If the set did have this value present, `false` is returned, and the entry is not updated.
+
+    # Panics
+
+    Panics if insertion would cause the set to excede its capacity.
 
     # Example
+
     ```
     use tinymap::*;
 
     let mut set = ArraySet::<[Inner<i32>; 10]>::new();
+
+    assert_eq!(set.insert(2), true);
+    assert_eq!(set.insert(2), false);
+    assert_eq!(set.len(), 1);
     ```
     */
-    pub fn new() -> Self {
-        Self::default()
+    pub fn insert(&mut self, value: A::Item) -> bool {
+        self.try_insert(value)
+            .unwrap_or_else(|_| panic!("Insertion would excede capacity"))
     }
     /**
-    Clears the set, removing all elements
+    Attempts to insert a value into the set
+
+    If the set did not have this value present, true is returned.
+
+    If the set did have this value present, `false` is returned, and the entry is not updated.
+
+    # Errors
+
+    If insertion would cause the set to excede its capacity, this function returns an error containing
+    the value that could not be inserted.
 
     # Example
+
     ```
     use tinymap::*;
 
-    let mut v = ArraySet::<[Inner<i32>; 10]>::new();
-    v.insert(1);
-    v.clear();
-    assert!(v.is_empty());
+    let mut set = ArraySet::<[Inner<i32>; 3]>::new();
+    assert!(set.try_insert(37).is_ok());
+    assert!(set.try_insert(2).is_ok());
+    assert!(set.try_insert(16).is_ok());
+    assert!(set.try_insert(0).is_err());
     ```
     */
-    pub fn clear(&mut self) {
-        *self = Self::new();
+    pub fn try_insert(&mut self, value: A::Item) -> Result<bool, A::Item> {
+        if !self.array.try_grow(self.len + 1) {
+            return Err(value);
+        }
+        match self.find(&value) {
+            Ok(_) => Ok(false),
+            Err(i) => self.try_insert_at(i, value).map(|_| true),
+        }
+    }
+    pub(crate) fn try_insert_at(&mut self, i: usize, value: A::Item) -> Result<(), A::Item> {
+        if !self.array.try_grow(self.len + 1) {
+            return Err(value);
+        }
+        let slice = self.array.as_mut_slice();
+        for j in ((i + 1)..=self.len).rev() {
+            slice.swap(j - 1, j);
+        }
+        let mut value = Inner::new(value);
+        swap(&mut value, &mut slice[i]);
+        self.len += 1;
+        Ok(())
     }
     /**
-    Returns the number of elements in the set
+    Removes a value from the set. Returns whether the value was present in the set.
 
     # Example
 
     ```
     use tinymap::*;
 
-    let mut v = ArraySet::<[Inner<i32>; 10]>::new();
-    assert_eq!(v.len(), 0);
-    v.insert(1);
-    assert_eq!(v.len(), 1);
+    let mut set = ArraySet::<[Inner<i32>; 10]>::new();
+
+    set.insert(2);
+    assert_eq!(set.remove(&2), true);
+    assert_eq!(set.remove(&2), false);
     ```
     */
-    pub fn len(&self) -> usize {
-        self.len
+    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Item>,
+    {
+        if let Ok(i) = self.find(value) {
+            self.remove_at(i);
+            true
+        } else {
+            false
+        }
     }
     /**
-    Returns `true` if the set contains no elements
+    Removes and returns the value in the set, if any, that is equal to the given one
 
     # Example
 
     ```
     use tinymap::*;
 
-    let mut v = ArraySet::<[Inner<i32>; 10]>::new();
-    assert!(v.is_empty());
-    v.insert(1);
-    assert!(!v.is_empty());
+    let mut set: ArraySet<[Inner<_>; 3]> = [1, 2, 3].iter().copied().collect();
+    assert_eq!(set.take(&2), Some(2));
+    assert_eq!(set.take(&2), None);
     ```
     */
-    pub fn is_empty(&self) -> bool {
-        self.len == 0
+    pub fn take<Q>(&mut self, value: &Q) -> Option<A::Item>
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Item>,
+    {
+        if let Ok(i) = self.find(value) {
+            Some(self.remove_at(i))
+        } else {
+            None
+        }
     }
     /**
-    Returns the maximum number of elements the set can contain
+    Returns a guard that gives mutable access to the value in the set that is equal to the given one
+
+    When the guard is dropped, the value is moved to wherever it now sorts. If it has become
+    equal to another value in the set, the changed value replaces the other one, as
+    [`replace`](#method.replace) would. Use [`KeyMut::finish`](struct.KeyMut.html#method.finish)
+    to get the value back instead.
 
     # Example
 
     ```
     use tinymap::*;
 
-    let mut a = ArraySet::<[Inner<i32>; 10]>::new();
-    assert_eq!(10, a.capacity());
+    let mut set: ArraySet<[Inner<_>; 3]> = [1, 2, 3].iter().copied().collect();
+
+    if let Some(mut value) = set.get_key_mut(&1) {
+        *value = 5;
+    }
+    assert_eq!(set.iter().copied().collect::<Vec<_>>(), [2, 3, 5]);
     ```
     */
-    pub fn capacity(&self) -> usize {
-        A::CAPACITY
+    pub fn get_key_mut<Q>(&mut self, value: &Q) -> Option<KeyMut<'_, A, C>>
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Item>,
+    {
+        let index = self.find(value).ok()?;
+        Some(KeyMut { set: self, index })
+    }
+    /// Moves the value at the given index to where it sorts, returning its new index and the
+    /// index of the other value as an error if they have become equal
+    fn restore_order(&mut self, mut i: usize) -> Result<(), (usize, usize)> {
+        while i > 0
+            && self
+                .cmp
+                .compare(&self.as_slice()[i], &self.as_slice()[i - 1])
+                == Ordering::Less
+        {
+            self.array.as_mut_slice().swap(i - 1, i);
+            i -= 1;
+        }
+        while i + 1 < self.len
+            && self
+                .cmp
+                .compare(&self.as_slice()[i], &self.as_slice()[i + 1])
+                == Ordering::Greater
+        {
+            self.array.as_mut_slice().swap(i, i + 1);
+            i += 1;
+        }
+        let values = self.as_slice();
+        if i > 0 && self.cmp.compare(&values[i - 1], &values[i]) == Ordering::Equal {
+            Err((i, i - 1))
+        } else if i + 1 < self.len
+            && self.cmp.compare(&values[i], &values[i + 1]) == Ordering::Equal
+        {
+            Err((i, i + 1))
+        } else {
+            Ok(())
+        }
     }
     /**
-    Gets an iterator over the entries of the set, sorted
+    Adds a value to the set, replacing the existing value, if any, that is equal to the given one
+
+    Returns the replaced value.
+
+    # Panics
+
+    Panics if insertion would cause the set to excede its capacity.
 
     # Example
 
     ```
     use tinymap::*;
 
-    let set: ArraySet<[Inner<i32>; 3]> = [3, 1, 2].iter().copied().collect();
-    let mut set_iter = set.iter();
-    assert_eq!(set_iter.next(), Some(&1));
-    assert_eq!(set_iter.next(), Some(&2));
-    assert_eq!(set_iter.next(), Some(&3));
-    assert_eq!(set_iter.next(), None);
+    let mut set = ArraySet::<[Inner<Vec<i32>>; 10]>::new();
+    set.insert(Vec::new());
+
+    assert_eq!(set.get(&[][..]).unwrap().capacity(), 0);
+    set.replace(Vec::with_capacity(10));
+    assert_eq!(set.get(&[][..]).unwrap().capacity(), 10);
     ```
     */
-    pub fn iter(&self) -> Iter<'_, A::Item> {
-        Iter {
-            iter: self.array.as_slice()[..self.len].iter(),
+    pub fn replace(&mut self, value: A::Item) -> Option<A::Item> {
+        match self.find(&value) {
+            Ok(i) => {
+                let old = replace(&mut self.array.as_mut_slice()[i], Inner::new(value));
+                Some(unsafe { old.assume_init() })
+            }
+            Err(_) => {
+                self.insert(value);
+                None
+            }
         }
     }
-    fn find<Q>(&self, value: &Q) -> Result<usize, usize>
-    where
-        A::Item: Borrow<Q>,
-        Q: Ord + ?Sized,
-    {
-        self.array.as_slice()[..self.len].binary_search_by_key(&value.borrow(), |value| {
-            unsafe { value.as_ptr().as_ref() }.unwrap().borrow()
-        })
-    }
     /**
-    Returns true if the set contains a value for the specified value
+    Returns a reference to the value in the set that is equal to the given one,
+    inserting the result of `f` if there is none
+
+    # Panics
+
+    Panics if the value returned by `f` is not equal to the given one,
+    or if insertion would cause the set to excede its capacity.
 
     # Example
 
     ```
     use tinymap::*;
 
-    let set: ArraySet<[Inner<_>; 3]> = [1, 2, 3].iter().copied().collect();
-    assert_eq!(set.contains(&1), true);
-    assert_eq!(set.contains(&4), false);
+    let mut set = ArraySet::<[Inner<String>; 10]>::new();
+    assert_eq!(set.get_or_insert_with("pear", |s| s.to_owned()), "pear");
+    assert_eq!(set.len(), 1);
+    set.get_or_insert_with("pear", |_| unreachable!());
+    assert_eq!(set.len(), 1);
     ```
     */
-    pub fn contains<Q>(&self, value: &Q) -> bool
+    pub fn get_or_insert_with<Q, F>(&mut self, value: &Q, f: F) -> &A::Item
     where
-        A::Item: Borrow<Q>,
-        Q: Ord + ?Sized,
+        Q: ?Sized,
+        C: Compare<Q, A::Item>,
+        F: FnOnce(&Q) -> A::Item,
     {
-        self.find(value).is_ok()
+        let i = match self.find(value) {
+            Ok(i) => i,
+            Err(i) => {
+                let new = f(value);
+                assert!(
+                    self.cmp.compare(value, &new) == Ordering::Equal,
+                    "New value is not equal to the given one"
+                );
+                self.try_insert_at(i, new)
+                    .unwrap_or_else(|_| panic!("Insertion would excede capacity"));
+                i
+            }
+        };
+        unsafe { self.array.as_slice()[i].as_ptr().as_ref() }.unwrap()
     }
     /**
-    Returns a reference to the value if it is present
+    Returns a reference to the value in the set that is equal to the given one,
+    inserting the given one if there is none
+
+    # Panics
+
+    Panics if insertion would cause the set to excede its capacity.
 
     # Example
 
     ```
     use tinymap::*;
 
-    let mut set: ArraySet<[Inner<_>; 3]> = [1, 2, 3].iter().copied().collect();
-    assert_eq!(set.get(&2), Some(&2));
-    assert_eq!(set.get(&4), None);
+    let mut set = ArraySet::<[Inner<i32>; 10]>::new();
+    assert_eq!(set.get_or_insert(2), &2);
+    assert_eq!(set.get_or_insert(2), &2);
+    assert_eq!(set.len(), 1);
     ```
     */
-    pub fn get<Q>(&self, value: &Q) -> Option<&A::Item>
-    where
-        A::Item: Borrow<Q>,
-        Q: Ord + ?Sized,
-    {
-        if let Ok(i) = self.find(value) {
-            Some(&unsafe { self.array.as_slice()[i].as_ptr().as_ref() }.unwrap())
-        } else {
-            None
+    pub fn get_or_insert(&mut self, value: A::Item) -> &A::Item {
+        self.entry(value).or_insert()
+    }
+    /**
+    Gets the given value's corresponding entry in the set for in-place manipulation
+
+    # Example
+
+    ```
+    use tinymap::{array_set::Entry, *};
+
+    let mut set = ArraySet::<[Inner<&str>; 2]>::new();
+
+    // only one search is needed to check for the value and insert it
+    match set.entry("apple") {
+        Entry::Vacant(entry) => {
+            assert_eq!(entry.insert(), &"apple");
         }
+        Entry::Occupied(_) => unreachable!(),
     }
-}
+    assert!(matches!(set.entry("apple"), Entry::Occupied(_)));
 
-impl<A> ArraySet<A>
-where
-    A: Array + Copy,
-{
+    assert!(set.entry("pear").or_try_insert().is_ok());
+    assert_eq!(set.entry("plum").or_try_insert(), Err("plum"));
+    ```
+    */
+    pub fn entry(&mut self, value: A::Item) -> Entry<'_, A, C> {
+        match self.find(&value) {
+            Ok(index) => Entry::Occupied(OccupiedEntry { set: self, index }),
+            Err(index) => Entry::Vacant(VacantEntry {
+                set: self,
+                index,
+                value,
+            }),
+        }
+    }
     /**
-    Get a copy of this set
-
-    Because of the way its internals work, ArraySet must implement `Drop`, so it cannot implement `Copy`.
+    Splits the set in two at the given value
 
-    However, that does not make a copy any less trivial with the right array type, hence this function.
+    Returns a set containing every value greater than or equal to the given value.
+    The smaller values stay in this set.
 
     # Example
 
     ```
     use tinymap::*;
 
-    let mut set = ArraySet::<[Inner<i32>; 10]>::new();
-    set.insert(1);
-    set.insert(2);
-    set.insert(3);
+    let mut a: ArraySet<[Inner<_>; 5]> = [1, 2, 3, 17, 41].iter().copied().collect();
+    let b = a.split_off(&3);
 
-    let copy = set.copy();
-    assert_eq!(3, copy.len());
+    assert_eq!(a.as_slice(), &[1, 2]);
+    assert_eq!(b.as_slice(), &[3, 17, 41]);
     ```
     */
-    pub fn copy(&self) -> Self {
-        ArraySet {
-            array: self.array,
-            len: self.len,
+    pub fn split_off<Q>(&mut self, value: &Q) -> Self
+    where
+        A: EmptyStorage,
+        Q: ?Sized,
+        C: Compare<Q, A::Item> + Clone,
+    {
+        let at = self.rank(value);
+        let mut other = ArraySet {
+            array: self.array.empty_like(),
+            len: 0,
+            cmp: self.cmp.clone(),
+        };
+        if !other.array.try_grow(self.len - at) {
+            panic!("Split would excede capacity");
+        }
+        for i in at..self.len {
+            swap(
+                &mut self.array.as_mut_slice()[i],
+                &mut other.array.as_mut_slice()[i - at],
+            );
         }
+        other.len = self.len - at;
+        self.len = at;
+        other
     }
-}
-
-impl<A> ArraySet<A>
-where
-    A: Array,
-    A::Item: Ord,
-{
     /**
-    Inserts an value into the set
+    Moves all values from `other` into this set, leaving `other` empty
 
-    If the set did not have this value present, true is returned.
-
-    If the set did have this value present, `false` is returned, and the entry is not updated.
+    If a value from `other` is already present in this set, the value from `other` replaces it.
 
     # Panics
 
-    Panics if insertion would cause the set to excede its capacity.
+    Panics if appending would cause the set to excede its capacity.
 
     # Example
 
     ```
     use tinymap::*;
 
-    let mut set = ArraySet::<[Inner<i32>; 10]>::new();
+    let mut a: ArraySet<[Inner<_>; 5]> = [1, 2, 3].iter().copied().collect();
+    let mut b: ArraySet<[Inner<_>; 5]> = [3, 4, 5].iter().copied().collect();
 
-    assert_eq!(set.insert(2), true);
-    assert_eq!(set.insert(2), false);
-    assert_eq!(set.len(), 1);
+    a.append(&mut b);
+
+    assert_eq!(a.as_slice(), &[1, 2, 3, 4, 5]);
+    assert!(b.is_empty());
     ```
     */
-    pub fn insert(&mut self, value: A::Item) -> bool {
-        self.try_insert(value)
-            .unwrap_or_else(|_| panic!("Insertion would excede capacity"))
+    pub fn append(&mut self, other: &mut Self) {
+        self.try_append(other)
+            .unwrap_or_else(|_| panic!("Append would excede capacity"))
     }
     /**
-    Attempts to insert a value into the set
+    Attempts to move all values from `other` into this set, leaving `other` empty
 
-    If the set did not have this value present, true is returned.
+    If a value from `other` is already present in this set, the value from `other` replaces it.
 
-    If the set did have this value present, `false` is returned, and the entry is not updated.
+    The two sets are merged in two linear passes: the first counts the merged values, so that
+    nothing is moved if they do not fit.
 
     # Errors
 
-    If insertion would cause the set to excede its capacity, this function returns an error containing
-    the value that could not be inserted.
+    If appending would cause the set to excede its capacity, this function returns an error
+    and leaves both sets unchanged.
 
     # Example
 
     ```
     use tinymap::*;
 
-    let mut set = ArraySet::<[Inner<i32>; 3]>::new();
-    assert!(set.try_insert(37).is_ok());
-    assert!(set.try_insert(2).is_ok());
-    assert!(set.try_insert(16).is_ok());
-    assert!(set.try_insert(0).is_err());
+    let mut a: ArraySet<[Inner<_>; 3]> = [1, 2].iter().copied().collect();
+    let mut b: ArraySet<[Inner<_>; 3]> = [2, 3].iter().copied().collect();
+    assert!(a.try_append(&mut b).is_ok());
+    assert_eq!(a.len(), 3);
+
+    b.insert(4);
+    assert_eq!(a.try_append(&mut b), Err(CapacityError));
+    assert_eq!(b.len(), 1);
     ```
     */
-    pub fn try_insert(&mut self, value: A::Item) -> Result<bool, A::Item> {
-        if self.len == A::CAPACITY {
-            return Err(value);
+    pub fn try_append(&mut self, other: &mut Self) -> Result<(), CapacityError> {
+        let total = self.merged_len(other);
+        if !self.array.try_grow(total) {
+            return Err(CapacityError);
         }
-        match self.find(&value) {
-            Ok(_) => Ok(false),
-            Err(i) => {
-                let slice = self.array.as_mut_slice();
-                for j in ((i + 1)..=self.len).rev() {
-                    slice.swap(j - 1, j);
+        // Leak rather than double-drop if a comparison or drop panics
+        let mut i = replace(&mut self.len, 0);
+        let mut j = replace(&mut other.len, 0);
+        let mut k = total;
+        let ours = self.array.as_mut_slice();
+        let theirs = other.array.as_mut_slice();
+        while j > 0 {
+            k -= 1;
+            let order = if i > 0 {
+                let a = unsafe { ours[i - 1].as_ptr().as_ref() }.unwrap();
+                let b = unsafe { theirs[j - 1].as_ptr().as_ref() }.unwrap();
+                self.cmp.compare(a, b)
+            } else {
+                Ordering::Less
+            };
+            match order {
+                Ordering::Greater => {
+                    ours.swap(i - 1, k);
+                    i -= 1;
+                }
+                Ordering::Less => {
+                    swap(&mut ours[k], &mut theirs[j - 1]);
+                    j -= 1;
+                }
+                Ordering::Equal => {
+                    unsafe { ours[i - 1].as_mut_ptr().drop_in_place() };
+                    swap(&mut ours[k], &mut theirs[j - 1]);
+                    i -= 1;
+                    j -= 1;
+                }
+            }
+        }
+        self.len = total;
+        Ok(())
+    }
+    fn merged_len(&self, other: &Self) -> usize {
+        let (ours, theirs) = (self.as_slice(), other.as_slice());
+        let (mut i, mut j, mut len) = (0, 0, 0);
+        while i < ours.len() && j < theirs.len() {
+            match self.cmp.compare(&ours[i], &theirs[j]) {
+                Ordering::Less => i += 1,
+                Ordering::Greater => j += 1,
+                Ordering::Equal => {
+                    i += 1;
+                    j += 1;
                 }
-                let mut value = Inner::new(value);
-                swap(&mut value, &mut slice[i]);
-                self.len += 1;
-                Ok(true)
             }
+            len += 1;
         }
+        len + (ours.len() - i) + (theirs.len() - j)
     }
     /**
-    Removes a value from the set. Returns whether the value was present in the set.
+    Gets an iterator over the values in this set that are also keys in `other`, along with `()` for this side and the value from `other`
+
+    `other` can be anything that iterates over references to a map or set in ascending key order,
+    such as another map or set from this crate. Both sides are walked in a single linear pass.
+    See the [`join`](../join/index.html) module for details.
 
     # Example
 
     ```
     use tinymap::*;
 
-    let mut set = ArraySet::<[Inner<i32>; 10]>::new();
+    let mut fruit = ArraySet::<[Inner<&str>; 4]>::new();
+    fruit.insert("apple");
+    fruit.insert("pear");
+    fruit.insert("plum");
 
-    set.insert(2);
-    assert_eq!(set.remove(&2), true);
-    assert_eq!(set.remove(&2), false);
+    let mut stock = ArrayMap::<[Inner<(&str, u32)>; 4]>::new();
+    stock.insert("pear", 10);
+    stock.insert("plum", 0);
+    stock.insert("quince", 5);
+
+    // Sets join as if they had `()` values
+    let joined: Vec<_> = fruit.join(&stock).collect();
+    assert_eq!(joined, [(&"pear", (), &10), (&"plum", (), &0)]);
     ```
     */
-    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    pub fn join<'a, I>(&'a self, other: I) -> Join<Iter<'a, A::Item>, I::IntoIter, &'a C>
     where
-        A::Item: Borrow<Q>,
-        Q: Ord + ?Sized,
+        I: IntoIterator,
+        I::Item: JoinItem<Key = &'a A::Item>,
     {
-        if let Ok(i) = self.find(value) {
-            let slice = self.array.as_mut_slice();
-            let remove_value = replace(&mut slice[i], Inner::uninit());
-            unsafe { remove_value.assume_init() };
-            for j in (i + 1)..self.len {
-                slice.swap(j - 1, j);
-            }
-            self.len -= 1;
-            true
-        } else {
-            false
-        }
+        Join::new(self.iter(), other.into_iter(), &self.cmp)
+    }
+    /**
+    Gets an iterator over the values in this set, along with `()` for this side and, if present, the value from `other`
+
+    `other` can be anything that iterates over references to a map or set in ascending key order,
+    such as another map or set from this crate. Both sides are walked in a single linear pass.
+    See the [`join`](../join/index.html) module for details.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut fruit = ArraySet::<[Inner<&str>; 4]>::new();
+    fruit.insert("apple");
+    fruit.insert("pear");
+    fruit.insert("plum");
+
+    let mut stock = ArrayMap::<[Inner<(&str, u32)>; 4]>::new();
+    stock.insert("pear", 10);
+    stock.insert("plum", 0);
+    stock.insert("quince", 5);
+
+    let joined: Vec<_> = fruit.left_join(&stock).collect();
+    assert_eq!(joined, [
+        (&"apple", (), None),
+        (&"pear", (), Some(&10)),
+        (&"plum", (), Some(&0)),
+    ]);
+    ```
+    */
+    pub fn left_join<'a, I>(&'a self, other: I) -> LeftJoin<Iter<'a, A::Item>, I::IntoIter, &'a C>
+    where
+        I: IntoIterator,
+        I::Item: JoinItem<Key = &'a A::Item>,
+    {
+        LeftJoin::new(self.iter(), other.into_iter(), &self.cmp)
+    }
+    /**
+    Gets an iterator over the keys in either this set or `other`, along with `()` for this side if the key is in this set and the value from `other` if it is there
+
+    `other` can be anything that iterates over references to a map or set in ascending key order,
+    such as another map or set from this crate. Both sides are walked in a single linear pass.
+    See the [`join`](../join/index.html) module for details.
+
+    # Example
+
+    ```
+    use tinymap::{join::EitherOrBoth, *};
+
+    let mut fruit = ArraySet::<[Inner<&str>; 4]>::new();
+    fruit.insert("apple");
+    fruit.insert("pear");
+    fruit.insert("plum");
+
+    let mut stock = ArrayMap::<[Inner<(&str, u32)>; 4]>::new();
+    stock.insert("pear", 10);
+    stock.insert("plum", 0);
+    stock.insert("quince", 5);
+
+    let joined: Vec<_> = fruit.outer_join(&stock).collect();
+    assert_eq!(joined, [
+        (&"apple", EitherOrBoth::Left(())),
+        (&"pear", EitherOrBoth::Both((), &10)),
+        (&"plum", EitherOrBoth::Both((), &0)),
+        (&"quince", EitherOrBoth::Right(&5)),
+    ]);
+    ```
+    */
+    pub fn outer_join<'a, I>(&'a self, other: I) -> OuterJoin<Iter<'a, A::Item>, I::IntoIter, &'a C>
+    where
+        I: IntoIterator,
+        I::Item: JoinItem<Key = &'a A::Item>,
+    {
+        OuterJoin::new(self.iter(), other.into_iter(), &self.cmp)
     }
 }
 
-impl<A> fmt::Debug for ArraySet<A>
+impl<A, C> fmt::Debug for ArraySet<A, C>
 where
-    A: Array,
+    A: Storage,
     A::Item: fmt::Debug,
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -360,27 +1590,30 @@ where
     }
 }
 
-impl<A> From<A> for ArraySet<A>
+impl<A, C> From<A> for ArraySet<A, C>
 where
     A: Array,
-    A::Item: Ord,
+    C: Compare<A::Item> + Default,
 {
     fn from(mut array: A) -> Self {
+        let cmp = C::default();
         array.as_mut_slice().sort_unstable_by(|a, b| {
-            unsafe { a.as_ptr().as_ref() }
-                .unwrap()
-                .cmp(unsafe { b.as_ptr().as_ref() }.unwrap())
+            cmp.compare(
+                unsafe { a.as_ptr().as_ref() }.unwrap(),
+                unsafe { b.as_ptr().as_ref() }.unwrap(),
+            )
         });
         ArraySet {
             array,
             len: A::CAPACITY,
+            cmp,
         }
     }
 }
 
-impl<A> PartialEq for ArraySet<A>
+impl<A, C> PartialEq for ArraySet<A, C>
 where
-    A: Array,
+    A: Storage,
     A::Item: PartialEq,
 {
     fn eq(&self, other: &Self) -> bool {
@@ -388,16 +1621,16 @@ where
     }
 }
 
-impl<A> Eq for ArraySet<A>
+impl<A, C> Eq for ArraySet<A, C>
 where
-    A: Array,
+    A: Storage,
     A::Item: Eq,
 {
 }
 
-impl<'a, A> IntoIterator for &'a ArraySet<A>
+impl<'a, A, C> IntoIterator for &'a ArraySet<A, C>
 where
-    A: Array,
+    A: Storage,
 {
     type Item = &'a A::Item;
     type IntoIter = Iter<'a, A::Item>;
@@ -407,7 +1640,7 @@ where
 }
 
 #[cfg(feature = "alloc")]
-impl<A> IntoIterator for ArraySet<A>
+impl<A, C> IntoIterator for ArraySet<A, C>
 where
     A: Array,
 {
@@ -415,19 +1648,47 @@ where
     type IntoIter = IntoIter<A::Item>;
     fn into_iter(mut self) -> Self::IntoIter {
         let array = replace(&mut self.array, unsafe { zeroed() });
+        let len = replace(&mut self.len, 0);
         let mut vec = array.into_boxed_slice().into_vec();
-        vec.truncate(self.len());
+        vec.truncate(len);
         IntoIter {
             iter: vec.into_iter(),
         }
     }
 }
 
-/// Elements from the iterator beyond the set's capacity will be discarded.
-impl<A> FromIterator<A::Item> for ArraySet<A>
+/// The values are moved into the vector in the set's order.
+#[cfg(feature = "alloc")]
+impl<A, C> From<ArraySet<A, C>> for Vec<A::Item>
+where
+    A: Array,
+{
+    fn from(mut set: ArraySet<A, C>) -> Self {
+        let array = replace(&mut set.array, unsafe { zeroed() });
+        let len = replace(&mut set.len, 0);
+        let mut vec = ManuallyDrop::new(array.into_boxed_slice().into_vec());
+        // `Inner<T>` has the same layout as `T`, and the first `len` values are initialized
+        unsafe { Vec::from_raw_parts(vec.as_mut_ptr() as *mut _, len, vec.capacity()) }
+    }
+}
+
+/// The tree is ordered by the values' `Ord` implementation, whatever the set's comparator.
+#[cfg(feature = "alloc")]
+impl<A, C> From<ArraySet<A, C>> for std::collections::BTreeSet<A::Item>
 where
     A: Array,
     A::Item: Ord,
+{
+    fn from(set: ArraySet<A, C>) -> Self {
+        set.into_iter().collect()
+    }
+}
+
+/// Elements from the iterator beyond the set's capacity will be discarded.
+impl<A, C> FromIterator<A::Item> for ArraySet<A, C>
+where
+    A: Array,
+    C: Compare<A::Item> + Default,
 {
     fn from_iter<I>(iter: I) -> Self
     where
@@ -442,10 +1703,10 @@ where
 }
 
 /// Elements from the iterator beyond the set's capacity will be discarded.
-impl<A> Extend<A::Item> for ArraySet<A>
+impl<A, C> Extend<A::Item> for ArraySet<A, C>
 where
-    A: Array,
-    A::Item: Ord,
+    A: Storage,
+    C: Compare<A::Item>,
 {
     fn extend<I>(&mut self, iter: I)
     where
@@ -459,9 +1720,9 @@ where
     }
 }
 
-impl<A> Drop for ArraySet<A>
+impl<A, C> Drop for ArraySet<A, C>
 where
-    A: Array,
+    A: Storage,
 {
     fn drop(&mut self) {
         for i in 0..self.len {
@@ -486,6 +1747,53 @@ impl<T> Iterator for IntoIter<T> {
     }
 }
 
+#[cfg(feature = "alloc")]
+impl<T> Drop for IntoIter<T> {
+    fn drop(&mut self) {
+        self.for_each(drop);
+    }
+}
+
+/// A consuming iterator over the values of an ArraySet that does not need to allocate
+///
+/// This is the iterator that a TinyVecSet turns into, whatever storage it has spilled into.
+pub struct IntoItems<A>
+where
+    A: Storage,
+{
+    array: A,
+    index: usize,
+    len: usize,
+}
+
+impl<A> Iterator for IntoItems<A>
+where
+    A: Storage,
+{
+    type Item = A::Item;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index == self.len {
+            return None;
+        }
+        let value = replace(&mut self.array.as_mut_slice()[self.index], Inner::uninit());
+        self.index += 1;
+        Some(unsafe { value.assume_init() })
+    }
+}
+
+impl<A> Drop for IntoItems<A>
+where
+    A: Storage,
+{
+    fn drop(&mut self) {
+        for i in self.index..self.len {
+            unsafe {
+                self.array.as_mut_slice()[i].as_mut_ptr().drop_in_place();
+            }
+        }
+    }
+}
+
 /// An iterator over references to the values in an ArraySet
 pub struct Iter<'a, T> {
     iter: core::slice::Iter<'a, Inner<T>>,
@@ -499,3 +1807,269 @@ impl<'a, T> Iterator for Iter<'a, T> {
             .map(|value| unsafe { value.as_ptr().as_ref() }.unwrap())
     }
 }
+
+/// A view into a single value in a set, which may either be vacant or occupied.
+pub enum Entry<'a, A, C = Natural>
+where
+    A: Storage,
+{
+    /// A vacant entry.
+    Vacant(VacantEntry<'a, A, C>),
+    /// An occupied entry.
+    Occupied(OccupiedEntry<'a, A, C>),
+}
+
+impl<'a, A, C> Entry<'a, A, C>
+where
+    A: Storage,
+    C: Compare<A::Item>,
+{
+    /**
+    Ensures the value is in the set by inserting it if the entry is vacant,
+    and returns a reference to the value in the set.
+
+    # Panics
+
+    Panics if insertion would cause the set to excede its capacity.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut set = ArraySet::<[Inner<&str>; 10]>::new();
+    assert_eq!(set.entry("poneyland").or_insert(), &"poneyland");
+    assert!(set.contains("poneyland"));
+    ```
+    */
+    pub fn or_insert(self) -> &'a A::Item {
+        match self {
+            Entry::Vacant(entry) => entry.insert(),
+            Entry::Occupied(entry) => entry.into_ref(),
+        }
+    }
+    /**
+    Attempts to ensure the value is in the set by inserting it if the entry is vacant,
+    and returns a reference to the value in the set.
+
+    # Errors
+
+    If insertion would cause the set to excede its capacity, this function returns an error containing
+    the value that could not be inserted.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut set = ArraySet::<[Inner<&str>; 1]>::new();
+    assert!(set.entry("poneyland").or_try_insert().is_ok());
+    assert!(set.entry("poneyland").or_try_insert().is_ok());
+    assert_eq!(set.entry("donkeyville").or_try_insert(), Err("donkeyville"));
+    ```
+    */
+    pub fn or_try_insert(self) -> Result<&'a A::Item, A::Item> {
+        match self {
+            Entry::Vacant(entry) => entry.try_insert(),
+            Entry::Occupied(entry) => Ok(entry.into_ref()),
+        }
+    }
+    /**
+    Returns a reference to this entry's value
+
+    For a vacant entry, this is the value that would be inserted.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut set = ArraySet::<[Inner<&str>; 10]>::new();
+    assert_eq!(set.entry("poneyland").get(), &"poneyland");
+    ```
+    */
+    pub fn get(&self) -> &A::Item {
+        match self {
+            Entry::Vacant(entry) => entry.get(),
+            Entry::Occupied(entry) => entry.get(),
+        }
+    }
+}
+
+/// An entry in an ArraySet that is vacant
+pub struct VacantEntry<'a, A, C = Natural>
+where
+    A: Storage,
+{
+    set: &'a mut ArraySet<A, C>,
+    index: usize,
+    value: A::Item,
+}
+
+impl<'a, A, C> VacantEntry<'a, A, C>
+where
+    A: Storage + 'a,
+    C: Compare<A::Item>,
+{
+    /**
+    Gets a reference to the value that would be inserted through the VacantEntry.
+    */
+    pub fn get(&self) -> &A::Item {
+        &self.value
+    }
+    /**
+    Take ownership of the value.
+    */
+    pub fn into_value(self) -> A::Item {
+        self.value
+    }
+    /**
+    Inserts the VacantEntry's value into the set and returns a reference to it.
+
+    # Panics
+
+    Panics if insertion would cause the set to excede its capacity.
+    */
+    pub fn insert(self) -> &'a A::Item {
+        self.try_insert()
+            .unwrap_or_else(|_| panic!("Insertion would excede capacity"))
+    }
+    /**
+    Attempts to insert the VacantEntry's value into the set and return a reference to it.
+
+    # Errors
+
+    If insertion would cause the set to excede its capacity, this function returns an error containing
+    the value that could not be inserted.
+    */
+    pub fn try_insert(self) -> Result<&'a A::Item, A::Item> {
+        let index = self.index;
+        let set = self.set;
+        set.try_insert_at(index, self.value)?;
+        Ok(unsafe { set.array.as_slice()[index].as_ptr().as_ref() }.unwrap())
+    }
+}
+
+/// An entry in an ArraySet that is occupied
+pub struct OccupiedEntry<'a, A, C = Natural>
+where
+    A: Storage,
+{
+    set: &'a mut ArraySet<A, C>,
+    index: usize,
+}
+
+impl<'a, A, C> OccupiedEntry<'a, A, C>
+where
+    A: Storage + 'a,
+    C: Compare<A::Item>,
+{
+    /**
+    Gets a reference to the value in the set
+    */
+    pub fn get(&self) -> &A::Item {
+        unsafe { self.set.array.as_slice()[self.index].as_ptr().as_ref() }.unwrap()
+    }
+    /**
+    Converts the entry into a reference to the value in the set.
+    */
+    pub fn into_ref(self) -> &'a A::Item {
+        unsafe { self.set.array.as_slice()[self.index].as_ptr().as_ref() }.unwrap()
+    }
+    /**
+    Takes the value out of the set, and returns it.
+    */
+    pub fn remove(self) -> A::Item {
+        self.set.remove_at(self.index)
+    }
+}
+
+/**
+A guard that gives mutable access to a value in an ArraySet
+
+Created with [`ArraySet::get_key_mut`](struct.ArraySet.html#method.get_key_mut).
+When the guard is dropped, the value is moved to wherever it now sorts, replacing any
+other value it has become equal to.
+*/
+pub struct KeyMut<'a, A, C = Natural>
+where
+    A: Storage,
+    C: Compare<A::Item>,
+{
+    set: &'a mut ArraySet<A, C>,
+    index: usize,
+}
+
+impl<'a, A, C> KeyMut<'a, A, C>
+where
+    A: Storage,
+    C: Compare<A::Item>,
+{
+    /**
+    Moves the value to wherever it now sorts
+
+    # Errors
+
+    If the value has become equal to another value in the set, the changed value is removed
+    from the set and returned, leaving the other value in place.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut set: ArraySet<[Inner<_>; 3]> = [1, 2].iter().copied().collect();
+
+    let mut value = set.get_key_mut(&1).unwrap();
+    *value = 2;
+    assert_eq!(value.finish(), Err(2));
+    assert_eq!(set.len(), 1);
+    ```
+    */
+    pub fn finish(self) -> Result<(), A::Item> {
+        let mut this = ManuallyDrop::new(self);
+        let index = this.index;
+        match this.set.restore_order(index) {
+            Ok(()) => Ok(()),
+            Err((i, _)) => Err(this.set.remove_at(i)),
+        }
+    }
+}
+
+impl<'a, A, C> Deref for KeyMut<'a, A, C>
+where
+    A: Storage,
+    C: Compare<A::Item>,
+{
+    type Target = A::Item;
+    fn deref(&self) -> &Self::Target {
+        &self.set.as_slice()[self.index]
+    }
+}
+
+impl<'a, A, C> DerefMut for KeyMut<'a, A, C>
+where
+    A: Storage,
+    C: Compare<A::Item>,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe {
+            self.set.array.as_mut_slice()[self.index]
+                .as_mut_ptr()
+                .as_mut()
+        }
+        .unwrap()
+    }
+}
+
+impl<'a, A, C> Drop for KeyMut<'a, A, C>
+where
+    A: Storage,
+    C: Compare<A::Item>,
+{
+    fn drop(&mut self) {
+        if let Err((_, other)) = self.set.restore_order(self.index) {
+            self.set.remove_at(other);
+        }
+    }
+}
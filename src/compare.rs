@@ -0,0 +1,268 @@
+/*!
+Comparators that decide the order of keys in the containers in this crate
+
+Every container takes a comparator type parameter which defaults to [`Natural`](struct.Natural.html),
+the order given by the key's `Ord` implementation. Any type that implements
+[`Compare`](trait.Compare.html) can be used instead, which allows orders that would otherwise
+need a newtype around the key.
+
+```
+use std::cmp::Ordering;
+use tinymap::{compare::*, *};
+
+// Zero-sized comparators can be created with `Default`
+let mut map = ArrayMap::<[Inner<(i32, &str)>; 10], Reverse>::default();
+map.insert(1, "a");
+map.insert(2, "b");
+assert_eq!(map.keys().copied().collect::<Vec<_>>(), [2, 1]);
+
+// Comparators can also carry state
+let by_len = from_fn(|a: &&str, b: &&str| a.len().cmp(&b.len()));
+let mut map = ArrayMap::<[Inner<(&str, i32)>; 10], _>::with_comparator(by_len);
+map.insert("ccc", 3);
+map.insert("a", 1);
+assert_eq!(map.get(&"bbb"), Some(&3));
+```
+
+# Comparators on the heap
+
+`TinyMap` and `TinySet` keep their entries in a `BTreeMap` or `BTreeSet` by default once they move to the heap.
+A tree orders its keys with `Ord` and has no room for a comparator, so it only works with comparators
+that implement [`TreeOrder`](trait.TreeOrder.html), which are the ones that follow the keys' `Ord`
+implementation, either as it is or reversed. Comparators that carry state or order keys in some other way
+need a `VecMap` or `VecSet` heap instead, which keeps the comparator alongside the entries.
+
+```
+use tinymap::{compare::from_fn, *};
+
+let by_abs = from_fn(|a: &i32, b: &i32| a.abs().cmp(&b.abs()));
+let mut set = TinySet::<[Inner<i32>; 2], _, VecSet<_, _>>::with_comparator(by_abs);
+set.extend(vec![3, -2, 1]);
+assert!(set.is_spilled());
+assert!(set.contains(&-3));
+```
+*/
+
+use core::{borrow::Borrow, cmp::Ordering, fmt};
+
+use crate::sealed::Sealed;
+
+/// A comparator that orders values of type `L` relative to values of type `R`
+pub trait Compare<L: ?Sized, R: ?Sized = L> {
+    /// Compare two values
+    fn compare(&self, l: &L, r: &R) -> Ordering;
+}
+
+impl<L, R, C> Compare<L, R> for &C
+where
+    L: ?Sized,
+    R: ?Sized,
+    C: Compare<L, R> + ?Sized,
+{
+    fn compare(&self, l: &L, r: &R) -> Ordering {
+        (**self).compare(l, r)
+    }
+}
+
+/**
+A query type that can be compared with keys of type `K`
+
+This is blanket-implemented for every `Ord` type that the key can be borrowed as,
+which covers the usual `Borrow` lookups. Implementing it for other types allows looking up
+keys by a view that the key cannot be borrowed as.
+
+Because of the blanket implementation, this can only be implemented for types that are not `Ord`,
+which in practice means a newtype of your own. For example, a `(String, u32)` key cannot be looked
+up with a `(&str, u32)` directly, but it can with a newtype that wraps the `&str` and the `u32`.
+
+Lookups with [`Natural`](struct.Natural.html) or [`Reverse`](struct.Reverse.html) accept any
+`Comparable` query. Once a TinyMap or TinySet has moved to a `BTreeMap` or `BTreeSet`, the tree can only
+be searched with queries that its keys can be borrowed as. Finding a key that is equal to any other
+query, as `get`, `contains_key`, `remove` and their relatives do, scans the tree in O(n) instead.
+Lookups by position or bound, such as `rank`, `lower_bound` and `split_off`, keep needing queries
+that the keys can be borrowed as there.
+
+There is no `range` method on any container in this crate. A range is walked with the cursor returned
+by `lower_bound` or `upper_bound`, which accept the same queries as the container's other ordered lookups.
+
+The order must agree with the key's `Ord` implementation.
+
+# Example
+
+```
+use std::cmp::Ordering;
+use tinymap::{compare::Comparable, *};
+
+struct Pair<'a>(&'a str, u32);
+
+impl Comparable<(String, u32)> for Pair<'_> {
+    fn compare(&self, key: &(String, u32)) -> Ordering {
+        (self.0, self.1).cmp(&(key.0.as_str(), key.1))
+    }
+}
+
+let mut map = ArrayMap::<[Inner<((String, u32), i32)>; 3]>::new();
+map.insert((String::from("a"), 1), 10);
+map.insert((String::from("b"), 2), 20);
+map.insert((String::from("c"), 3), 30);
+
+assert_eq!(map.get(&Pair("b", 2)), Some(&20));
+assert!(!map.contains_key(&Pair("b", 3)));
+```
+*/
+pub trait Comparable<K: ?Sized> {
+    /// Compare this query to a key
+    fn compare(&self, key: &K) -> Ordering;
+    /// Runs a search that can only use the query directly if the key can be borrowed as it
+    #[doc(hidden)]
+    fn search<S>(&self, search: S) -> S::Output
+    where
+        S: Search<K>,
+    {
+        search.scan(self)
+    }
+}
+
+impl<Q, K> Comparable<K> for Q
+where
+    Q: Ord + ?Sized,
+    K: Borrow<Q> + ?Sized,
+{
+    fn compare(&self, key: &K) -> Ordering {
+        self.cmp(key.borrow())
+    }
+    fn search<S>(&self, search: S) -> S::Output
+    where
+        S: Search<K>,
+    {
+        search.find(self)
+    }
+}
+
+/// A search of a tree, which can only use a query directly if its keys can be borrowed as it
+#[doc(hidden)]
+pub trait Search<K: ?Sized> {
+    /// The result of the search
+    type Output;
+    /// Searches the tree with a query that the keys can be borrowed as
+    fn find<T>(self, query: &T) -> Self::Output
+    where
+        T: Ord + ?Sized,
+        K: Borrow<T>;
+    /// Searches the tree by comparing its keys with the query one at a time
+    fn scan<Q>(self, query: &Q) -> Self::Output
+    where
+        Q: Comparable<K> + ?Sized;
+}
+
+/// A comparator that uses the natural order given by `Ord`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Natural;
+
+impl<Q, K> Compare<Q, K> for Natural
+where
+    Q: Comparable<K> + ?Sized,
+    K: ?Sized,
+{
+    fn compare(&self, l: &Q, r: &K) -> Ordering {
+        l.compare(r)
+    }
+}
+
+/// A comparator that reverses the order of another comparator
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Reverse<C = Natural>(pub C);
+
+impl<L, R, C> Compare<L, R> for Reverse<C>
+where
+    L: ?Sized,
+    R: ?Sized,
+    C: Compare<L, R>,
+{
+    fn compare(&self, l: &L, r: &R) -> Ordering {
+        self.0.compare(l, r).reverse()
+    }
+}
+
+/**
+A comparator that calls a function
+
+Created with [`from_fn`](fn.from_fn.html)
+*/
+#[derive(Clone, Copy)]
+pub struct FromFn<F>(F);
+
+impl<F> fmt::Debug for FromFn<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FromFn").finish()
+    }
+}
+
+impl<T, F> Compare<T> for FromFn<F>
+where
+    T: ?Sized,
+    F: Fn(&T, &T) -> Ordering,
+{
+    fn compare(&self, l: &T, r: &T) -> Ordering {
+        (self.0)(l, r)
+    }
+}
+
+/**
+Create a comparator from a function
+
+# Example
+
+```
+use tinymap::{compare::*, *};
+
+let mut set = ArraySet::<[Inner<f64>; 10], _>::with_comparator(from_fn(f64::total_cmp));
+set.insert(2.5);
+set.insert(-1.0);
+assert_eq!(set.iter().copied().collect::<Vec<_>>(), [-1.0, 2.5]);
+```
+*/
+pub fn from_fn<T, F>(f: F) -> FromFn<F>
+where
+    T: ?Sized,
+    F: Fn(&T, &T) -> Ordering,
+{
+    FromFn(f)
+}
+
+/**
+A comparator whose order a `BTreeMap` or `BTreeSet` can keep
+
+TinyMap and TinySet move their entries into a tree once they grow beyond their arrays.
+The tree orders its keys with their `Ord` implementation and cannot store a comparator,
+so these containers only accept comparators that follow that order, either as it is or reversed.
+A reversed order is kept by walking the tree from the back.
+
+This trait is sealed and only implemented for [`Natural`](struct.Natural.html) and
+[`Reverse`](struct.Reverse.html) wrapping another `TreeOrder`. A comparator that carries state or orders
+keys in some other way cannot keep a tree's order, so a TinyMap or TinySet with such a comparator
+moves into a `VecMap` or `VecSet` instead.
+*/
+pub trait TreeOrder<K: ?Sized>: Compare<K> + Default + Sealed {
+    /// Whether the comparator reverses the keys' `Ord` implementation
+    const REVERSED: bool;
+}
+
+impl Sealed for Natural {}
+
+impl<C> Sealed for Reverse<C> {}
+
+impl<K> TreeOrder<K> for Natural
+where
+    K: Ord + ?Sized,
+{
+    const REVERSED: bool = false;
+}
+
+impl<K, C> TreeOrder<K> for Reverse<C>
+where
+    K: ?Sized,
+    C: TreeOrder<K>,
+{
+    const REVERSED: bool = !C::REVERSED;
+}
@@ -0,0 +1,127 @@
+use core::mem::size_of;
+
+use deepsize::{Context, DeepSizeOf};
+
+use crate::{
+    heap_size::hash_table, tiny_map::MapHeap, tiny_set::SetHeap, Array, ArrayHashMap, ArrayHashSet,
+    ArrayMap, ArraySet, Inner, MapArray, MapStorage, Storage, TinyHashMap, TinyMap, TinySet,
+};
+
+impl<A, C> DeepSizeOf for ArrayMap<A, C>
+where
+    A: MapStorage,
+    A::Key: DeepSizeOf,
+    A::Value: DeepSizeOf,
+{
+    fn deep_size_of_children(&self, context: &mut Context) -> usize {
+        self.storage().heap_capacity() * size_of::<Inner<(A::Key, A::Value)>>()
+            + self
+                .iter()
+                .map(|(k, v)| k.deep_size_of_children(context) + v.deep_size_of_children(context))
+                .sum::<usize>()
+    }
+}
+
+impl<A, C> DeepSizeOf for ArraySet<A, C>
+where
+    A: Storage,
+    A::Item: DeepSizeOf,
+{
+    fn deep_size_of_children(&self, context: &mut Context) -> usize {
+        self.storage().heap_capacity() * size_of::<Inner<A::Item>>()
+            + self
+                .iter()
+                .map(|value| value.deep_size_of_children(context))
+                .sum::<usize>()
+    }
+}
+
+impl<A, C, H> DeepSizeOf for TinyMap<A, C, H>
+where
+    A: MapArray,
+    A::Key: DeepSizeOf,
+    A::Value: DeepSizeOf,
+    H: MapHeap<C, Key = A::Key, Value = A::Value>,
+{
+    fn deep_size_of_children(&self, context: &mut Context) -> usize {
+        match self {
+            TinyMap::Stack(map) => map.deep_size_of_children(context),
+            TinyMap::Heap(map) => {
+                map.allocation_size()
+                    + map
+                        .iter()
+                        .map(|(k, v)| {
+                            k.deep_size_of_children(context) + v.deep_size_of_children(context)
+                        })
+                        .sum::<usize>()
+            }
+        }
+    }
+}
+
+impl<A, C, H> DeepSizeOf for TinySet<A, C, H>
+where
+    A: Array,
+    A::Item: DeepSizeOf,
+    H: SetHeap<C, Item = A::Item>,
+{
+    fn deep_size_of_children(&self, context: &mut Context) -> usize {
+        match self {
+            TinySet::Stack(set) => set.deep_size_of_children(context),
+            TinySet::Heap(set) => {
+                set.allocation_size()
+                    + set
+                        .iter()
+                        .map(|value| value.deep_size_of_children(context))
+                        .sum::<usize>()
+            }
+        }
+    }
+}
+
+impl<A, S> DeepSizeOf for ArrayHashMap<A, S>
+where
+    A: MapArray,
+    A::Key: DeepSizeOf,
+    A::Value: DeepSizeOf,
+{
+    fn deep_size_of_children(&self, context: &mut Context) -> usize {
+        self.iter()
+            .map(|(k, v)| k.deep_size_of_children(context) + v.deep_size_of_children(context))
+            .sum()
+    }
+}
+
+impl<A, S> DeepSizeOf for ArrayHashSet<A, S>
+where
+    A: Array,
+    A::Item: DeepSizeOf,
+{
+    fn deep_size_of_children(&self, context: &mut Context) -> usize {
+        self.iter()
+            .map(|value| value.deep_size_of_children(context))
+            .sum()
+    }
+}
+
+impl<A, S> DeepSizeOf for TinyHashMap<A, S>
+where
+    A: MapArray,
+    A::Key: DeepSizeOf,
+    A::Value: DeepSizeOf,
+{
+    fn deep_size_of_children(&self, context: &mut Context) -> usize {
+        match self {
+            TinyHashMap::Stack(map) => map.deep_size_of_children(context),
+            TinyHashMap::Heap(map) => {
+                hash_table::<A::Key, A::Value>(map.capacity())
+                    + map
+                        .iter()
+                        .map(|(k, v)| {
+                            k.deep_size_of_children(context) + v.deep_size_of_children(context)
+                        })
+                        .sum::<usize>()
+            }
+        }
+    }
+}
@@ -0,0 +1,86 @@
+/*!
+Differences between two sorted maps
+*/
+
+use core::{cmp::Ordering, iter::Peekable};
+
+use crate::compare::Compare;
+
+/// A single difference between two maps
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffItem<'a, K, V> {
+    /// The key is only in the other map
+    Added(&'a K, &'a V),
+    /// The key is only in this map
+    Removed(&'a K, &'a V),
+    /// The key is in both maps, but with different values. The value from this map comes first.
+    Changed(&'a K, &'a V, &'a V),
+}
+
+impl<'a, K, V> DiffItem<'a, K, V> {
+    /// Get the key that the difference is for
+    pub fn key(&self) -> &'a K {
+        match *self {
+            DiffItem::Added(key, _) | DiffItem::Removed(key, _) | DiffItem::Changed(key, ..) => key,
+        }
+    }
+}
+
+/**
+An iterator over the differences between two maps
+
+Created with [`ArrayMap::diff`](../array_map/struct.ArrayMap.html#method.diff)
+or [`TinyMap::diff`](../tiny_map/enum.TinyMap.html#method.diff)
+*/
+pub struct Diff<I: Iterator, C> {
+    ours: Peekable<I>,
+    theirs: Peekable<I>,
+    cmp: C,
+}
+
+impl<I: Iterator, C> Diff<I, C> {
+    pub(crate) fn new(ours: I, theirs: I, cmp: C) -> Self {
+        Diff {
+            ours: ours.peekable(),
+            theirs: theirs.peekable(),
+            cmp,
+        }
+    }
+}
+
+impl<'a, K, V, I, C> Iterator for Diff<I, C>
+where
+    K: 'a,
+    V: PartialEq + 'a,
+    I: Iterator<Item = (&'a K, &'a V)>,
+    C: Compare<K>,
+{
+    type Item = DiffItem<'a, K, V>;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let order = match (self.ours.peek(), self.theirs.peek()) {
+                (Some(a), Some(b)) => self.cmp.compare(a.0, b.0),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => return None,
+            };
+            match order {
+                Ordering::Less => {
+                    let (key, value) = self.ours.next().unwrap();
+                    return Some(DiffItem::Removed(key, value));
+                }
+                Ordering::Greater => {
+                    let (key, value) = self.theirs.next().unwrap();
+                    return Some(DiffItem::Added(key, value));
+                }
+                Ordering::Equal => {
+                    let (key, a) = self.ours.next().unwrap();
+                    let (_, b) = self.theirs.next().unwrap();
+                    if a != b {
+                        return Some(DiffItem::Changed(key, a, b));
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,325 @@
+/*!
+Heap memory accounting
+
+The [`HeapSize`](trait.HeapSize.html) trait estimates how much heap memory a value owns.
+For the containers in this crate, that is nothing but the heap memory of their keys and values
+while they are on the stack. Once a TinyMap or TinySet moves to the heap, the nodes of its tree
+or the buffer of its `VecMap` or `VecSet` are counted as well.
+
+# Example
+
+```
+use tinymap::*;
+
+let mut map = TinyMap::<[Inner<(i32, i32)>; 2]>::new();
+map.insert(1, 1);
+map.insert(2, 2);
+assert_eq!(map.heap_size(), 0);
+
+map.insert(3, 3);
+assert!(map.heap_size() > 0);
+
+let mut map = TinyMap::<[Inner<(i32, String)>; 2]>::new();
+map.insert(1, String::with_capacity(10));
+assert_eq!(map.heap_size(), 10);
+```
+*/
+
+use core::mem::size_of;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::{
+    tiny_map::MapHeap, tiny_set::SetHeap, Array, ArrayHashMap, ArrayHashSet, ArrayMap, ArraySet,
+    Inner, MapArray, MapStorage, Storage, TinyHashMap, TinyMap, TinySet,
+};
+
+/// A value that can estimate how much heap memory it owns
+pub trait HeapSize {
+    /**
+    Returns an estimate of the number of bytes that the value owns on the heap
+
+    This does not include the size of the value itself, and it does not account for
+    the allocator's overhead.
+    */
+    fn heap_size(&self) -> usize;
+}
+
+macro_rules! no_heap {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl HeapSize for $ty {
+                fn heap_size(&self) -> usize {
+                    0
+                }
+            }
+        )*
+    };
+}
+
+no_heap!(
+    (),
+    bool,
+    char,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    f32,
+    f64,
+);
+
+/// Borrowed values are owned by someone else, so they are not counted.
+impl<T> HeapSize for &T
+where
+    T: ?Sized,
+{
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+impl HeapSize for String {
+    fn heap_size(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl HeapSize for Box<str> {
+    fn heap_size(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T> HeapSize for Box<T>
+where
+    T: HeapSize,
+{
+    fn heap_size(&self) -> usize {
+        size_of::<T>() + (**self).heap_size()
+    }
+}
+
+impl<T> HeapSize for Box<[T]>
+where
+    T: HeapSize,
+{
+    fn heap_size(&self) -> usize {
+        self.len() * size_of::<T>() + self.iter().map(T::heap_size).sum::<usize>()
+    }
+}
+
+impl<T> HeapSize for Vec<T>
+where
+    T: HeapSize,
+{
+    fn heap_size(&self) -> usize {
+        self.capacity() * size_of::<T>() + self.iter().map(T::heap_size).sum::<usize>()
+    }
+}
+
+impl<T> HeapSize for Option<T>
+where
+    T: HeapSize,
+{
+    fn heap_size(&self) -> usize {
+        self.as_ref().map_or(0, T::heap_size)
+    }
+}
+
+impl<T, const N: usize> HeapSize for [T; N]
+where
+    T: HeapSize,
+{
+    fn heap_size(&self) -> usize {
+        self.iter().map(T::heap_size).sum()
+    }
+}
+
+macro_rules! tuple_heap {
+    ($(($($name:ident),*)),*) => {
+        $(
+            #[allow(non_snake_case)]
+            impl<$($name),*> HeapSize for ($($name,)*)
+            where
+                $($name: HeapSize),*
+            {
+                fn heap_size(&self) -> usize {
+                    let ($($name,)*) = self;
+                    0 $(+ $name.heap_size())*
+                }
+            }
+        )*
+    };
+}
+
+tuple_heap!((A), (A, B), (A, B, C), (A, B, C, D));
+
+// The node capacity of the standard library's B-trees
+const BTREE_B: usize = 6;
+const BTREE_MAX: usize = 2 * BTREE_B - 1;
+const BTREE_MIN: usize = BTREE_B - 1;
+
+/**
+Estimates the heap memory used by the nodes of a B-tree with `len` entries
+
+Nodes are assumed to be half way between their minimum and maximum fill,
+and every node is counted as large as an internal node.
+*/
+pub(crate) fn btree_nodes<K, V>(len: usize) -> usize {
+    let node = size_of::<(usize, u16, u16, [(K, V); BTREE_MAX], [usize; BTREE_B * 2])>();
+    len * node * 2 / (BTREE_MAX + BTREE_MIN)
+}
+
+impl<K, V> HeapSize for BTreeMap<K, V>
+where
+    K: HeapSize,
+    V: HeapSize,
+{
+    fn heap_size(&self) -> usize {
+        btree_nodes::<K, V>(self.len())
+            + self
+                .iter()
+                .map(|(k, v)| k.heap_size() + v.heap_size())
+                .sum::<usize>()
+    }
+}
+
+impl<T> HeapSize for BTreeSet<T>
+where
+    T: HeapSize,
+{
+    fn heap_size(&self) -> usize {
+        btree_nodes::<T, ()>(self.len()) + self.iter().map(T::heap_size).sum::<usize>()
+    }
+}
+
+/**
+Estimates the heap memory used by the table of a HashMap with room for `capacity` entries
+
+The standard library's table stores a control byte next to each entry.
+*/
+pub(crate) fn hash_table<K, V>(capacity: usize) -> usize {
+    capacity * (size_of::<(K, V)>() + 1)
+}
+
+impl<K, V, S> HeapSize for HashMap<K, V, S>
+where
+    K: HeapSize,
+    V: HeapSize,
+{
+    fn heap_size(&self) -> usize {
+        hash_table::<K, V>(self.capacity())
+            + self
+                .iter()
+                .map(|(k, v)| k.heap_size() + v.heap_size())
+                .sum::<usize>()
+    }
+}
+
+impl<A, C> HeapSize for ArrayMap<A, C>
+where
+    A: MapStorage,
+    A::Key: HeapSize,
+    A::Value: HeapSize,
+{
+    fn heap_size(&self) -> usize {
+        self.storage().heap_capacity() * size_of::<Inner<(A::Key, A::Value)>>()
+            + self
+                .iter()
+                .map(|(k, v)| k.heap_size() + v.heap_size())
+                .sum::<usize>()
+    }
+}
+
+impl<A, C> HeapSize for ArraySet<A, C>
+where
+    A: Storage,
+    A::Item: HeapSize,
+{
+    fn heap_size(&self) -> usize {
+        self.storage().heap_capacity() * size_of::<Inner<A::Item>>()
+            + self.iter().map(HeapSize::heap_size).sum::<usize>()
+    }
+}
+
+impl<A, C, H> HeapSize for TinyMap<A, C, H>
+where
+    A: MapArray,
+    A::Key: HeapSize,
+    A::Value: HeapSize,
+    H: MapHeap<C, Key = A::Key, Value = A::Value>,
+{
+    fn heap_size(&self) -> usize {
+        match self {
+            TinyMap::Stack(map) => map.heap_size(),
+            TinyMap::Heap(map) => {
+                map.allocation_size()
+                    + map
+                        .iter()
+                        .map(|(k, v)| k.heap_size() + v.heap_size())
+                        .sum::<usize>()
+            }
+        }
+    }
+}
+
+impl<A, C, H> HeapSize for TinySet<A, C, H>
+where
+    A: Array,
+    A::Item: HeapSize,
+    H: SetHeap<C, Item = A::Item>,
+{
+    fn heap_size(&self) -> usize {
+        match self {
+            TinySet::Stack(set) => set.heap_size(),
+            TinySet::Heap(set) => {
+                set.allocation_size() + set.iter().map(|v| v.heap_size()).sum::<usize>()
+            }
+        }
+    }
+}
+
+impl<A, S> HeapSize for ArrayHashMap<A, S>
+where
+    A: MapArray,
+    A::Key: HeapSize,
+    A::Value: HeapSize,
+{
+    fn heap_size(&self) -> usize {
+        self.iter()
+            .map(|(k, v)| k.heap_size() + v.heap_size())
+            .sum()
+    }
+}
+
+impl<A, S> HeapSize for ArrayHashSet<A, S>
+where
+    A: Array,
+    A::Item: HeapSize,
+{
+    fn heap_size(&self) -> usize {
+        self.iter().map(HeapSize::heap_size).sum()
+    }
+}
+
+impl<A, S> HeapSize for TinyHashMap<A, S>
+where
+    A: MapArray,
+    A::Key: HeapSize,
+    A::Value: HeapSize,
+{
+    fn heap_size(&self) -> usize {
+        match self {
+            TinyHashMap::Stack(map) => map.heap_size(),
+            TinyHashMap::Heap(map) => map.heap_size(),
+        }
+    }
+}
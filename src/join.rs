@@ -0,0 +1,210 @@
+/*!
+Sorted merge-joins of maps and sets
+
+The join adapters walk two collections that are sorted by key in a single linear pass.
+They are created with the `join`, `left_join` and `outer_join` methods on the containers in this crate,
+which accept anything that iterates over references to a map or set with the same key type,
+including the crate's own containers and `BTreeMap`s and `BTreeSet`s. Both sides must be sorted
+by the comparator of the container that the join was created from.
+
+Sets take part in joins as if they were maps with `()` values.
+*/
+
+use core::{cmp::Ordering, iter::Peekable};
+
+use crate::compare::Compare;
+
+/// An item from a sorted map or set that can take part in a join
+pub trait JoinItem {
+    /// The key type
+    type Key;
+    /// The value type yielded by a join
+    type Value;
+    /// Get the item's key
+    fn key(&self) -> &Self::Key;
+    /// Split the item into its key and value
+    fn into_parts(self) -> (Self::Key, Self::Value);
+}
+
+impl<'a, K, V> JoinItem for (&'a K, &'a V) {
+    type Key = &'a K;
+    type Value = &'a V;
+    fn key(&self) -> &&'a K {
+        &self.0
+    }
+    fn into_parts(self) -> (&'a K, &'a V) {
+        self
+    }
+}
+
+impl<'a, T> JoinItem for &'a T {
+    type Key = &'a T;
+    type Value = ();
+    fn key(&self) -> &&'a T {
+        self
+    }
+    fn into_parts(self) -> (&'a T, ()) {
+        (self, ())
+    }
+}
+
+/// A value that is present on the left, the right, or both sides of a join
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EitherOrBoth<L, R> {
+    /// Only the left side had the key
+    Left(L),
+    /// Only the right side had the key
+    Right(R),
+    /// Both sides had the key
+    Both(L, R),
+}
+
+impl<L, R> EitherOrBoth<L, R> {
+    /// Get the left value, if there is one
+    pub fn left(self) -> Option<L> {
+        match self {
+            EitherOrBoth::Left(l) | EitherOrBoth::Both(l, _) => Some(l),
+            EitherOrBoth::Right(_) => None,
+        }
+    }
+    /// Get the right value, if there is one
+    pub fn right(self) -> Option<R> {
+        match self {
+            EitherOrBoth::Right(r) | EitherOrBoth::Both(_, r) => Some(r),
+            EitherOrBoth::Left(_) => None,
+        }
+    }
+}
+
+/**
+An iterator over the keys of two sorted collections and the values on either or both sides
+
+Created with the `outer_join` methods on this crate's containers
+*/
+pub struct OuterJoin<I: Iterator, J: Iterator, C> {
+    left: Peekable<I>,
+    right: Peekable<J>,
+    cmp: C,
+}
+
+impl<I: Iterator, J: Iterator, C> OuterJoin<I, J, C> {
+    pub(crate) fn new(left: I, right: J, cmp: C) -> Self {
+        OuterJoin {
+            left: left.peekable(),
+            right: right.peekable(),
+            cmp,
+        }
+    }
+}
+
+impl<'a, K, I, J, C> Iterator for OuterJoin<I, J, C>
+where
+    K: ?Sized + 'a,
+    I: Iterator,
+    I::Item: JoinItem<Key = &'a K>,
+    J: Iterator,
+    J::Item: JoinItem<Key = &'a K>,
+    C: Compare<K>,
+{
+    type Item = (
+        &'a K,
+        EitherOrBoth<<I::Item as JoinItem>::Value, <J::Item as JoinItem>::Value>,
+    );
+    fn next(&mut self) -> Option<Self::Item> {
+        let order = match (self.left.peek(), self.right.peek()) {
+            (Some(l), Some(r)) => self.cmp.compare(l.key(), r.key()),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => return None,
+        };
+        Some(match order {
+            Ordering::Less => {
+                let (key, l) = self.left.next().unwrap().into_parts();
+                (key, EitherOrBoth::Left(l))
+            }
+            Ordering::Greater => {
+                let (key, r) = self.right.next().unwrap().into_parts();
+                (key, EitherOrBoth::Right(r))
+            }
+            Ordering::Equal => {
+                let (key, l) = self.left.next().unwrap().into_parts();
+                let (_, r) = self.right.next().unwrap().into_parts();
+                (key, EitherOrBoth::Both(l, r))
+            }
+        })
+    }
+}
+
+/**
+An iterator over the keys present in both of two sorted collections
+
+Created with the `join` methods on this crate's containers
+*/
+pub struct Join<I: Iterator, J: Iterator, C>(OuterJoin<I, J, C>);
+
+impl<I: Iterator, J: Iterator, C> Join<I, J, C> {
+    pub(crate) fn new(left: I, right: J, cmp: C) -> Self {
+        Join(OuterJoin::new(left, right, cmp))
+    }
+}
+
+impl<'a, K, I, J, C> Iterator for Join<I, J, C>
+where
+    K: ?Sized + 'a,
+    I: Iterator,
+    I::Item: JoinItem<Key = &'a K>,
+    J: Iterator,
+    J::Item: JoinItem<Key = &'a K>,
+    C: Compare<K>,
+{
+    type Item = (
+        &'a K,
+        <I::Item as JoinItem>::Value,
+        <J::Item as JoinItem>::Value,
+    );
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let (key, EitherOrBoth::Both(l, r)) = self.0.next()? {
+                return Some((key, l, r));
+            }
+        }
+    }
+}
+
+/**
+An iterator over the keys of the left of two sorted collections and the matching values on the right
+
+Created with the `left_join` methods on this crate's containers
+*/
+pub struct LeftJoin<I: Iterator, J: Iterator, C>(OuterJoin<I, J, C>);
+
+impl<I: Iterator, J: Iterator, C> LeftJoin<I, J, C> {
+    pub(crate) fn new(left: I, right: J, cmp: C) -> Self {
+        LeftJoin(OuterJoin::new(left, right, cmp))
+    }
+}
+
+impl<'a, K, I, J, C> Iterator for LeftJoin<I, J, C>
+where
+    K: ?Sized + 'a,
+    I: Iterator,
+    I::Item: JoinItem<Key = &'a K>,
+    J: Iterator,
+    J::Item: JoinItem<Key = &'a K>,
+    C: Compare<K>,
+{
+    type Item = (
+        &'a K,
+        <I::Item as JoinItem>::Value,
+        Option<<J::Item as JoinItem>::Value>,
+    );
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.0.next()? {
+                (key, EitherOrBoth::Left(l)) => return Some((key, l, None)),
+                (key, EitherOrBoth::Both(l, r)) => return Some((key, l, Some(r))),
+                (_, EitherOrBoth::Right(_)) => {}
+            }
+        }
+    }
+}
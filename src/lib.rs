@@ -1,5 +1,7 @@
 #![warn(missing_docs, rust_2018_idioms)]
+#![allow(clippy::type_complexity)]
 #![cfg_attr(not(feature = "alloc"), no_std)]
+#![cfg_attr(feature = "allocator_api", feature(allocator_api, btreemap_alloc))]
 
 /*!
 # Description
@@ -10,6 +12,10 @@ and removed.
 
 - [`ArrayMap`](array_map/struct.ArrayMap.html) is an array-backed map
 - [`ArraySet`](array_set/struct.ArraySet.html) is an array-backed set
+- [`SliceMap`](array_map/type.SliceMap.html) and [`SliceSet`](array_set/type.SliceSet.html) are
+  the same structures on top of a borrowed slice
+- [`ArrayHashMap`](array_hash_map/struct.ArrayHashMap.html) and [`ArrayHashSet`](array_hash_set/struct.ArrayHashSet.html)
+  are array-backed hash tables for keys that are `Hash` and `Eq` instead of ordered
 
 If the `alloc` feature is enabled (which it is by default), this crate also
 provides variants of these stack-based structures that automatically move to the
@@ -17,6 +23,14 @@ heap if they grow beyond their array's capacity.
 
 - [`TinyMap`](tiny_map/enum.TinyMap.html) is an auto-allocating map
 - [`TinySet`](tiny_set/enum.TinySet.html) is an auto-allocating set
+- [`BoxedArrayMap`](array_map/type.BoxedArrayMap.html) and [`BoxedArraySet`](array_set/type.BoxedArraySet.html)
+  are like ArrayMap and ArraySet, but with a capacity that is chosen at runtime
+- [`VecMap`](array_map/type.VecMap.html) and [`VecSet`](array_set/type.VecSet.html)
+  are like ArrayMap and ArraySet on top of a sorted `Vec` that grows as needed
+- [`TinyVecMap`](array_map/type.TinyVecMap.html) and [`TinyVecSet`](array_set/type.TinyVecSet.html)
+  are like TinyMap and TinySet, but they move into a sorted `Vec` instead of a B-tree
+  and keep the whole ArrayMap and ArraySet API once they have moved
+- [`TinyHashMap`](tiny_hash_map/enum.TinyHashMap.html) is an ArrayHashMap that moves into a `HashMap`
 
 # Array Types
 
@@ -31,6 +45,15 @@ less efficient.
 - Arrays for ArrayMap must implement [`MapArray`](trait.MapArray.html).
     - Implemented for `[Inner<(K, V)>; N]` for all `K` and `V` and for `N` up to 50
 
+ArrayHashSet and ArrayHashMap use the same arrays, but their capacity may be at most 64.
+
+Most operations only need the weaker [`Storage`](trait.Storage.html) and
+[`MapStorage`](trait.MapStorage.html) traits, which are also implemented for
+`&mut [Inner<T>]` and `&mut [Inner<(K, V)>]`, which SliceSet and SliceMap are built on,
+for boxed slices, which BoxedArraySet and BoxedArrayMap are built on, for vectors,
+which VecSet and VecMap are built on, and for [`Spill`](enum.Spill.html), which TinyVecSet and
+TinyVecMap are built on.
+
 # Macros
 
 Until [const generics](https://github.com/rust-lang/rfcs/blob/master/text/2000-const-generics.md)
@@ -54,6 +77,26 @@ let map = arraymap!(i32 => &str; 10);
 Serde serialization and deserialization for the data structures in this crate can be enabled
 with the `serde` feature.
 
+# Memory Use
+
+The [`HeapSize`](heap_size/trait.HeapSize.html) trait estimates how much heap memory a container
+and its keys and values use. The containers also implement `DeepSizeOf` from the
+[`deepsize`](https://docs.rs/deepsize) crate if the `deepsize` feature is enabled.
+
+# Allocators
+
+The `allocator_api` feature, which needs nightly Rust, lets a TinyVecMap or TinyVecSet move its items
+into a `Vec` in a given allocator. Such a container is created with `new_in` or `with_heap_in`.
+
+It also lets a TinyMap or TinySet move into a `BTreeMap` or `BTreeSet` in another allocator, which is
+named as the container's heap type. A container on the stack has nowhere to keep an allocator, so it creates
+its tree in the allocator's `Default` when it moves to the heap. `new_in` starts it on the heap in a given allocator instead.
+
+# Statistics
+
+The `stats` feature makes TinyMaps and TinySets record how often they move to the heap and how long
+they grow, which helps with choosing their capacities. See the [`stats`](stats/index.html) module.
+
 # Performance
 
 You can run the benchmarks for yourself using `cargo bench`.
@@ -68,26 +111,56 @@ In general, for small array sizes (~<50):
 
 */
 
+pub mod array_hash_map;
+pub mod array_hash_set;
 pub mod array_map;
 pub mod array_set;
+pub mod compare;
+#[cfg(feature = "deepsize")]
+mod deep_size;
+pub mod diff;
+#[cfg(feature = "alloc")]
+pub mod heap_size;
+pub mod join;
 #[cfg(feature = "serde")]
 mod serialize;
+#[cfg(feature = "stats")]
+pub mod stats;
 #[cfg(test)]
 #[cfg(feature = "alloc")]
 mod test;
 #[cfg(feature = "alloc")]
+pub mod tiny_hash_map;
+#[cfg(feature = "alloc")]
 pub mod tiny_map;
 #[cfg(feature = "alloc")]
 pub mod tiny_set;
 
-pub use array_map::ArrayMap;
-pub use array_set::ArraySet;
+pub use array_hash_map::ArrayHashMap;
+pub use array_hash_set::ArrayHashSet;
+pub use array_map::{ArrayMap, SliceMap};
+#[cfg(feature = "alloc")]
+pub use array_map::{BoxedArrayMap, TinyVecMap, VecMap};
+pub use array_set::{ArraySet, SliceSet};
+#[cfg(feature = "alloc")]
+pub use array_set::{BoxedArraySet, TinyVecSet, VecSet};
+#[cfg(feature = "alloc")]
+pub use heap_size::HeapSize;
+#[cfg(feature = "alloc")]
+pub use tiny_hash_map::TinyHashMap;
 #[cfg(feature = "alloc")]
 pub use tiny_map::TinyMap;
 #[cfg(feature = "alloc")]
 pub use tiny_set::TinySet;
 
-use core::mem::MaybeUninit;
+use core::{fmt, mem::MaybeUninit};
+#[cfg(feature = "allocator_api")]
+use std::alloc::Allocator;
+
+mod sealed {
+    /// A supertrait that keeps a public trait from being implemented outside of this crate
+    pub trait Sealed {}
+}
 
 /**
 Create a new ArrayMap with the specified parameters
@@ -194,65 +267,496 @@ macro_rules! tinyset {
 /// An entry in an array
 pub type Inner<T> = MaybeUninit<T>;
 
-/// Behavior for an array
-pub trait Array {
+/// An error indicating that an operation would cause a container to excede its capacity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapacityError;
+
+impl fmt::Display for CapacityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Operation would excede capacity")
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl std::error::Error for CapacityError {}
+
+/// Behavior for the storage of an ArraySet
+pub trait Storage {
     /// The Item type
     type Item;
-    /// The array's capacity
-    const CAPACITY: usize;
-    /// Get a slice into the array
+    /// Get the storage's capacity
+    fn capacity(&self) -> usize;
+    /// Get a slice into the storage
     fn as_slice(&self) -> &[Inner<Self::Item>];
-    /// Get a mutable slice into the array
+    /// Get a mutable slice into the storage
     fn as_mut_slice(&mut self) -> &mut [Inner<Self::Item>];
+    /**
+    Makes sure the storage can hold `capacity` items, growing it if it can
+
+    Returns whether the storage can hold them. Storage with a fixed size only checks its capacity.
+    */
+    fn try_grow(&mut self, capacity: usize) -> bool {
+        capacity <= self.capacity()
+    }
+    /// Get the number of items that the storage has room for on the heap
+    fn heap_capacity(&self) -> usize {
+        0
+    }
+}
+
+/// Behavior for an array
+pub trait Array: Storage + EmptyStorage {
+    /// The array's capacity
+    const CAPACITY: usize;
     /// Turn the array into a boxed slice
     #[cfg(feature = "alloc")]
     fn into_boxed_slice(self) -> Box<[Inner<Self::Item>]>;
 }
 
-/// Behavior for a map array
-pub trait MapArray {
+/// Behavior for the storage of an ArrayMap
+pub trait MapStorage {
     /// The Key type
     type Key;
     /// The Value type
     type Value;
-    /// The array's capacity
-    const CAPACITY: usize;
-    /// Get a slice into the array
+    /// Get the storage's capacity
+    fn capacity(&self) -> usize;
+    /// Get a slice into the storage
     fn as_slice(&self) -> &[Inner<(Self::Key, Self::Value)>];
-    /// Get a mutable slice into the array
+    /// Get a mutable slice into the storage
     fn as_mut_slice(&mut self) -> &mut [Inner<(Self::Key, Self::Value)>];
+    /**
+    Makes sure the storage can hold `capacity` items, growing it if it can
+
+    Returns whether the storage can hold them. Storage with a fixed size only checks its capacity.
+    */
+    fn try_grow(&mut self, capacity: usize) -> bool {
+        capacity <= self.capacity()
+    }
+    /// Get the number of items that the storage has room for on the heap
+    fn heap_capacity(&self) -> usize {
+        0
+    }
+}
+
+/// Behavior for a map array
+pub trait MapArray: MapStorage + EmptyStorage {
+    /// The array's capacity
+    const CAPACITY: usize;
     /// Turn the array into a boxed slice
     #[cfg(feature = "alloc")]
     fn into_boxed_slice(self) -> Box<[Inner<(Self::Key, Self::Value)>]>;
 }
 
+/**
+Storage that can create new, empty storage of its own type
+
+This lets operations that make a second container, like `split_off`, work on any storage that
+implements it: arrays, vectors, boxed slices and [`Spill`](enum.Spill.html) storage. The new
+storage is in the same allocator as the old one, and can hold at least as many items once
+`try_grow` is called on it.
+*/
+pub trait EmptyStorage {
+    /// Create empty storage of the same type
+    fn empty_like(&self) -> Self;
+}
+
+impl<T> Storage for &mut [Inner<T>] {
+    type Item = T;
+    fn capacity(&self) -> usize {
+        self.len()
+    }
+    fn as_slice(&self) -> &[Inner<Self::Item>] {
+        self
+    }
+    fn as_mut_slice(&mut self) -> &mut [Inner<Self::Item>] {
+        self
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> Storage for Box<[Inner<T>]> {
+    type Item = T;
+    fn capacity(&self) -> usize {
+        self.len()
+    }
+    fn as_slice(&self) -> &[Inner<Self::Item>] {
+        self
+    }
+    fn as_mut_slice(&mut self) -> &mut [Inner<Self::Item>] {
+        self
+    }
+    fn heap_capacity(&self) -> usize {
+        self.len()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> EmptyStorage for Box<[Inner<T>]> {
+    fn empty_like(&self) -> Self {
+        let mut slots = Vec::with_capacity(self.len());
+        slots.resize_with(self.len(), Inner::uninit);
+        slots.into_boxed_slice()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K, V> MapStorage for Box<[Inner<(K, V)>]> {
+    type Key = K;
+    type Value = V;
+    fn capacity(&self) -> usize {
+        self.len()
+    }
+    fn as_slice(&self) -> &[Inner<(Self::Key, Self::Value)>] {
+        self
+    }
+    fn as_mut_slice(&mut self) -> &mut [Inner<(Self::Key, Self::Value)>] {
+        self
+    }
+    fn heap_capacity(&self) -> usize {
+        self.len()
+    }
+}
+
+/// Grows a vector to hold at least `capacity` items, filling all of its allocation
+#[cfg(all(feature = "alloc", not(feature = "allocator_api")))]
+fn grow_vec<T>(vec: &mut Vec<Inner<T>>, capacity: usize) {
+    if capacity > vec.len() {
+        vec.reserve(capacity - vec.len());
+        let full = Vec::capacity(vec);
+        vec.resize_with(full, Inner::uninit);
+    }
+}
+
+/// Grows a vector to hold at least `capacity` items, filling all of its allocation
+#[cfg(feature = "allocator_api")]
+fn grow_vec<T, Al: Allocator>(vec: &mut Vec<Inner<T>, Al>, capacity: usize) {
+    if capacity > vec.len() {
+        vec.reserve(capacity - vec.len());
+        let full = Vec::capacity(vec);
+        vec.resize_with(full, Inner::uninit);
+    }
+}
+
+#[cfg(all(feature = "alloc", not(feature = "allocator_api")))]
+impl<T> Storage for Vec<Inner<T>> {
+    type Item = T;
+    fn capacity(&self) -> usize {
+        self.len()
+    }
+    fn as_slice(&self) -> &[Inner<Self::Item>] {
+        self
+    }
+    fn as_mut_slice(&mut self) -> &mut [Inner<Self::Item>] {
+        self
+    }
+    fn try_grow(&mut self, capacity: usize) -> bool {
+        grow_vec(self, capacity);
+        true
+    }
+    fn heap_capacity(&self) -> usize {
+        self.len()
+    }
+}
+
+#[cfg(feature = "allocator_api")]
+impl<T, Al: Allocator> Storage for Vec<Inner<T>, Al> {
+    type Item = T;
+    fn capacity(&self) -> usize {
+        self.len()
+    }
+    fn as_slice(&self) -> &[Inner<Self::Item>] {
+        self
+    }
+    fn as_mut_slice(&mut self) -> &mut [Inner<Self::Item>] {
+        self
+    }
+    fn try_grow(&mut self, capacity: usize) -> bool {
+        grow_vec(self, capacity);
+        true
+    }
+    fn heap_capacity(&self) -> usize {
+        self.len()
+    }
+}
+
+#[cfg(all(feature = "alloc", not(feature = "allocator_api")))]
+impl<T> EmptyStorage for Vec<Inner<T>> {
+    fn empty_like(&self) -> Self {
+        Vec::new()
+    }
+}
+
+#[cfg(feature = "allocator_api")]
+impl<T, Al: Allocator + Clone> EmptyStorage for Vec<Inner<T>, Al> {
+    fn empty_like(&self) -> Self {
+        Vec::new_in(self.allocator().clone())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K, V> MapStorage for Vec<Inner<(K, V)>> {
+    type Key = K;
+    type Value = V;
+    fn capacity(&self) -> usize {
+        self.len()
+    }
+    fn as_slice(&self) -> &[Inner<(Self::Key, Self::Value)>] {
+        self
+    }
+    fn as_mut_slice(&mut self) -> &mut [Inner<(Self::Key, Self::Value)>] {
+        self
+    }
+    fn try_grow(&mut self, capacity: usize) -> bool {
+        grow_vec(self, capacity);
+        true
+    }
+    fn heap_capacity(&self) -> usize {
+        self.len()
+    }
+}
+
+/**
+Growable storage that a [`Spill`](enum.Spill.html) moves its items into
+
+This is implemented for `Vec<Inner<T>>`. With the `allocator_api` feature, it is implemented
+for vectors in any allocator that can be cloned. Its `try_grow` must always make room.
+*/
+#[cfg(feature = "alloc")]
+pub trait SpillHeap: Storage {
+    /// The allocator that the storage is created in
+    type Alloc: Clone;
+    /// Create empty storage in the given allocator
+    fn new_in(alloc: Self::Alloc) -> Self;
+    /// Get the allocator that the storage is in
+    fn allocator(&self) -> Self::Alloc;
+}
+
+#[cfg(all(feature = "alloc", not(feature = "allocator_api")))]
+impl<T> SpillHeap for Vec<Inner<T>> {
+    type Alloc = ();
+    fn new_in(_: ()) -> Self {
+        Vec::new()
+    }
+    fn allocator(&self) {}
+}
+
+#[cfg(feature = "allocator_api")]
+impl<T, Al: Allocator + Clone> SpillHeap for Vec<Inner<T>, Al> {
+    type Alloc = Al;
+    fn new_in(alloc: Al) -> Self {
+        Vec::new_in(alloc)
+    }
+    fn allocator(&self) -> Al {
+        Vec::allocator(self).clone()
+    }
+}
+
+/**
+Storage that starts out as an inline array and moves into growable storage once it needs more room
+
+This is the storage that TinyVecMap and TinyVecSet are built on. The inline array can be any
+[`Array`](trait.Array.html), so a map is spilled from the same `[Inner<(K, V)>; N]` arrays as an ArrayMap.
+The storage it moves into is a `Vec` unless another [`SpillHeap`](trait.SpillHeap.html) is given.
+*/
+#[cfg(feature = "alloc")]
+pub enum Spill<A, H = Vec<Inner<<A as Storage>::Item>>>
+where
+    A: Array,
+    H: SpillHeap<Item = A::Item>,
+{
+    /// Items in an inline array, along with the allocator to move them into
+    Inline(A, H::Alloc),
+    /// Items on the heap
+    Heap(H),
+}
+
+#[cfg(feature = "alloc")]
+impl<A, H> Default for Spill<A, H>
+where
+    A: Array,
+    H: SpillHeap<Item = A::Item>,
+    H::Alloc: Default,
+{
+    fn default() -> Self {
+        Spill::new_in(H::Alloc::default())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<A, H> Spill<A, H>
+where
+    A: Array,
+    H: SpillHeap<Item = A::Item>,
+{
+    /// Creates empty storage that moves into the given allocator
+    pub(crate) fn new_in(alloc: H::Alloc) -> Self {
+        Spill::Inline(unsafe { core::mem::zeroed() }, alloc)
+    }
+    /// Returns `true` if the items have moved to the heap
+    pub fn is_spilled(&self) -> bool {
+        matches!(self, Spill::Heap(_))
+    }
+    /// Moves the items onto the heap with room for at least `capacity` of them if they are not there already
+    pub(crate) fn spill(&mut self, capacity: usize) {
+        if let Spill::Inline(array, alloc) = self {
+            let mut heap = H::new_in(alloc.clone());
+            heap.try_grow(capacity.max(A::CAPACITY));
+            for (slot, item) in heap.as_mut_slice().iter_mut().zip(array.as_mut_slice()) {
+                *slot = core::mem::replace(item, Inner::uninit());
+            }
+            *self = Spill::Heap(heap);
+        }
+    }
+    fn slice(&self) -> &[Inner<A::Item>] {
+        match self {
+            Spill::Inline(array, _) => array.as_slice(),
+            Spill::Heap(heap) => heap.as_slice(),
+        }
+    }
+    fn slice_mut(&mut self) -> &mut [Inner<A::Item>] {
+        match self {
+            Spill::Inline(array, _) => array.as_mut_slice(),
+            Spill::Heap(heap) => heap.as_mut_slice(),
+        }
+    }
+    fn grow(&mut self, capacity: usize) -> bool {
+        match self {
+            Spill::Inline(..) if capacity <= A::CAPACITY => true,
+            Spill::Inline(..) => {
+                self.spill(capacity.max(A::CAPACITY * 2));
+                true
+            }
+            Spill::Heap(heap) => heap.try_grow(capacity),
+        }
+    }
+    fn heap_len(&self) -> usize {
+        match self {
+            Spill::Inline(..) => 0,
+            Spill::Heap(heap) => heap.heap_capacity(),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<A, H> EmptyStorage for Spill<A, H>
+where
+    A: Array,
+    H: SpillHeap<Item = A::Item>,
+{
+    fn empty_like(&self) -> Self {
+        match self {
+            Spill::Inline(_, alloc) => Spill::new_in(alloc.clone()),
+            Spill::Heap(heap) => Spill::new_in(heap.allocator()),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<A, H> Storage for Spill<A, H>
+where
+    A: Array,
+    H: SpillHeap<Item = A::Item>,
+{
+    type Item = A::Item;
+    fn capacity(&self) -> usize {
+        self.slice().len()
+    }
+    fn as_slice(&self) -> &[Inner<Self::Item>] {
+        self.slice()
+    }
+    fn as_mut_slice(&mut self) -> &mut [Inner<Self::Item>] {
+        self.slice_mut()
+    }
+    fn try_grow(&mut self, capacity: usize) -> bool {
+        self.grow(capacity)
+    }
+    fn heap_capacity(&self) -> usize {
+        self.heap_len()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K, V, A, H> MapStorage for Spill<A, H>
+where
+    A: Array<Item = (K, V)>,
+    H: SpillHeap<Item = (K, V)>,
+{
+    type Key = K;
+    type Value = V;
+    fn capacity(&self) -> usize {
+        self.slice().len()
+    }
+    fn as_slice(&self) -> &[Inner<(Self::Key, Self::Value)>] {
+        self.slice()
+    }
+    fn as_mut_slice(&mut self) -> &mut [Inner<(Self::Key, Self::Value)>] {
+        self.slice_mut()
+    }
+    fn try_grow(&mut self, capacity: usize) -> bool {
+        self.grow(capacity)
+    }
+    fn heap_capacity(&self) -> usize {
+        self.heap_len()
+    }
+}
+
+impl<K, V> MapStorage for &mut [Inner<(K, V)>] {
+    type Key = K;
+    type Value = V;
+    fn capacity(&self) -> usize {
+        self.len()
+    }
+    fn as_slice(&self) -> &[Inner<(Self::Key, Self::Value)>] {
+        self
+    }
+    fn as_mut_slice(&mut self) -> &mut [Inner<(Self::Key, Self::Value)>] {
+        self
+    }
+}
+
 macro_rules! impl_array {
     ($($n:literal),*) => {
         $(
-            impl<T> Array for [Inner<T>; $n]  {
+            impl<T> Storage for [Inner<T>; $n]  {
                 type Item = T;
-                const CAPACITY: usize = $n;
+                fn capacity(&self) -> usize {
+                    $n
+                }
                 fn as_slice(&self) -> &[Inner<Self::Item>] {
                     self
                 }
                 fn as_mut_slice(&mut self) -> &mut [Inner<Self::Item>] {
                     self
                 }
+            }
+            impl<T> EmptyStorage for [Inner<T>; $n]  {
+                fn empty_like(&self) -> Self {
+                    unsafe { core::mem::zeroed() }
+                }
+            }
+            impl<T> Array for [Inner<T>; $n]  {
+                const CAPACITY: usize = $n;
                 #[cfg(feature = "alloc")]
                 fn into_boxed_slice(self) -> Box<[Inner<Self::Item>]> {
                     Box::new(self)
                 }
             }
-            impl<K, V> MapArray for [Inner<(K, V)>; $n]  {
+            impl<K, V> MapStorage for [Inner<(K, V)>; $n]  {
                 type Key = K;
                 type Value = V;
-                const CAPACITY: usize = $n;
+                fn capacity(&self) -> usize {
+                    $n
+                }
                 fn as_slice(&self) -> &[Inner<(Self::Key, Self::Value)>] {
                     self
                 }
                 fn as_mut_slice(&mut self) -> &mut [Inner<(Self::Key, Self::Value)>] {
                     self
                 }
+            }
+            impl<K, V> MapArray for [Inner<(K, V)>; $n]  {
+                const CAPACITY: usize = $n;
                 #[cfg(feature = "alloc")]
                 fn into_boxed_slice(self) -> Box<[Inner<(Self::Key, Self::Value)>]> {
                     Box::new(self)
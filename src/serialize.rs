@@ -1,17 +1,27 @@
-use core::{fmt, marker::PhantomData};
+use core::{
+    fmt,
+    hash::{BuildHasher, Hash},
+    marker::PhantomData,
+};
 
 use serde::{
     de::{Error, MapAccess, SeqAccess, Visitor},
     Deserialize, Deserializer, Serialize, Serializer,
 };
 
-use crate::{Array, ArrayMap, ArraySet, MapArray};
+use crate::{
+    compare::Compare, Array, ArrayHashMap, ArrayHashSet, ArrayMap, ArraySet, MapArray, MapStorage,
+    Storage,
+};
 #[cfg(feature = "alloc")]
-use crate::{TinyMap, TinySet};
+use crate::{
+    tiny_map::MapHeap, tiny_set::SetHeap, SpillHeap, TinyHashMap, TinyMap, TinySet, TinyVecMap,
+    TinyVecSet, VecMap, VecSet,
+};
 
-impl<A> Serialize for ArrayMap<A>
+impl<A, C> Serialize for ArrayMap<A, C>
 where
-    A: MapArray,
+    A: MapStorage,
     A::Key: Serialize,
     A::Value: Serialize,
 {
@@ -24,11 +34,12 @@ where
 }
 
 #[cfg(feature = "alloc")]
-impl<A> Serialize for TinyMap<A>
+impl<A, C, H> Serialize for TinyMap<A, C, H>
 where
     A: MapArray,
     A::Key: Serialize,
     A::Value: Serialize,
+    H: MapHeap<C, Key = A::Key, Value = A::Value>,
 {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -38,9 +49,9 @@ where
     }
 }
 
-impl<A> Serialize for ArraySet<A>
+impl<A, C> Serialize for ArraySet<A, C>
 where
-    A: Array,
+    A: Storage,
     A::Item: Serialize,
 {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -52,10 +63,11 @@ where
 }
 
 #[cfg(feature = "alloc")]
-impl<A> Serialize for TinySet<A>
+impl<A, C, H> Serialize for TinySet<A, C, H>
 where
     A: Array,
     A::Item: Serialize,
+    H: SetHeap<C, Item = A::Item>,
 {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -65,25 +77,27 @@ where
     }
 }
 
-impl<'de, A> Deserialize<'de> for ArrayMap<A>
+impl<'de, A, C> Deserialize<'de> for ArrayMap<A, C>
 where
     A: MapArray,
-    A::Key: Deserialize<'de> + Ord,
+    A::Key: Deserialize<'de>,
     A::Value: Deserialize<'de>,
+    C: Compare<A::Key> + Default,
 {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        struct ThisVisitor<'de, A>(PhantomData<(&'de (), A)>);
+        struct ThisVisitor<'de, A, C>(PhantomData<(&'de (), A, C)>);
 
-        impl<'de, A> Visitor<'de> for ThisVisitor<'de, A>
+        impl<'de, A, C> Visitor<'de> for ThisVisitor<'de, A, C>
         where
             A: MapArray,
-            A::Key: Deserialize<'de> + Ord,
+            A::Key: Deserialize<'de>,
             A::Value: Deserialize<'de>,
+            C: Compare<A::Key> + Default,
         {
-            type Value = ArrayMap<A>;
+            type Value = ArrayMap<A, C>;
 
             fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
                 write!(formatter, "a map with no more than {} items", A::CAPACITY)
@@ -93,7 +107,7 @@ where
             where
                 M: MapAccess<'de>,
             {
-                let mut array_map = Self::Value::new();
+                let mut array_map = Self::Value::default();
                 while let Some((key, value)) = map.next_entry()? {
                     if array_map.try_insert(key, value).is_err() {
                         return Err(M::Error::invalid_length(A::CAPACITY + 1, &self));
@@ -103,30 +117,34 @@ where
             }
         }
 
-        deserializer.deserialize_map(ThisVisitor::<A>(PhantomData))
+        deserializer.deserialize_map(ThisVisitor::<A, C>(PhantomData))
     }
 }
 
 #[cfg(feature = "alloc")]
-impl<'de, A> Deserialize<'de> for TinyMap<A>
+impl<'de, A, C, H> Deserialize<'de> for TinyMap<A, C, H>
 where
     A: MapArray,
-    A::Key: Deserialize<'de> + Ord,
+    A::Key: Deserialize<'de>,
     A::Value: Deserialize<'de>,
+    C: Compare<A::Key> + Default,
+    H: MapHeap<C, Key = A::Key, Value = A::Value>,
 {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        struct ThisVisitor<'de, A>(PhantomData<(&'de (), A)>);
+        struct ThisVisitor<'de, A, C, H>(PhantomData<(&'de (), A, C, H)>);
 
-        impl<'de, A> Visitor<'de> for ThisVisitor<'de, A>
+        impl<'de, A, C, H> Visitor<'de> for ThisVisitor<'de, A, C, H>
         where
             A: MapArray,
-            A::Key: Deserialize<'de> + Ord,
+            A::Key: Deserialize<'de>,
             A::Value: Deserialize<'de>,
+            C: Compare<A::Key> + Default,
+            H: MapHeap<C, Key = A::Key, Value = A::Value>,
         {
-            type Value = TinyMap<A>;
+            type Value = TinyMap<A, C, H>;
 
             fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
                 write!(formatter, "a map with no more than {} items", A::CAPACITY)
@@ -136,7 +154,7 @@ where
             where
                 M: MapAccess<'de>,
             {
-                let mut tiny_map = Self::Value::new();
+                let mut tiny_map = Self::Value::default();
                 while let Some((key, value)) = map.next_entry()? {
                     tiny_map.insert(key, value);
                 }
@@ -144,27 +162,29 @@ where
             }
         }
 
-        deserializer.deserialize_map(ThisVisitor::<A>(PhantomData))
+        deserializer.deserialize_map(ThisVisitor::<A, C, H>(PhantomData))
     }
 }
 
-impl<'de, A> Deserialize<'de> for ArraySet<A>
+impl<'de, A, C> Deserialize<'de> for ArraySet<A, C>
 where
     A: Array,
-    A::Item: Deserialize<'de> + Ord,
+    A::Item: Deserialize<'de>,
+    C: Compare<A::Item> + Default,
 {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        struct ThisVisitor<'de, A>(PhantomData<(&'de (), A)>);
+        struct ThisVisitor<'de, A, C>(PhantomData<(&'de (), A, C)>);
 
-        impl<'de, A> Visitor<'de> for ThisVisitor<'de, A>
+        impl<'de, A, C> Visitor<'de> for ThisVisitor<'de, A, C>
         where
             A: Array,
-            A::Item: Deserialize<'de> + Ord,
+            A::Item: Deserialize<'de>,
+            C: Compare<A::Item> + Default,
         {
-            type Value = ArraySet<A>;
+            type Value = ArraySet<A, C>;
 
             fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
                 write!(formatter, "a map with no more than {} items", A::CAPACITY)
@@ -174,7 +194,7 @@ where
             where
                 S: SeqAccess<'de>,
             {
-                let mut array_set = Self::Value::new();
+                let mut array_set = Self::Value::default();
                 while let Some(item) = set.next_element()? {
                     array_set.insert(item);
                 }
@@ -182,28 +202,32 @@ where
             }
         }
 
-        deserializer.deserialize_seq(ThisVisitor::<A>(PhantomData))
+        deserializer.deserialize_seq(ThisVisitor::<A, C>(PhantomData))
     }
 }
 
 #[cfg(feature = "alloc")]
-impl<'de, A> Deserialize<'de> for TinySet<A>
+impl<'de, A, C, H> Deserialize<'de> for TinySet<A, C, H>
 where
     A: Array,
-    A::Item: Deserialize<'de> + Ord,
+    A::Item: Deserialize<'de>,
+    C: Compare<A::Item> + Default,
+    H: SetHeap<C, Item = A::Item>,
 {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        struct ThisVisitor<'de, A>(PhantomData<(&'de (), A)>);
+        struct ThisVisitor<'de, A, C, H>(PhantomData<(&'de (), A, C, H)>);
 
-        impl<'de, A> Visitor<'de> for ThisVisitor<'de, A>
+        impl<'de, A, C, H> Visitor<'de> for ThisVisitor<'de, A, C, H>
         where
             A: Array,
-            A::Item: Deserialize<'de> + Ord,
+            A::Item: Deserialize<'de>,
+            C: Compare<A::Item> + Default,
+            H: SetHeap<C, Item = A::Item>,
         {
-            type Value = TinySet<A>;
+            type Value = TinySet<A, C, H>;
 
             fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
                 write!(formatter, "a map with no more than {} items", A::CAPACITY)
@@ -213,7 +237,7 @@ where
             where
                 S: SeqAccess<'de>,
             {
-                let mut tiny_set = Self::Value::new();
+                let mut tiny_set = Self::Value::default();
                 while let Some(item) = set.next_element()? {
                     tiny_set.insert(item);
                 }
@@ -221,6 +245,275 @@ where
             }
         }
 
-        deserializer.deserialize_seq(ThisVisitor::<A>(PhantomData))
+        deserializer.deserialize_seq(ThisVisitor::<A, C, H>(PhantomData))
+    }
+}
+
+/// A visitor for maps that grow to hold as many entries as they are given
+#[cfg(feature = "alloc")]
+struct GrowMapVisitor<'de, M, K, V>(PhantomData<(&'de (), M, K, V)>);
+
+#[cfg(feature = "alloc")]
+impl<'de, M, K, V> Visitor<'de> for GrowMapVisitor<'de, M, K, V>
+where
+    M: Default + Extend<(K, V)>,
+    K: Deserialize<'de>,
+    V: Deserialize<'de>,
+{
+    type Value = M;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "a map")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut grow_map = M::default();
+        while let Some(entry) = map.next_entry()? {
+            grow_map.extend(Some(entry));
+        }
+        Ok(grow_map)
+    }
+}
+
+/// A visitor for sets that grow to hold as many values as they are given
+#[cfg(feature = "alloc")]
+struct GrowSetVisitor<'de, S, T>(PhantomData<(&'de (), S, T)>);
+
+#[cfg(feature = "alloc")]
+impl<'de, S, T> Visitor<'de> for GrowSetVisitor<'de, S, T>
+where
+    S: Default + Extend<T>,
+    T: Deserialize<'de>,
+{
+    type Value = S;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "a sequence")
+    }
+
+    fn visit_seq<A>(self, mut set: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut grow_set = S::default();
+        while let Some(item) = set.next_element()? {
+            grow_set.extend(Some(item));
+        }
+        Ok(grow_set)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'de, K, V, C> Deserialize<'de> for VecMap<K, V, C>
+where
+    K: Deserialize<'de>,
+    V: Deserialize<'de>,
+    C: Compare<K> + Default,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(GrowMapVisitor(PhantomData))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'de, K, V, A, C, H> Deserialize<'de> for TinyVecMap<A, C, H>
+where
+    A: Array<Item = (K, V)>,
+    H: SpillHeap<Item = (K, V)>,
+    H::Alloc: Default,
+    K: Deserialize<'de>,
+    V: Deserialize<'de>,
+    C: Compare<K> + Default,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(GrowMapVisitor(PhantomData))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'de, T, C> Deserialize<'de> for VecSet<T, C>
+where
+    T: Deserialize<'de>,
+    C: Compare<T> + Default,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(GrowSetVisitor(PhantomData))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'de, A, C, H> Deserialize<'de> for TinyVecSet<A, C, H>
+where
+    A: Array,
+    H: SpillHeap<Item = A::Item>,
+    H::Alloc: Default,
+    A::Item: Deserialize<'de>,
+    C: Compare<A::Item> + Default,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(GrowSetVisitor(PhantomData))
+    }
+}
+
+impl<A, H> Serialize for ArrayHashMap<A, H>
+where
+    A: MapArray,
+    A::Key: Serialize,
+    A::Value: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_map(self)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<A, H> Serialize for TinyHashMap<A, H>
+where
+    A: MapArray,
+    A::Key: Serialize,
+    A::Value: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_map(self)
+    }
+}
+
+impl<A, H> Serialize for ArrayHashSet<A, H>
+where
+    A: Array,
+    A::Item: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(self)
+    }
+}
+
+impl<'de, A, H> Deserialize<'de> for ArrayHashMap<A, H>
+where
+    A: MapArray,
+    A::Key: Deserialize<'de> + Hash + Eq,
+    A::Value: Deserialize<'de>,
+    H: BuildHasher + Default,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct ThisVisitor<'de, A, H>(PhantomData<(&'de (), A, H)>);
+
+        impl<'de, A, H> Visitor<'de> for ThisVisitor<'de, A, H>
+        where
+            A: MapArray,
+            A::Key: Deserialize<'de> + Hash + Eq,
+            A::Value: Deserialize<'de>,
+            H: BuildHasher + Default,
+        {
+            type Value = ArrayHashMap<A, H>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(formatter, "a map with no more than {} items", A::CAPACITY)
+            }
+
+            fn visit_map<M>(self, mut map: M) -> Result<Self::Value, M::Error>
+            where
+                M: MapAccess<'de>,
+            {
+                let mut hash_map = Self::Value::default();
+                while let Some((key, value)) = map.next_entry()? {
+                    if hash_map.try_insert(key, value).is_err() {
+                        return Err(M::Error::invalid_length(A::CAPACITY + 1, &self));
+                    }
+                }
+                Ok(hash_map)
+            }
+        }
+
+        deserializer.deserialize_map(ThisVisitor::<A, H>(PhantomData))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'de, A, H> Deserialize<'de> for TinyHashMap<A, H>
+where
+    A: MapArray,
+    A::Key: Deserialize<'de> + Hash + Eq,
+    A::Value: Deserialize<'de>,
+    H: BuildHasher + Default,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(GrowMapVisitor(PhantomData))
+    }
+}
+
+impl<'de, A, H> Deserialize<'de> for ArrayHashSet<A, H>
+where
+    A: Array,
+    A::Item: Deserialize<'de> + Hash + Eq,
+    H: BuildHasher + Default,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct ThisVisitor<'de, A, H>(PhantomData<(&'de (), A, H)>);
+
+        impl<'de, A, H> Visitor<'de> for ThisVisitor<'de, A, H>
+        where
+            A: Array,
+            A::Item: Deserialize<'de> + Hash + Eq,
+            H: BuildHasher + Default,
+        {
+            type Value = ArrayHashSet<A, H>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(
+                    formatter,
+                    "a sequence with no more than {} items",
+                    A::CAPACITY
+                )
+            }
+
+            fn visit_seq<S>(self, mut set: S) -> Result<Self::Value, S::Error>
+            where
+                S: SeqAccess<'de>,
+            {
+                let mut hash_set = Self::Value::default();
+                while let Some(item) = set.next_element()? {
+                    if hash_set.try_insert(item).is_err() {
+                        return Err(S::Error::invalid_length(A::CAPACITY + 1, &self));
+                    }
+                }
+                Ok(hash_set)
+            }
+        }
+
+        deserializer.deserialize_seq(ThisVisitor::<A, H>(PhantomData))
     }
 }
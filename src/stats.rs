@@ -0,0 +1,161 @@
+/*!
+Spill and length statistics for choosing the capacities of TinyMaps and TinySets
+
+This module is enabled by the `stats` feature. While it is enabled, every TinyMap and TinySet
+records when it moves to the heap and the lengths it grows to. Statistics are kept per container type,
+so containers with the same key, value, capacity and comparator types share a single record.
+
+Lengths are recorded by every insertion: `insert`, and so `extend` and `collect`, as well as inserting
+through a vacant entry. `append` and `merge_with` record the length they grow a container to once.
+
+Recording takes a global lock, so this feature is meant for measuring, not for release builds.
+
+# Example
+
+```
+use tinymap::*;
+
+stats::reset();
+let mut map = tinymap!(u8 => u8; 2);
+map.extend(vec![(1, 1), (2, 2), (3, 3)]);
+
+let report = stats::report();
+let stats = report.of::<TinyMap<[Inner<(u8, u8)>; 2]>>().unwrap();
+assert_eq!(stats.spills, 1);
+assert_eq!(stats.peak_len, 3);
+assert_eq!(stats.histogram, [1, 1, 1, 0]);
+
+// The report can be printed
+println!("{}", report);
+```
+*/
+
+use std::{any::type_name, collections::BTreeMap, fmt, sync::Mutex};
+
+static STATS: Mutex<BTreeMap<&'static str, TypeStats>> = Mutex::new(BTreeMap::new());
+
+/// Statistics for one container type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeStats {
+    /// The container's inline capacity
+    pub capacity: usize,
+    /// The number of times a container moved to the heap
+    pub spills: u64,
+    /// The largest length that a container grew to
+    pub peak_len: usize,
+    /**
+    The number of insertions that grew a container to each length
+
+    `histogram[i]` counts insertions that grew a container to length `i + 1`. An `append` or
+    `merge_with` counts as a single insertion.
+    The histogram covers lengths up to twice the capacity, and its last bucket also counts
+    insertions that grew a container beyond that. If containers are only ever inserted into,
+    each bucket is the number of containers that reached its length.
+    */
+    pub histogram: Vec<u64>,
+}
+
+impl TypeStats {
+    fn new(capacity: usize) -> Self {
+        TypeStats {
+            capacity,
+            spills: 0,
+            peak_len: 0,
+            histogram: vec![0; capacity * 2],
+        }
+    }
+}
+
+fn record<T, F>(capacity: usize, f: F)
+where
+    T: ?Sized,
+    F: FnOnce(&mut TypeStats),
+{
+    let mut stats = STATS.lock().unwrap_or_else(|e| e.into_inner());
+    f(stats
+        .entry(type_name::<T>())
+        .or_insert_with(|| TypeStats::new(capacity)))
+}
+
+/// Record that a container of type `T` moved to the heap
+pub(crate) fn record_spill<T: ?Sized>(capacity: usize) {
+    record::<T, _>(capacity, |stats| stats.spills += 1)
+}
+
+/// Record that an insertion grew a container of type `T` to `len`
+pub(crate) fn record_len<T: ?Sized>(capacity: usize, len: usize) {
+    record::<T, _>(capacity, |stats| {
+        stats.peak_len = stats.peak_len.max(len);
+        if let Some(last) = stats.histogram.len().checked_sub(1) {
+            stats.histogram[(len - 1).min(last)] += 1;
+        }
+    })
+}
+
+/// Get a snapshot of the statistics recorded so far
+pub fn report() -> Report {
+    let stats = STATS.lock().unwrap_or_else(|e| e.into_inner());
+    Report {
+        types: stats
+            .iter()
+            .map(|(&name, stats)| (name, stats.clone()))
+            .collect(),
+    }
+}
+
+/// Clear all recorded statistics
+pub fn reset() {
+    STATS.lock().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+/**
+A snapshot of the recorded statistics
+
+Created with [`report`](fn.report.html). Its `Display` implementation prints the statistics
+for each container type.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    types: Vec<(&'static str, TypeStats)>,
+}
+
+impl Report {
+    /// Iterate over the container type names and their statistics, ordered by name
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, &TypeStats)> {
+        self.types.iter().map(|(name, stats)| (*name, stats))
+    }
+    /// Get the statistics for a container type by its name
+    pub fn get(&self, type_name: &str) -> Option<&TypeStats> {
+        self.iter()
+            .find(|(name, _)| *name == type_name)
+            .map(|(_, stats)| stats)
+    }
+    /// Get the statistics for a container type
+    pub fn of<T: ?Sized>(&self) -> Option<&TypeStats> {
+        self.get(type_name::<T>())
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, stats) in self.iter() {
+            writeln!(f, "{}", name)?;
+            writeln!(
+                f,
+                "    capacity: {}, spills: {}, peak length: {}",
+                stats.capacity, stats.spills, stats.peak_len
+            )?;
+            write!(f, "    lengths:")?;
+            for (i, count) in stats.histogram.iter().enumerate() {
+                let plus = if i + 1 == stats.histogram.len() {
+                    "+"
+                } else {
+                    ""
+                };
+                write!(f, " {}{}: {}", i + 1, plus, count)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
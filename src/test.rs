@@ -2,6 +2,7 @@ use crate as tinymap;
 use tinymap::*;
 
 #[test]
+#[allow(clippy::never_loop)]
 fn into_iter_empty() {
     let map = tinymap!(usize => usize; 10);
     for _ in map {
@@ -49,6 +50,39 @@ fn drop_map() {
     assert_eq!(5, counter.load(Ordering::Relaxed));
 }
 
+#[test]
+fn drop_into_keys() {
+    use std::sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    };
+    struct Foo(Arc<AtomicU32>);
+
+    impl Drop for Foo {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    let counter = Arc::new(AtomicU32::new(0));
+    let get_counter = || Arc::clone(&counter);
+
+    {
+        let mut map = arraymap!(i32 => Foo; 10);
+        for i in 0..5 {
+            map.insert(i, Foo(get_counter()));
+        }
+
+        let mut keys = map.into_keys();
+        assert_eq!(Some(0), keys.next());
+        assert_eq!(1, counter.load(Ordering::Relaxed));
+        assert_eq!(Some(1), keys.next());
+        assert_eq!(2, counter.load(Ordering::Relaxed));
+    }
+
+    assert_eq!(5, counter.load(Ordering::Relaxed));
+}
+
 #[test]
 fn drop_set() {
     use std::{
@@ -72,7 +106,7 @@ fn drop_set() {
 
     impl PartialOrd for Foo {
         fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-            self.0.partial_cmp(&other.0)
+            Some(self.cmp(other))
         }
     }
 
@@ -159,3 +193,1464 @@ fn tiny_set_serialization() {
     let ser = serde_yaml::to_string(&set).unwrap();
     assert_eq!(set, serde_yaml::from_str(&ser).unwrap());
 }
+
+#[cfg(feature = "serde")]
+#[test]
+fn vec_serialization() {
+    let map = (0..10)
+        .map(|i| (i, i * 2))
+        .collect::<TinyVecMap<[Inner<(i32, i32)>; 5]>>();
+    let ser = serde_yaml::to_string(&map).unwrap();
+    assert_eq!(map, serde_yaml::from_str(&ser).unwrap());
+    let de: VecMap<i32, i32> = serde_yaml::from_str(&ser).unwrap();
+    assert!(de.iter().eq(map.iter()));
+
+    let set = (0..10).collect::<VecSet<i32>>();
+    let ser = serde_yaml::to_string(&set).unwrap();
+    assert_eq!(set, serde_yaml::from_str(&ser).unwrap());
+    let de: TinyVecSet<[Inner<i32>; 5]> = serde_yaml::from_str(&ser).unwrap();
+    assert!(de.iter().eq(set.iter()));
+}
+
+#[test]
+fn tiny_map_positional_heap() {
+    let mut map = tinymap!(i32 => i32; 2);
+    for i in (0..10).rev() {
+        map.insert(i * 10, i);
+    }
+    assert!(matches!(map, TinyMap::Heap(_)));
+    assert_eq!(map.get_index(3), Some((&30, &3)));
+    assert_eq!(map.index_of(&70), Some(7));
+    assert_eq!(map.index_of(&75), None);
+    assert_eq!(map.rank(&75), 8);
+    assert_eq!(map.partition_point(|k, _| *k < 42), 5);
+    assert_eq!(map.remove_index(4), Some((40, 4)));
+    assert_eq!(map.remove_index(9), None);
+    assert_eq!(map.len(), 9);
+    assert_eq!(map.get_index(4), Some((&50, &5)));
+}
+
+#[test]
+fn tiny_set_positional_heap() {
+    let mut set = tinyset!(i32; 2);
+    for i in (0..10).rev() {
+        set.insert(i * 10);
+    }
+    assert!(matches!(set, TinySet::Heap(_)));
+    assert_eq!(set.get_index(3), Some(&30));
+    assert_eq!(set.index_of(&70), Some(7));
+    assert_eq!(set.rank(&75), 8);
+    assert_eq!(set.remove_index(4), Some(40));
+    assert_eq!(set.get_index(4), Some(&50));
+}
+
+#[test]
+fn tiny_map_cursor_heap() {
+    use core::ops::Bound::*;
+
+    let mut map = tinymap!(i32 => i32; 2);
+    for i in 0..6 {
+        map.insert(i * 10, i);
+    }
+    assert!(matches!(map, TinyMap::Heap(_)));
+
+    let mut cursor = map.lower_bound(Excluded(&20));
+    assert_eq!(cursor.peek_prev(), Some((&20, &2)));
+    assert_eq!(cursor.next(), Some((&30, &3)));
+    assert_eq!(cursor.prev(), Some((&30, &3)));
+    assert_eq!(cursor.prev(), Some((&20, &2)));
+    let cursor = map.lower_bound::<i32>(Unbounded);
+    assert_eq!(cursor.peek_prev(), None);
+    assert_eq!(cursor.peek_next(), Some((&0, &0)));
+    let cursor = map.upper_bound(Excluded(&20));
+    assert_eq!(cursor.peek_next(), Some((&20, &2)));
+
+    let mut cursor = map.lower_bound_mut(Included(&25));
+    assert_eq!(cursor.insert_after(20, 0), Err((20, 0)));
+    assert_eq!(cursor.insert_after(25, 25), Ok(()));
+    assert_eq!(cursor.insert_before(24, 24), Ok(()));
+    assert_eq!(cursor.as_cursor().peek_prev(), Some((&24, &24)));
+    assert_eq!(cursor.remove_next(), Some((25, 25)));
+    assert_eq!(cursor.remove_prev(), Some((24, 24)));
+    if let Some((_, v)) = cursor.next() {
+        *v = 100;
+    }
+    assert_eq!(map[&30], 100);
+    assert_eq!(map.len(), 6);
+}
+
+#[test]
+fn tiny_map_cursor_spill() {
+    use core::ops::Bound::*;
+
+    let mut map = tinymap!(i32 => i32; 2);
+    map.insert(1, 1);
+    map.insert(3, 3);
+    let mut cursor = map.upper_bound_mut(Included(&1));
+    assert_eq!(cursor.insert_before(2, 2), Ok(()));
+    assert_eq!(cursor.peek_prev(), Some((&2, &mut 2)));
+    assert_eq!(cursor.peek_next(), Some((&3, &mut 3)));
+    assert!(matches!(map, TinyMap::Heap(_)));
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [1, 2, 3]);
+
+    let mut map = arraymap!(i32 => i32; 2);
+    map.insert(1, 1);
+    map.insert(3, 3);
+    let mut cursor = map.upper_bound_mut(Included(&1));
+    assert_eq!(cursor.insert_before(2, 2), Err((2, 2)));
+}
+
+#[test]
+fn tiny_map_cursor_mut_reversed_heap() {
+    use core::ops::Bound::*;
+
+    let mut map = TinyMap::<[Inner<(i32, i32)>; 2], compare::Reverse>::default();
+    map.extend(vec![(10, 1), (30, 3)]);
+    let mut cursor = map.lower_bound_mut(Excluded(&30));
+    assert_eq!(cursor.insert_before(20, 2), Ok(()));
+    assert!(cursor.as_cursor().peek_prev() == Some((&20, &2)));
+    assert_eq!(cursor.insert_after(25, 0), Err((25, 0)));
+    assert_eq!(cursor.insert_after(15, 15), Ok(()));
+    assert_eq!(cursor.peek_next(), Some((&15, &mut 15)));
+    assert_eq!(cursor.remove_next(), Some((15, 15)));
+    assert_eq!(cursor.remove_prev(), Some((20, 2)));
+    assert_eq!(cursor.prev(), Some((&30, &mut 3)));
+    assert_eq!(cursor.prev(), None);
+    if let Some((_, v)) = cursor.next() {
+        *v = 300;
+    }
+    assert_eq!(cursor.next(), Some((&10, &mut 1)));
+    assert_eq!(cursor.next(), None);
+    assert!(matches!(map, TinyMap::Heap(_)));
+    assert_eq!(map.into_iter().collect::<Vec<_>>(), [(30, 300), (10, 1)]);
+}
+
+#[test]
+fn drop_append() {
+    use std::sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    };
+    struct Foo(Arc<AtomicU32>);
+
+    impl Drop for Foo {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    let counter = Arc::new(AtomicU32::new(0));
+    let get_counter = || Arc::clone(&counter);
+
+    {
+        let mut a = arraymap!(i32 => Foo; 6);
+        let mut b = arraymap!(i32 => Foo; 6);
+        for i in &[1, 3, 5, 7] {
+            a.insert(*i, Foo(get_counter()));
+        }
+        for i in &[0, 3, 4] {
+            b.insert(*i, Foo(get_counter()));
+        }
+        a.append(&mut b);
+        assert_eq!(1, counter.load(Ordering::Relaxed));
+        assert_eq!(a.keys().copied().collect::<Vec<_>>(), [0, 1, 3, 4, 5, 7]);
+        assert!(b.is_empty());
+
+        b.insert(8, Foo(get_counter()));
+        assert!(a.try_append(&mut b).is_err());
+        assert_eq!(6, a.len());
+        assert_eq!(1, b.len());
+
+        let c = a.split_off(&4);
+        assert_eq!(c.keys().copied().collect::<Vec<_>>(), [4, 5, 7]);
+        assert_eq!(a.keys().copied().collect::<Vec<_>>(), [0, 1, 3]);
+        assert_eq!(1, counter.load(Ordering::Relaxed));
+    }
+
+    assert_eq!(8, counter.load(Ordering::Relaxed));
+}
+
+#[test]
+fn tiny_append_spill() {
+    let mut a = tinymap!(i32 => i32; 3);
+    let mut b = tinymap!(i32 => i32; 3);
+    a.insert(1, 1);
+    a.insert(2, 2);
+    b.insert(2, 20);
+    b.insert(3, 30);
+    a.append(&mut b);
+    assert!(matches!(a, TinyMap::Stack(_)));
+    b.insert(4, 40);
+    a.append(&mut b);
+    assert!(matches!(a, TinyMap::Heap(_)));
+    assert!(b.is_empty());
+    assert_eq!(
+        a.iter().map(|(k, v)| (*k, *v)).collect::<Vec<_>>(),
+        [(1, 1), (2, 20), (3, 30), (4, 40)]
+    );
+    let high = a.split_off(&3);
+    assert_eq!(high.len(), 2);
+    assert_eq!(a.len(), 2);
+
+    let mut s = tinyset!(i32; 2);
+    let mut t = tinyset!(i32; 2);
+    s.insert(1);
+    t.insert(2);
+    t.insert(3);
+    t.insert(4);
+    s.append(&mut t);
+    assert!(t.is_empty());
+    assert_eq!(s.iter().copied().collect::<Vec<_>>(), [1, 2, 3, 4]);
+}
+
+#[test]
+fn tiny_append_keeps_entries_from_other() {
+    // Compares by number only, so the tag shows which side an entry came from
+    #[derive(Debug, Clone, Copy)]
+    struct Tagged(i32, char);
+
+    impl PartialEq for Tagged {
+        fn eq(&self, other: &Self) -> bool {
+            self.0 == other.0
+        }
+    }
+    impl Eq for Tagged {}
+    impl PartialOrd for Tagged {
+        fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+    impl Ord for Tagged {
+        fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+            self.0.cmp(&other.0)
+        }
+    }
+
+    // Both on the stack, after spilling from the stack, and both on the heap
+    for (ours, theirs) in [(1, 2), (2, 2), (3, 3)] {
+        let mut a = tinymap!(Tagged => char; 2);
+        let mut b = tinymap!(Tagged => char; 2);
+        a.extend((0..ours).map(|i| (Tagged(i, 'a'), 'a')));
+        b.extend((1..theirs + 1).map(|i| (Tagged(i, 'b'), 'b')));
+        a.append(&mut b);
+        assert!(b.is_empty());
+        assert!(a.iter().skip(1).all(|(k, v)| k.1 == 'b' && *v == 'b'));
+
+        let mut s = tinyset!(Tagged; 2);
+        let mut t = tinyset!(Tagged; 2);
+        s.extend((0..ours).map(|i| Tagged(i, 'a')));
+        t.extend((1..theirs + 1).map(|i| Tagged(i, 'b')));
+        s.append(&mut t);
+        assert!(t.is_empty());
+        assert!(s.iter().skip(1).all(|v| v.1 == 'b'));
+    }
+}
+
+#[test]
+fn merge_with_and_diff() {
+    use crate::diff::DiffItem;
+
+    let mut a = arraymap!(i32 => i32; 6);
+    let mut b = arraymap!(i32 => i32; 6);
+    for i in &[1, 3, 5] {
+        a.insert(*i, *i);
+    }
+    for i in &[0, 3, 4] {
+        b.insert(*i, *i * 10);
+    }
+    let before = a.clone();
+    a.merge_with(b, |_, x, y| x + y);
+    assert_eq!(
+        a.iter().map(|(k, v)| (*k, *v)).collect::<Vec<_>>(),
+        [(0, 0), (1, 1), (3, 33), (4, 40), (5, 5)]
+    );
+    assert_eq!(
+        before.diff(&a).collect::<Vec<_>>(),
+        [
+            DiffItem::Added(&0, &0),
+            DiffItem::Changed(&3, &3, &33),
+            DiffItem::Added(&4, &40),
+        ]
+    );
+
+    let mut c = tinymap!(i32 => i32; 3);
+    let mut d = tinymap!(i32 => i32; 3);
+    for i in 0..3 {
+        c.insert(i, i);
+        d.insert(i + 2, i);
+    }
+    let before = c.clone();
+    c.merge_with(d, |_, x, y| x * 100 + y);
+    assert!(matches!(c, TinyMap::Heap(_)));
+    assert_eq!(
+        c.iter().map(|(k, v)| (*k, *v)).collect::<Vec<_>>(),
+        [(0, 0), (1, 1), (2, 200), (3, 1), (4, 2)]
+    );
+    assert_eq!(c.diff(&c).count(), 0);
+    assert_eq!(
+        c.diff(&before).collect::<Vec<_>>(),
+        [
+            DiffItem::Changed(&2, &200, &2),
+            DiffItem::Removed(&3, &1),
+            DiffItem::Removed(&4, &2),
+        ]
+    );
+}
+
+#[test]
+fn joins_heap_and_std() {
+    use crate::join::EitherOrBoth;
+    use std::collections::{BTreeMap, BTreeSet};
+
+    let mut a = tinymap!(i32 => char; 2);
+    for (i, c) in "abcd".chars().enumerate() {
+        a.insert(i as i32, c);
+    }
+    assert!(matches!(a, TinyMap::Heap(_)));
+    let b: BTreeMap<i32, u8> = [(1, 10), (3, 30), (5, 50)].iter().copied().collect();
+    let s: BTreeSet<i32> = [0, 3].iter().copied().collect();
+
+    assert_eq!(
+        a.join(&b).collect::<Vec<_>>(),
+        [(&1, &'b', &10), (&3, &'d', &30)]
+    );
+    assert_eq!(
+        a.left_join(&s)
+            .map(|(k, _, r)| (*k, r.is_some()))
+            .collect::<Vec<_>>(),
+        [(0, true), (1, false), (2, false), (3, true)]
+    );
+    assert_eq!(
+        a.outer_join(&b).skip(3).collect::<Vec<_>>(),
+        [
+            (&3, EitherOrBoth::Both(&'d', &30)),
+            (&5, EitherOrBoth::Right(&50)),
+        ]
+    );
+
+    let mut set = arrayset!(i32; 4);
+    set.insert(3);
+    set.insert(5);
+    assert_eq!(set.join(&s).collect::<Vec<_>>(), [(&3, (), ())]);
+    assert_eq!(set.join(&b).count(), 2);
+}
+
+#[test]
+fn comparators_survive_spill() {
+    use crate::compare::{from_fn, Reverse};
+    use core::ops::Bound;
+
+    let mut map = TinyMap::<[Inner<(i32, char)>; 2], Reverse>::default();
+    for (i, c) in "abcd".chars().enumerate() {
+        map.insert(i as i32, c);
+    }
+    assert!(matches!(map, TinyMap::Heap(_)));
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [3, 2, 1, 0]);
+    assert_eq!(map.get(&1), Some(&'b'));
+    assert_eq!(map.rank(&1), 2);
+    assert_eq!(
+        map.lower_bound(Bound::Excluded(&2)).next(),
+        Some((&1, &'b'))
+    );
+    assert_eq!(map.get_index(1), Some((&2, &'c')));
+    assert_eq!(map.partition_point(|&k, _| k > 1), 2);
+    let mut cursor = map.upper_bound(Bound::Included(&2));
+    assert_eq!(cursor.peek_next(), Some((&1, &'b')));
+    assert_eq!(cursor.prev(), Some((&2, &'c')));
+    assert_eq!(cursor.prev(), Some((&3, &'d')));
+    assert_eq!(cursor.prev(), None);
+    assert_eq!(
+        map.clone().into_iter().map(|(_, c)| c).collect::<String>(),
+        "dcba"
+    );
+    let rest = map.split_off(&1);
+    assert_eq!(rest.keys().copied().collect::<Vec<_>>(), [1, 0]);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [3, 2]);
+    assert_eq!(map.remove_index(0), Some((3, 'd')));
+
+    let mut set = TinySet::<[Inner<i32>; 2], Reverse>::default();
+    set.extend(vec![5, 1, 3]);
+    assert!(matches!(set, TinySet::Heap(_)));
+    assert_eq!(set.iter().copied().collect::<Vec<_>>(), [5, 3, 1]);
+    assert!(set.contains(&3));
+    assert_eq!(set.index_of(&1), Some(2));
+    assert_eq!(set.get_index(0), Some(&5));
+    let low = set.split_off(&3);
+    assert_eq!(low.iter().copied().collect::<Vec<_>>(), [3, 1]);
+    assert_eq!(set.into_iter().collect::<Vec<_>>(), [5]);
+
+    let by_len = from_fn(|a: &&str, b: &&str| a.len().cmp(&b.len()));
+    let mut map = ArrayMap::<[Inner<(&str, i32)>; 4], _>::with_comparator(by_len);
+    map.insert("ccc", 3);
+    map.insert("a", 1);
+    map.insert("bb", 2);
+    assert_eq!(map.insert("zz", 4), Some(2));
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), ["a", "zz", "ccc"]);
+    let mut other = ArrayMap::<[Inner<(&str, i32)>; 4], _>::with_comparator(by_len);
+    other.insert("dddd", 4);
+    map.append(&mut other);
+    assert_eq!(map.len(), 4);
+}
+
+#[test]
+fn stateful_comparators_spill_into_vecs() {
+    use crate::compare::from_fn;
+    use core::ops::Bound;
+
+    // Orders numbers by their remainder, which a tree could not keep
+    let modulus = 10;
+    let by_rem = from_fn(move |a: &i32, b: &i32| (a % modulus).cmp(&(b % modulus)));
+
+    let mut map = TinyMap::<[Inner<(i32, char)>; 2], _, VecMap<_, _, _>>::with_comparator(by_rem);
+    for (k, c) in [(13, 'a'), (21, 'b'), (5, 'c'), (19, 'd')] {
+        map.insert(k, c);
+    }
+    assert!(map.is_spilled());
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [21, 13, 5, 19]);
+    assert_eq!(map.get(&3), Some(&'a'));
+    assert_eq!(map.get_index(2), Some((&5, &'c')));
+    assert_eq!(map.rank(&14), 2);
+    assert_eq!(map.index_of(&9), Some(3));
+    assert_eq!(
+        map.lower_bound(Bound::Excluded(&3)).next(),
+        Some((&5, &'c'))
+    );
+    *map.entry(41).or_insert('x') = 'e';
+    assert_eq!(map[&1], 'e');
+    if let Some(mut key) = map.get_key_mut(&5) {
+        *key = 16;
+    }
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [21, 13, 16, 19]);
+    let mut high = map.split_off(&15);
+    assert_eq!(high.keys().copied().collect::<Vec<_>>(), [16, 19]);
+    let mut other = TinyMap::<[Inner<(i32, char)>; 2], _, VecMap<_, _, _>>::with_comparator(by_rem);
+    other.insert(7, 'f');
+    high.append(&mut other);
+    assert!(other.is_empty());
+    assert_eq!(high.into_iter().map(|(_, c)| c).collect::<String>(), "cfd");
+    map.clear();
+    map.insert(12, 'g');
+    map.insert(2, 'h');
+    assert_eq!(map.iter().collect::<Vec<_>>(), [(&2, &'h')]);
+
+    let mut set = TinySet::<[Inner<i32>; 2], _, VecSet<_, _>>::with_comparator(by_rem);
+    set.extend(vec![13, 21, 5, 19]);
+    assert!(set.is_spilled());
+    assert_eq!(set.iter().copied().collect::<Vec<_>>(), [21, 13, 5, 19]);
+    assert!(set.contains(&3));
+    assert_eq!(set.entry(41).or_insert(), &21);
+    assert_eq!(set.get_or_insert(32), &32);
+    if let Some(mut value) = set.get_key_mut(&5) {
+        *value = 16;
+    }
+    assert_eq!(set.rank(&16), 3);
+    let high = set.split_off(&15);
+    assert_eq!(high.into_iter().collect::<Vec<_>>(), [16, 19]);
+    assert_eq!(set.remove_index(0), Some(21));
+    assert_eq!(set.iter().copied().collect::<Vec<_>>(), [32, 13]);
+}
+
+#[test]
+fn comparable_lookups() {
+    use crate::compare::{Comparable, Reverse};
+    use core::cmp::Ordering;
+
+    struct View<'a>(&'a str, u32);
+
+    impl Comparable<(String, u32)> for View<'_> {
+        fn compare(&self, key: &(String, u32)) -> Ordering {
+            (self.0, self.1).cmp(&(key.0.as_str(), key.1))
+        }
+    }
+
+    let mut map = ArrayMap::<[Inner<((String, u32), i32)>; 4]>::new();
+    map.insert((String::from("a"), 1), 1);
+    map.insert((String::from("b"), 2), 2);
+    assert_eq!(map.get(&View("b", 2)), Some(&2));
+    assert_eq!(map.rank(&View("b", 0)), 1);
+    assert_eq!(map.remove(&View("a", 1)), Some(1));
+
+    let mut map = ArrayMap::<[Inner<((String, u32), i32)>; 4], Reverse>::default();
+    for (i, s) in ["a", "b", "c", "d"].iter().enumerate() {
+        map.insert((String::from(*s), i as u32), i as i32);
+    }
+    assert_eq!(map[&View("c", 2)], 2);
+    assert_eq!(map.index_of(&View("c", 2)), Some(1));
+    assert_eq!(map.rank(&View("b", 5)), 2);
+    assert!(!map.contains_key(&View("c", 3)));
+    let rest = map.split_off(&View("b", 1));
+    assert_eq!(rest.len(), 2);
+
+    let mut set = TinySet::<[Inner<String>; 2]>::new();
+    set.extend(vec![
+        String::from("x"),
+        String::from("y"),
+        String::from("z"),
+    ]);
+    assert_eq!(set.get("y").map(String::as_str), Some("y"));
+    assert!(set.remove("x"));
+    assert!(!set.contains("x"));
+}
+
+#[test]
+fn comparable_lookups_heap() {
+    use crate::compare::Comparable;
+    use core::cmp::Ordering;
+
+    struct View<'a>(&'a str, u32);
+
+    impl Comparable<(String, u32)> for View<'_> {
+        fn compare(&self, key: &(String, u32)) -> Ordering {
+            (self.0, self.1).cmp(&(key.0.as_str(), key.1))
+        }
+    }
+
+    let mut map = TinyMap::<[Inner<((String, u32), i32)>; 2]>::new();
+    for (i, s) in ["a", "b", "c", "d"].iter().enumerate() {
+        map.insert((String::from(*s), i as u32), i as i32);
+    }
+    assert!(map.is_spilled());
+    assert_eq!(map.get(&View("b", 1)), Some(&1));
+    assert_eq!(map.get(&View("b", 2)), None);
+    assert_eq!(map.get(&View("e", 0)), None);
+    assert_eq!(map.get_key_value(&View("c", 2)).map(|(k, _)| k.1), Some(2));
+    assert!(map.contains_key(&View("d", 3)));
+    assert!(!map.contains_key(&View("0", 0)));
+    *map.get_mut(&View("c", 2)).unwrap() += 10;
+    assert_eq!(map[&View("c", 2)], 12);
+    assert_eq!(map.remove(&View("b", 1)), Some(1));
+    assert_eq!(map.remove(&View("b", 1)), None);
+    assert_eq!(map.remove(&View("bb", 0)), None);
+    assert_eq!(
+        map.remove_entry(&View("d", 3)),
+        Some(((String::from("d"), 3), 3))
+    );
+    map.get_key_mut(&View("a", 0)).unwrap().1 = 5;
+    assert_eq!(
+        map.keys().map(|k| (k.0.as_str(), k.1)).collect::<Vec<_>>(),
+        [("a", 5), ("c", 2)]
+    );
+
+    let mut set = TinySet::<[Inner<(String, u32)>; 2]>::new();
+    for (i, s) in ["a", "b", "c"].iter().enumerate() {
+        set.insert((String::from(*s), i as u32));
+    }
+    assert!(set.is_spilled());
+    assert!(set.contains(&View("b", 1)));
+    assert!(!set.contains(&View("b", 0)));
+    assert_eq!(set.get(&View("c", 2)).map(|v| v.1), Some(2));
+    assert!(set.remove(&View("a", 0)));
+    assert!(!set.remove(&View("a", 0)));
+    assert_eq!(set.take(&View("c", 2)), Some((String::from("c"), 2)));
+    assert_eq!(set.take(&View("z", 0)), None);
+    assert_eq!(set.len(), 1);
+}
+
+#[test]
+fn get_many_mut_heap() {
+    let mut map = tinymap!(String => i32; 2);
+    for (i, s) in ["a", "b", "c", "d"].iter().enumerate() {
+        map.insert(String::from(*s), i as i32);
+    }
+    assert!(matches!(map, TinyMap::Heap(_)));
+    let [d, a, c] = map.get_many_mut(["d", "a", "c"]).unwrap();
+    *d += 10;
+    *a += 20;
+    *c += 30;
+    assert_eq!(map["a"], 20);
+    assert_eq!(map["d"], 13);
+    assert!(map.get_many_mut(["a", "b", "a"]).is_none());
+    assert!(map.get_many_mut(["a", "e"]).is_none());
+    assert!(map.get_many_mut(["bb", "a"]).is_none());
+    assert!(map.get_many_mut(["0", "d"]).is_none());
+    let [] = map.get_many_mut::<str, 0>([]).unwrap();
+    let [b] = unsafe { map.get_many_unchecked_mut(["b"]) }.unwrap();
+    *b = -1;
+    assert_eq!(map["b"], -1);
+}
+
+#[test]
+fn stored_keys_returned() {
+    use std::{borrow::Borrow, cmp::Ordering};
+
+    #[derive(Debug, Clone, Copy)]
+    struct Tagged(u32, &'static str);
+
+    impl PartialEq for Tagged {
+        fn eq(&self, other: &Self) -> bool {
+            self.0 == other.0
+        }
+    }
+    impl Eq for Tagged {}
+    impl PartialOrd for Tagged {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+    impl Ord for Tagged {
+        fn cmp(&self, other: &Self) -> Ordering {
+            self.0.cmp(&other.0)
+        }
+    }
+    impl Borrow<u32> for Tagged {
+        fn borrow(&self) -> &u32 {
+            &self.0
+        }
+    }
+
+    let mut map = tinymap!(Tagged => i32; 2);
+    for (i, tag) in ["a", "b", "c"].iter().enumerate() {
+        map.insert(Tagged(i as u32, tag), i as i32);
+    }
+    assert!(matches!(map, TinyMap::Heap(_)));
+    assert_eq!(map.get_key_value(&1).unwrap().0 .1, "b");
+    assert_eq!(map.remove_entry(&2).unwrap().0 .1, "c");
+    assert_eq!(map.remove_entry(&2), None);
+
+    let mut set = tinyset!(Tagged; 2);
+    set.insert(Tagged(1, "x"));
+    assert_eq!(set.replace(Tagged(1, "y")).unwrap().1, "x");
+    assert_eq!(set.get_or_insert_with(&2, |&i| Tagged(i, "z")).1, "z");
+    assert_eq!(set.get_or_insert_with(&3, |&i| Tagged(i, "w")).1, "w");
+    assert!(matches!(set, TinySet::Heap(_)));
+    assert_eq!(set.replace(Tagged(2, "v")).unwrap().1, "z");
+    assert_eq!(set.replace(Tagged(4, "u")), None);
+    assert_eq!(set.take(&1).unwrap().1, "y");
+    assert_eq!(set.iter().map(|t| t.1).collect::<Vec<_>>(), ["v", "w", "u"]);
+}
+
+#[test]
+fn set_entries_spill() {
+    use crate::tiny_set::Entry;
+
+    let mut set = tinyset!(i32; 2);
+    assert_eq!(set.entry(3).or_insert(), &3);
+    assert_eq!(set.entry(1).or_insert(), &1);
+    assert!(matches!(set, TinySet::Stack(_)));
+    assert_eq!(set.get_or_insert(2), &2);
+    assert!(matches!(set, TinySet::Heap(_)));
+    assert_eq!(set.entry(0).or_insert(), &0);
+    assert_eq!(set.iter().copied().collect::<Vec<_>>(), [0, 1, 2, 3]);
+    match set.entry(2) {
+        Entry::Occupied(entry) => assert_eq!(entry.remove(), 2),
+        Entry::Vacant(_) => unreachable!(),
+    }
+    match set.entry(2) {
+        Entry::Vacant(entry) => assert_eq!(entry.into_value(), 2),
+        Entry::Occupied(_) => unreachable!(),
+    }
+    assert_eq!(set.len(), 3);
+
+    let mut set = tinyset!(i32; 4);
+    set.extend(vec![1, 2, 3]);
+    match set.entry(2) {
+        Entry::Occupied(entry) => assert_eq!(entry.remove(), 2),
+        Entry::Vacant(_) => unreachable!(),
+    }
+    assert_eq!(set.iter().copied().collect::<Vec<_>>(), [1, 3]);
+}
+
+#[test]
+fn key_guards_restore_order() {
+    let mut map = ArrayMap::<[Inner<(i32, &str)>; 4]>::new();
+    map.extend(vec![(1, "a"), (2, "b"), (3, "c")]);
+    *map.get_key_mut(&1).unwrap() = 4;
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [2, 3, 4]);
+    assert_eq!(map[&4], "a");
+
+    let mut map = tinymap!(i32 => &str; 2);
+    map.extend(vec![(1, "a"), (2, "b"), (3, "c")]);
+    assert!(matches!(map, TinyMap::Heap(_)));
+    *map.get_key_mut(&3).unwrap() = 0;
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [0, 1, 2]);
+    assert_eq!(*map.get_key_mut(&0).unwrap(), 0);
+    assert_eq!(map.iter().next(), Some((&0, &"c")));
+    assert!(map.get_key_mut(&5).is_none());
+
+    let mut set = tinyset!(i32; 2);
+    set.extend(vec![1, 2, 3]);
+    *set.get_key_mut(&1).unwrap() = 4;
+    assert_eq!(set.iter().copied().collect::<Vec<_>>(), [2, 3, 4]);
+    let mut value = set.get_key_mut(&2).unwrap();
+    assert_eq!(*value, 2);
+    *value = 2;
+    drop(value);
+    assert_eq!(set.iter().copied().collect::<Vec<_>>(), [2, 3, 4]);
+}
+
+#[test]
+fn key_guards_replace_on_collision() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    let mut map = ArrayMap::<[Inner<(i32, &str)>; 4]>::new();
+    map.extend(vec![(1, "a"), (2, "b"), (3, "c")]);
+    *map.get_key_mut(&1).unwrap() = 3;
+    assert_eq!(map.iter().collect::<Vec<_>>(), [(&2, &"b"), (&3, &"a")]);
+    let mut key = map.get_key_mut(&2).unwrap();
+    *key = 3;
+    assert_eq!(key.finish(), Err((3, "b")));
+    assert_eq!(map.iter().collect::<Vec<_>>(), [(&3, &"a")]);
+
+    let mut map = tinymap!(String => &str; 2);
+    map.extend(vec![
+        ("a".into(), "a"),
+        ("b".into(), "b"),
+        ("c".into(), "c"),
+    ]);
+    assert!(matches!(map, TinyMap::Heap(_)));
+    map.get_key_mut("c").unwrap().replace_range(.., "a");
+    assert_eq!(map.iter().map(|(_, v)| *v).collect::<Vec<_>>(), ["c", "b"]);
+    let mut key = map.get_key_mut("b").unwrap();
+    key.replace_range(.., "a");
+    assert_eq!(key.finish(), Err(("a".into(), "b")));
+    assert_eq!(map.iter().map(|(_, v)| *v).collect::<Vec<_>>(), ["c"]);
+    let mut key = map.get_key_mut("a").unwrap();
+    key.replace_range(.., "d");
+    assert_eq!(key.finish(), Ok(()));
+    assert_eq!(map["d"], "c");
+
+    let mut set = arrayset!(i32; 4);
+    set.extend(vec![1, 2, 3]);
+    *set.get_key_mut(&3).unwrap() = 1;
+    assert_eq!(set.iter().copied().collect::<Vec<_>>(), [1, 2]);
+    let mut value = set.get_key_mut(&2).unwrap();
+    *value = 1;
+    assert_eq!(value.finish(), Err(1));
+    assert_eq!(set.iter().copied().collect::<Vec<_>>(), [1]);
+
+    let mut set = tinyset!(String; 2);
+    set.extend(vec!["a".to_string(), "b".into(), "c".into()]);
+    set.get_key_mut("a").unwrap().replace_range(.., "b");
+    assert_eq!(set.iter().collect::<Vec<_>>(), ["b", "c"]);
+    let mut value = set.get_key_mut("c").unwrap();
+    value.replace_range(.., "b");
+    assert_eq!(value.finish(), Err("b".into()));
+    assert_eq!(set.iter().collect::<Vec<_>>(), ["b"]);
+
+    // Dropping a colliding guard while unwinding must not panic again
+    let mut map = ArrayMap::<[Inner<(i32, &str)>; 4]>::new();
+    map.extend(vec![(1, "a"), (2, "b")]);
+    assert!(catch_unwind(AssertUnwindSafe(|| {
+        let mut key = map.get_key_mut(&1).unwrap();
+        *key = 2;
+        panic!("unwinding with a colliding key");
+    }))
+    .is_err());
+    assert_eq!(map.iter().collect::<Vec<_>>(), [(&2, &"a")]);
+
+    let mut map = tinymap!(i32 => &str; 1);
+    map.extend(vec![(1, "a"), (2, "b")]);
+    assert!(catch_unwind(AssertUnwindSafe(|| {
+        let mut key = map.get_key_mut(&1).unwrap();
+        *key = 2;
+        panic!("unwinding with a colliding key");
+    }))
+    .is_err());
+    assert_eq!(map.iter().collect::<Vec<_>>(), [(&2, &"a")]);
+
+    let mut set = arrayset!(i32; 4);
+    set.extend(vec![1, 2]);
+    assert!(catch_unwind(AssertUnwindSafe(|| {
+        let mut value = set.get_key_mut(&1).unwrap();
+        *value = 2;
+        panic!("unwinding with a colliding value");
+    }))
+    .is_err());
+    assert_eq!(set.iter().copied().collect::<Vec<_>>(), [2]);
+
+    let mut set = tinyset!(i32; 1);
+    set.extend(vec![1, 2]);
+    assert!(catch_unwind(AssertUnwindSafe(|| {
+        let mut value = set.get_key_mut(&1).unwrap();
+        *value = 2;
+        panic!("unwinding with a colliding value");
+    }))
+    .is_err());
+    assert_eq!(set.iter().copied().collect::<Vec<_>>(), [2]);
+}
+
+#[test]
+fn map_values_keeps_state() {
+    use std::rc::Rc;
+
+    let rc = Rc::new(());
+    let mut map = tinymap!(i32 => Rc<()>; 4);
+    for i in 0..4 {
+        map.insert(i, rc.clone());
+    }
+    let result = map.try_map_values(|&k, v| if k < 2 { Ok(v) } else { Err(k) });
+    assert_eq!(result.err(), Some(2));
+    assert_eq!(Rc::strong_count(&rc), 1);
+
+    let mut map = tinymap!(i32 => i32; 2);
+    map.extend(vec![(1, 1), (2, 2), (3, 3)]);
+    let map = map.filter_map_values(|_, v| if v == 2 { None } else { Some(v * 10) });
+    assert!(matches!(map, TinyMap::Heap(_)));
+    assert_eq!(map.iter().collect::<Vec<_>>(), [(&1, &10), (&3, &30)]);
+}
+
+#[test]
+fn conversions_move_entries() {
+    use std::rc::Rc;
+
+    let rc = Rc::new(());
+    let mut map = arraymap!(i32 => Rc<()>; 3);
+    for i in 0..3 {
+        map.insert(i, rc.clone());
+    }
+    let map = map.try_convert::<[Inner<(i32, Rc<()>)>; 2]>().unwrap_err();
+    let map = map.resize_into::<[Inner<(i32, Rc<()>)>; 5]>();
+    assert_eq!(Rc::strong_count(&rc), 4);
+    let map = TinyMap::from(map).resize_into::<[Inner<(i32, Rc<()>)>; 1]>();
+    assert!(matches!(map, TinyMap::Heap(_)));
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [0, 1, 2]);
+    drop(map);
+    assert_eq!(Rc::strong_count(&rc), 1);
+}
+
+#[test]
+#[should_panic]
+fn resize_into_too_small() {
+    let mut set = arrayset!(i32; 3);
+    set.extend(vec![1, 2, 3]);
+    set.resize_into::<[Inner<i32>; 2]>();
+}
+
+#[test]
+fn into_std_collections() {
+    use std::collections::{BTreeMap, BTreeSet};
+    use std::rc::Rc;
+
+    let rc = Rc::new(());
+    let mut map = arraymap!(i32 => Rc<()>; 4);
+    map.insert(2, rc.clone());
+    map.insert(1, rc.clone());
+    let vec = Vec::from(map);
+    assert_eq!(vec.iter().map(|(k, _)| *k).collect::<Vec<_>>(), [1, 2]);
+    drop(vec);
+    assert_eq!(Rc::strong_count(&rc), 1);
+
+    let mut map = TinyMap::<[Inner<(i32, i32)>; 2], compare::Reverse>::default();
+    map.extend(vec![(1, 1), (2, 2), (3, 3)]);
+    let tree = BTreeMap::from(map);
+    assert_eq!(tree.keys().copied().collect::<Vec<_>>(), [1, 2, 3]);
+    let map = TinyMap::<[Inner<(i32, i32)>; 2], compare::Reverse>::from(tree);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [3, 2, 1]);
+    assert_eq!(map.into_btree_map().len(), 3);
+
+    let mut set = arrayset!(i32; 3);
+    set.extend(vec![3, 1]);
+    assert_eq!(Vec::from(set.clone()), [1, 3]);
+    assert_eq!(BTreeSet::from(set).len(), 2);
+    let mut set = tinyset!(i32; 2);
+    set.extend(vec![3, 1, 2]);
+    let tree = BTreeSet::from(set);
+    assert_eq!(tree.iter().copied().collect::<Vec<_>>(), [1, 2, 3]);
+    let set = TinySet::<[Inner<i32>; 2], compare::Reverse>::from(tree);
+    assert_eq!(set.iter().copied().collect::<Vec<_>>(), [3, 2, 1]);
+}
+
+#[test]
+fn collect_skips_stack() {
+    let map: TinyMap<[Inner<(i32, i32)>; 2]> = (0..3).map(|i| (i, i)).collect();
+    assert!(map.is_spilled());
+    assert_eq!(map.len(), 3);
+    let map: TinyMap<[Inner<(i32, i32)>; 2]> = (0..3).filter(|_| true).map(|i| (i, i)).collect();
+    assert!(map.is_spilled());
+    let map: TinyMap<[Inner<(i32, i32)>; 2]> = (0..2).map(|i| (i, i)).collect();
+    assert!(!map.is_spilled());
+
+    let set: TinySet<[Inner<i32>; 2]> = (0..5).collect();
+    assert!(set.is_spilled());
+    assert_eq!(set.iter().copied().collect::<Vec<_>>(), [0, 1, 2, 3, 4]);
+    let mut set = TinySet::<[Inner<i32>; 2], compare::Reverse>::with_heap();
+    set.extend(vec![1, 2]);
+    assert_eq!(set.iter().copied().collect::<Vec<_>>(), [2, 1]);
+}
+
+#[test]
+fn heap_size_counts_contents() {
+    use std::collections::BTreeMap;
+
+    let mut map = tinymap!(i32 => String; 2);
+    map.insert(1, String::with_capacity(4));
+    map.insert(2, String::with_capacity(8));
+    assert_eq!(map.heap_size(), 12);
+    map.insert(3, String::with_capacity(16));
+    let tree: BTreeMap<i32, String> = map.iter().map(|(&k, v)| (k, v.clone())).collect();
+    let strings: usize = map.values().map(String::capacity).sum();
+    assert_eq!(
+        map.heap_size() - strings,
+        tree.heap_size() - tree.values().map(String::capacity).sum::<usize>()
+    );
+
+    let mut set = tinyset!(Box<u64>; 2);
+    set.insert(Box::new(1));
+    assert_eq!(set.heap_size(), 8);
+    assert_eq!(arraymap!(&str => Vec<u8>; 2).heap_size(), 0);
+}
+
+#[test]
+fn slice_storage() {
+    use std::rc::Rc;
+
+    let rc = Rc::new(());
+    let mut storage: [Inner<(i32, Rc<()>)>; 3] = std::array::from_fn(|_| Inner::uninit());
+    {
+        let mut map = SliceMap::new_in(&mut storage);
+        for i in (0..3).rev() {
+            map.insert(i, rc.clone());
+        }
+        assert!(map.try_insert(3, rc.clone()).is_err());
+        assert_eq!(map.keys().copied().collect::<Vec<_>>(), [0, 1, 2]);
+        map.remove(&1);
+        *map.get_key_mut(&2).unwrap() = -1;
+        assert_eq!(map.keys().copied().collect::<Vec<_>>(), [-1, 0]);
+        let copy = map
+            .iter()
+            .map(|(&k, v)| (k, v.clone()))
+            .collect::<ArrayMap<[Inner<(i32, Rc<()>)>; 2]>>();
+        assert!(map.iter().eq(copy.iter()));
+    }
+    assert_eq!(Rc::strong_count(&rc), 1);
+
+    let mut storage = [Inner::<i32>::uninit(); 4];
+    let mut set = SliceSet::with_comparator_in(&mut storage, compare::Reverse(compare::Natural));
+    set.extend(vec![1, 3, 2]);
+    assert_eq!(set.iter().copied().collect::<Vec<_>>(), [3, 2, 1]);
+    let set = set.try_convert::<[Inner<i32>; 3]>().ok().unwrap();
+    assert_eq!(set.iter().copied().collect::<Vec<_>>(), [3, 2, 1]);
+}
+
+#[test]
+fn boxed_storage() {
+    use std::rc::Rc;
+
+    let rc = Rc::new(());
+    {
+        let mut map = BoxedArrayMap::with_capacity(3);
+        for i in (0..3).rev() {
+            map.insert(i, rc.clone());
+        }
+        assert_eq!(map.capacity(), 3);
+        assert!(map.try_insert(3, rc.clone()).is_err());
+        let copy = map.clone();
+        assert_eq!(copy.capacity(), 3);
+        assert!(map.iter().eq(copy.iter()));
+        map.remove(&1);
+        let mut iter = map.into_iter();
+        assert_eq!(iter.next().map(|(k, _)| k), Some(0));
+        assert_eq!(Rc::strong_count(&rc), 5);
+    }
+    assert_eq!(Rc::strong_count(&rc), 1);
+
+    let mut set =
+        BoxedArraySet::with_capacity_and_comparator(2, compare::Reverse(compare::Natural));
+    set.extend(vec![1, 2]);
+    assert_eq!(set.try_insert(3), Err(3));
+    assert_eq!(set.clone().into_iter().collect::<Vec<_>>(), [2, 1]);
+    assert_eq!(BoxedArraySet::<i32>::with_capacity(0).try_insert(1), Err(1));
+}
+
+#[cfg(feature = "allocator_api")]
+#[test]
+fn spill_into_allocator() {
+    use std::{
+        alloc::{AllocError, Allocator, Global, Layout},
+        cell::Cell,
+        ptr::NonNull,
+    };
+
+    #[derive(Clone, Copy)]
+    struct Counting<'a>(&'a Cell<usize>);
+
+    unsafe impl Allocator for Counting<'_> {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            self.0.set(self.0.get() + 1);
+            Global.allocate(layout)
+        }
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            Global.deallocate(ptr, layout)
+        }
+    }
+
+    let count = Cell::new(0);
+    let mut map = TinyVecMap::<
+        [Inner<(i32, String)>; 2],
+        compare::Reverse,
+        Vec<Inner<(i32, String)>, Counting<'_>>,
+    >::new_in(Counting(&count));
+    map.extend((0..2).map(|i| (i, i.to_string())));
+    assert_eq!(count.get(), 0);
+    map.insert(2, "2".into());
+    assert!(map.is_spilled());
+    assert_eq!(count.get(), 1);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [2, 1, 0]);
+    assert!(map.heap_size() > map.capacity() * std::mem::size_of::<(i32, String)>());
+
+    let mut set =
+        TinyVecSet::<[Inner<i32>; 2], compare::Natural, Vec<Inner<i32>, Counting<'_>>>::with_heap_in(
+            Counting(&count),
+        );
+    set.extend(vec![3, 1, 2]);
+    assert_eq!(count.get(), 2);
+    assert_eq!(set.iter().copied().collect::<Vec<_>>(), [1, 2, 3]);
+
+    let copy = map.clone();
+    assert_eq!(count.get(), 3);
+    let high = map.split_off(&2);
+    assert_eq!(count.get(), 4);
+    assert!(high.is_spilled() && map.is_empty());
+    assert_eq!(high.keys().copied().collect::<Vec<_>>(), [2, 1, 0]);
+    assert!(copy.into_iter().map(|(k, _)| k).eq([2, 1, 0]));
+    let high = set.split_off(&1);
+    assert_eq!(count.get(), 5);
+    assert!(high.into_iter().eq([1, 2, 3]));
+    assert_eq!(set.into_iter().next(), None);
+}
+
+#[cfg(feature = "allocator_api")]
+#[test]
+fn tree_in_allocator() {
+    use std::{
+        alloc::{AllocError, Allocator, Global, Layout},
+        cell::Cell,
+        collections::{BTreeMap, BTreeSet},
+        ptr::NonNull,
+    };
+
+    thread_local! {
+        static COUNT: Cell<usize> = const { Cell::new(0) };
+    }
+
+    // Counts in a thread local, so that a tree can be created with `Default` when the map spills
+    #[derive(Clone, Copy, Default)]
+    struct Counting;
+
+    unsafe impl Allocator for Counting {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            COUNT.with(|count| count.set(count.get() + 1));
+            Global.allocate(layout)
+        }
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            Global.deallocate(ptr, layout)
+        }
+    }
+
+    let count = || COUNT.with(Cell::get);
+    type Map =
+        TinyMap<[Inner<(i32, String)>; 2], compare::Reverse, BTreeMap<i32, String, Counting>>;
+    let mut map = Map::default();
+    map.extend((0..2).map(|i| (i, i.to_string())));
+    assert_eq!(count(), 0);
+    map.insert(2, "2".into());
+    assert!(map.is_spilled());
+    assert!(count() > 0);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [2, 1, 0]);
+    assert!(map.heap_size() > 0);
+
+    let mut other = Map::new_in(Counting);
+    assert!(other.is_spilled());
+    other.insert(5, "5".into());
+    other.insert(1, "one".into());
+    map.append(&mut other);
+    assert!(other.is_empty());
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [5, 2, 1, 0]);
+    assert_eq!(map[&1], "one");
+    let low = map.split_off(&1);
+    assert!(low.into_keys().eq([1, 0]));
+    assert!(map.into_iter().map(|(k, _)| k).eq([5, 2]));
+
+    let before = count();
+    let mut set =
+        TinySet::<[Inner<i32>; 2], compare::Natural, BTreeSet<i32, Counting>>::new_in(Counting);
+    set.extend(vec![3, 1, 2]);
+    assert!(count() > before);
+    assert_eq!(set.iter().copied().collect::<Vec<_>>(), [1, 2, 3]);
+    let high = set.split_off(&2);
+    assert!(high.into_iter().eq([2, 3]));
+    assert_eq!(set.into_iter().collect::<Vec<_>>(), [1]);
+}
+
+#[test]
+fn split_off_grows() {
+    let mut map = (0..20)
+        .map(|i| (i, i.to_string()))
+        .collect::<VecMap<_, _>>();
+    let high = map.split_off(&5);
+    assert!(map.keys().copied().eq(0..5));
+    assert!(high.into_keys().eq(5..20));
+
+    let mut set = (0..20).collect::<VecSet<_>>();
+    let high = set.split_off(&5);
+    assert!(set.iter().copied().eq(0..5));
+    assert!(high.into_iter().eq(5..20));
+
+    let mut map = (0..20)
+        .map(|i| (i, i.to_string()))
+        .collect::<TinyVecMap<[Inner<(i32, String)>; 4]>>();
+    let high = map.split_off(&5);
+    assert!(high.is_spilled());
+    assert!(map.keys().copied().eq(0..5));
+    assert!(high.clone().into_iter().map(|(k, _)| k).eq(5..20));
+    let mut rest = high.into_iter();
+    assert_eq!(rest.next().map(|(k, _)| k), Some(5));
+
+    let mut set = (0..20).collect::<TinyVecSet<[Inner<i32>; 4]>>();
+    let high = set.split_off(&17);
+    assert!(!high.is_spilled());
+    assert!(set.iter().copied().eq(0..17));
+    assert!(high.into_iter().eq(17..20));
+
+    let mut map = BoxedArrayMap::with_capacity(4);
+    map.extend((0..4).map(|i| (i, i.to_string())));
+    let high = map.split_off(&1);
+    assert_eq!(high.capacity(), 4);
+    assert!(map.into_keys().eq(0..1));
+    assert!(high.into_keys().eq(1..4));
+}
+
+#[test]
+fn vec_storage() {
+    use std::rc::Rc;
+
+    let rc = Rc::new(());
+    {
+        let mut map = VecMap::new();
+        for i in (0..40).rev() {
+            assert!(map.try_insert(i, rc.clone()).is_ok());
+        }
+        assert!(map.capacity() >= 40);
+        assert!(map.keys().copied().eq(0..40));
+        let mut other = (40..80).map(|i| (i, rc.clone())).collect::<VecMap<_, _>>();
+        map.append(&mut other);
+        assert!(map.keys().copied().eq(0..80));
+        for i in (1..80).step_by(2) {
+            map.remove(&i);
+        }
+        map.shrink_to_fit();
+        assert_eq!(map.capacity(), 40);
+        let copy = map.clone();
+        assert!(map.iter().eq(copy.iter()));
+        let mut iter = copy.into_iter();
+        assert_eq!(iter.next().map(|(k, _)| k), Some(0));
+    }
+    assert_eq!(Rc::strong_count(&rc), 1);
+
+    {
+        let mut map = TinyVecMap::<[Inner<(i32, Rc<()>)>; 4]>::new();
+        for i in (0..4).rev() {
+            map.insert(i, rc.clone());
+        }
+        assert!(!map.is_spilled());
+        map.insert(4, rc.clone());
+        assert!(map.is_spilled());
+        assert!(map.keys().copied().eq(0..5));
+        assert!(map.clone().into_iter().map(|(k, _)| k).eq(0..5));
+    }
+    assert_eq!(Rc::strong_count(&rc), 1);
+
+    let mut set = VecSet::with_capacity_and_comparator(1, compare::Reverse(compare::Natural));
+    set.extend(vec![1, 3, 2]);
+    assert_eq!(set.iter().copied().collect::<Vec<_>>(), [3, 2, 1]);
+    let set = set.try_convert::<[Inner<i32>; 3]>().ok().unwrap();
+    assert_eq!(set.iter().copied().collect::<Vec<_>>(), [3, 2, 1]);
+
+    let set = (0..10).rev().collect::<TinyVecSet<[Inner<i32>; 8]>>();
+    assert!(set.is_spilled());
+    assert!(set.heap_size() >= 10 * std::mem::size_of::<i32>());
+    assert_eq!(TinyVecSet::<[Inner<i32>; 8]>::new().heap_size(), 0);
+    assert!(set.into_iter().eq(0..10));
+
+    let mut map = TinyVecMap::<[Inner<(i32, Rc<()>)>; 4]>::new();
+    map.extend((0..3).map(|i| (i, rc.clone())));
+    map.spill();
+    assert!(map.is_spilled());
+    assert!(map.capacity() >= 4);
+    map.spill();
+    assert!(map.keys().copied().eq(0..3));
+    drop(map);
+    assert_eq!(Rc::strong_count(&rc), 1);
+
+    let mut set = TinyVecSet::<[Inner<i32>; 4], compare::Reverse>::with_heap();
+    assert!(set.is_spilled());
+    assert_eq!(set.capacity(), 0);
+    set.extend(0..6);
+    assert!(set.iter().copied().eq((0..6).rev()));
+}
+
+/// A hasher that sends every small nonzero key to the last slot, so that entries collide and wrap around
+#[derive(Default)]
+struct Colliding(u64);
+
+impl std::hash::Hasher for Colliding {
+    fn finish(&self) -> u64 {
+        self.0.wrapping_neg()
+    }
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 = self.0 << 8 | u64::from(b);
+        }
+    }
+}
+
+type CollidingState = std::hash::BuildHasherDefault<Colliding>;
+
+#[test]
+fn array_hash_map_matches_hash_map() {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+    use std::collections::HashMap;
+
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut colliding = ArrayHashMap::<[Inner<(u8, u32)>; 16], CollidingState>::default();
+    let mut random = ArrayHashMap::<[Inner<(u8, u32)>; 16]>::new();
+    let mut expected = HashMap::new();
+    for i in 0..10_000 {
+        let key = rng.gen_range(0, 24);
+        if rng.gen_bool(0.5) {
+            let res = colliding.try_insert(key, i);
+            assert_eq!(res, random.try_insert(key, i));
+            if let Ok(old) = res {
+                assert_eq!(old, expected.insert(key, i));
+            } else {
+                assert_eq!(expected.len(), 14);
+            }
+        } else {
+            let res = colliding.remove(&key);
+            assert_eq!(res, random.remove(&key));
+            assert_eq!(res, expected.remove(&key));
+        }
+        assert_eq!(colliding.len(), expected.len());
+        assert!((0..24).all(|k| colliding.get(&k) == expected.get(&k)));
+        assert!((0..24).all(|k| random.get(&k) == expected.get(&k)));
+    }
+    let mut entries = colliding.into_iter().collect::<Vec<_>>();
+    entries.sort_unstable();
+    let mut expected = expected.into_iter().collect::<Vec<_>>();
+    expected.sort_unstable();
+    assert_eq!(entries, expected);
+}
+
+#[test]
+fn hash_retain_visits_each_entry_once() {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0..200 {
+        // Every key starts probing from the last slot, so the entries wrap around the array
+        let mut map = ArrayHashMap::<[Inner<(u8, u8)>; 16], CollidingState>::default();
+        map.extend((1..15).map(|k| (k, k)));
+        let keep: Vec<bool> = (0..15).map(|_| rng.gen_bool(0.5)).collect();
+        let mut calls = [0; 15];
+        map.retain(|&k, v| {
+            calls[usize::from(k)] += 1;
+            *v += 1;
+            keep[usize::from(k)]
+        });
+        assert!(calls[1..].iter().all(|&c| c == 1));
+        for k in 1..15 {
+            let expected = keep[usize::from(k)].then_some(k + 1);
+            assert_eq!(map.get(&k).copied(), expected);
+        }
+        let kept = keep[1..].iter().filter(|&&k| k).count();
+        assert_eq!(map.len(), kept);
+        assert_eq!(map.iter().size_hint(), (kept, Some(kept)));
+
+        let mut set = ArrayHashSet::<[Inner<u8>; 16], CollidingState>::default();
+        set.extend(1..15);
+        let mut calls = [0; 15];
+        set.retain(|&k| {
+            calls[usize::from(k)] += 1;
+            keep[usize::from(k)]
+        });
+        assert!(calls[1..].iter().all(|&c| c == 1));
+        assert!((1..15).all(|k| set.contains(&k) == keep[usize::from(k)]));
+        assert_eq!(set.len(), kept);
+        assert_eq!(set.into_iter().size_hint(), (kept, Some(kept)));
+    }
+}
+
+#[test]
+fn hash_iterators_know_their_length() {
+    let mut map = (0..5)
+        .map(|i| (i, i))
+        .collect::<ArrayHashMap<[Inner<(i32, i32)>; 8]>>();
+    let mut iter = map.iter();
+    iter.next();
+    assert_eq!(iter.size_hint(), (4, Some(4)));
+    assert_eq!(map.keys().size_hint(), (5, Some(5)));
+    assert_eq!(map.values_mut().size_hint(), (5, Some(5)));
+    let mut iter = map.clone().into_iter();
+    iter.next();
+    iter.next();
+    assert_eq!(iter.size_hint(), (3, Some(3)));
+    assert_eq!(map.iter_mut().count(), 5);
+
+    let mut map = (0..5)
+        .map(|i| (i, i))
+        .collect::<TinyHashMap<[Inner<(i32, i32)>; 2]>>();
+    assert!(map.is_spilled());
+    assert_eq!(map.iter().size_hint(), (5, Some(5)));
+    assert_eq!(map.iter_mut().size_hint(), (5, Some(5)));
+    assert_eq!(map.into_iter().size_hint(), (5, Some(5)));
+}
+
+#[test]
+fn hash_containers_drop_entries() {
+    use std::rc::Rc;
+
+    let rc = Rc::new(());
+    {
+        let mut map = ArrayHashMap::<[Inner<(i32, Rc<()>)>; 4], CollidingState>::default();
+        for i in 0..4 {
+            map.insert(i, rc.clone());
+        }
+        assert!(map.try_insert(4, rc.clone()).is_err());
+        let copy = map.clone();
+        assert_eq!(map, copy);
+        map.retain(|&k, _| k != 1);
+        assert_eq!(map.len(), 3);
+        let mut iter = map.into_iter();
+        iter.next();
+        assert_eq!(Rc::strong_count(&rc), 7);
+    }
+    assert_eq!(Rc::strong_count(&rc), 1);
+
+    {
+        let mut set = ArrayHashSet::<[Inner<Rc<i32>>; 3]>::new();
+        for i in 0..3 {
+            assert!(set.insert(Rc::new(i)));
+        }
+        assert!(!set.insert(Rc::new(0)));
+        assert_eq!(set.try_insert(Rc::new(3)), Err(Rc::new(3)));
+        assert_eq!(set.take(&1), Some(Rc::new(1)));
+        assert!(set.contains(&2));
+    }
+
+    {
+        let mut map = TinyHashMap::<[Inner<(i32, Rc<()>)>; 2], CollidingState>::default();
+        map.extend((0..2).map(|i| (i, rc.clone())));
+        assert!(!map.is_spilled());
+        map.insert(2, rc.clone());
+        assert!(map.is_spilled());
+        assert!((0..3).all(|i| map.contains_key(&i)));
+        let copy = map.clone();
+        assert_eq!(map, copy);
+        map.retain(|&k, _| k != 0);
+        assert_eq!(map.into_iter().count(), 2);
+        assert_eq!(Rc::strong_count(&rc), 4);
+    }
+    assert_eq!(Rc::strong_count(&rc), 1);
+
+    let map = (0..3)
+        .map(|i| (i, i))
+        .collect::<TinyHashMap<[Inner<(i32, i32)>; 2]>>();
+    assert!(map.heap_size() > 0);
+
+    let mut map = TinyHashMap::<[Inner<(i32, i32)>; 16], CollidingState>::default();
+    assert_eq!(map.capacity(), 14);
+    map.extend((0..14).map(|i| (i, i)));
+    assert!(!map.is_spilled());
+    map.insert(14, 14);
+    assert!(map.is_spilled());
+    assert!((0..15).all(|i| map.get(&i) == Some(&i)));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn hash_serialization() {
+    let map = (0..5)
+        .map(|i| (i, i * 2))
+        .collect::<ArrayHashMap<[Inner<(i32, i32)>; 5]>>();
+    let ser = serde_yaml::to_string(&map).unwrap();
+    assert_eq!(map, serde_yaml::from_str(&ser).unwrap());
+    assert!(serde_yaml::from_str::<ArrayHashMap<[Inner<(i32, i32)>; 4]>>(&ser).is_err());
+    let tiny: TinyHashMap<[Inner<(i32, i32)>; 2]> = serde_yaml::from_str(&ser).unwrap();
+    assert!(tiny.is_spilled());
+    assert_eq!(
+        tiny.into_hash_map(),
+        map.iter().map(|(&k, &v)| (k, v)).collect()
+    );
+
+    let set = (0..5).collect::<ArrayHashSet<[Inner<i32>; 5]>>();
+    let ser = serde_yaml::to_string(&set).unwrap();
+    assert_eq!(set, serde_yaml::from_str(&ser).unwrap());
+}
+
+#[cfg(feature = "stats")]
+#[test]
+fn stats_exact() {
+    // Every recording goes into one test, since `reset` clears the statistics of all types
+    type Map = TinyMap<[Inner<(u16, i8)>; 3]>;
+    type Set = TinySet<[Inner<i16>; 3]>;
+    stats::reset();
+
+    let mut a = Map::new();
+    a.insert(1, 1);
+    a.insert(2, 2);
+    *a.entry(3).or_insert(0) += 3;
+    a.entry(3).or_insert(0);
+    assert_eq!(a.entry(100).key(), &100);
+    a.insert(4, 4);
+    assert!(a.is_spilled());
+    a.entry(5).or_insert_with(|| 5);
+    for i in 6..=8 {
+        a.entry(i).or_default();
+    }
+
+    let mut b = Map::new();
+    b.insert(10, 10);
+    let mut c = Map::new();
+    c.insert(20, 20);
+    c.insert(21, 21);
+    b.append(&mut c);
+    b.append(&mut c);
+    assert!(!b.is_spilled());
+    let mut d = Map::new();
+    d.insert(30, 30);
+    d.insert(31, 31);
+    b.append(&mut d);
+    assert!(b.is_spilled());
+
+    let mut e = Map::new();
+    e.insert(50, 1);
+    let mut f = Map::new();
+    f.insert(50, 2);
+    f.insert(51, 3);
+    e.merge_with(f, |_, x, y| x + y);
+    assert_eq!(e[&50], 3);
+
+    let report = stats::report();
+    let map_stats = report.of::<Map>().unwrap();
+    assert_eq!(map_stats.capacity, 3);
+    assert_eq!(map_stats.spills, 2);
+    assert_eq!(map_stats.peak_len, 8);
+    assert_eq!(map_stats.histogram, [6, 5, 2, 1, 2, 3]);
+
+    let mut s = Set::new();
+    s.insert(1);
+    s.insert(2);
+    s.entry(3).or_insert();
+    s.entry(3).or_insert();
+    match s.entry(4) {
+        tiny_set::Entry::Vacant(entry) => assert_eq!(entry.insert(), &4),
+        tiny_set::Entry::Occupied(_) => unreachable!(),
+    }
+    assert!(s.is_spilled());
+    s.get_or_insert(5);
+
+    let mut t = Set::new();
+    t.insert(10);
+    let mut u = Set::new();
+    u.insert(20);
+    t.append(&mut u);
+
+    let report = stats::report();
+    let set_stats = report.of::<Set>().unwrap();
+    assert_eq!(set_stats.spills, 1);
+    assert_eq!(set_stats.peak_len, 5);
+    assert_eq!(set_stats.histogram, [3, 2, 1, 1, 1, 0]);
+
+    stats::reset();
+    assert!(stats::report().of::<Map>().is_none());
+}
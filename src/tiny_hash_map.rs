@@ -0,0 +1,912 @@
+//! A hash map that starts on the stack but can automatically move to the heap
+
+use core::{
+    borrow::Borrow,
+    fmt,
+    hash::{BuildHasher, Hash},
+    iter::FromIterator,
+    ops::Index,
+    ptr,
+};
+use std::collections::{hash_map, HashMap};
+
+use crate::{
+    array_hash_map::{self, ArrayHashMap, DefaultHashBuilder},
+    MapArray,
+};
+
+/**
+A hash map that starts on the stack but can automatically move to the heap
+
+A TinyHashMap is an [`ArrayHashMap`](../array_hash_map/struct.ArrayHashMap.html) until it grows beyond
+its array's capacity. Then it moves its entries into a `std::collections::HashMap` that uses the same hasher.
+
+Its keys only need to implement `Hash` and `Eq`, and its entries are in no particular order.
+*/
+pub enum TinyHashMap<A, S = DefaultHashBuilder>
+where
+    A: MapArray,
+{
+    /// A map with items on the stack
+    Stack(ArrayHashMap<A, S>),
+    /// A map with items on the heap
+    Heap(HashMap<A::Key, A::Value, S>),
+}
+
+impl<A, S> Default for TinyHashMap<A, S>
+where
+    A: MapArray,
+    S: Default,
+{
+    fn default() -> Self {
+        TinyHashMap::Stack(ArrayHashMap::default())
+    }
+}
+
+impl<A, S> Clone for TinyHashMap<A, S>
+where
+    A: MapArray,
+    A::Key: Clone,
+    A::Value: Clone,
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        match self {
+            TinyHashMap::Stack(map) => TinyHashMap::Stack(map.clone()),
+            TinyHashMap::Heap(map) => TinyHashMap::Heap(map.clone()),
+        }
+    }
+}
+
+impl<A> TinyHashMap<A>
+where
+    A: MapArray,
+{
+    /**
+    Creates a new empty TinyHashMap
+
+    # Example
+    ```
+    use tinymap::*;
+
+    let mut map = TinyHashMap::<[Inner<(i32, &str)>; 2]>::new();
+
+    // entries can now be inserted into the empty map
+    map.insert(1, "a");
+    ```
+    */
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<A, S> TinyHashMap<A, S>
+where
+    A: MapArray,
+{
+    /**
+    Creates a new empty TinyHashMap that hashes its keys with the given hasher
+
+    # Example
+    ```
+    use std::collections::hash_map::RandomState;
+    use tinymap::*;
+
+    let mut map = TinyHashMap::<[Inner<(i32, &str)>; 2], _>::with_hasher(RandomState::new());
+    map.insert(1, "a");
+    ```
+    */
+    pub fn with_hasher(hasher: S) -> Self {
+        TinyHashMap::Stack(ArrayHashMap::with_hasher(hasher))
+    }
+    /**
+    Returns a reference to the map's hasher
+    */
+    pub fn hasher(&self) -> &S {
+        match self {
+            TinyHashMap::Stack(map) => map.hasher(),
+            TinyHashMap::Heap(map) => map.hasher(),
+        }
+    }
+    /**
+    Clears the map, removing all elements
+
+    A map on the heap stays there and keeps its allocation.
+
+    # Example
+    ```
+    use tinymap::*;
+
+    let mut a = TinyHashMap::<[Inner<(i32, &str)>; 2]>::new();
+    a.insert(1, "a");
+    a.clear();
+    assert!(a.is_empty());
+    ```
+    */
+    pub fn clear(&mut self) {
+        match self {
+            TinyHashMap::Stack(map) => map.clear(),
+            TinyHashMap::Heap(map) => map.clear(),
+        }
+    }
+    /**
+    Returns the number of elements in the map
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut a = TinyHashMap::<[Inner<(i32, &str)>; 2]>::new();
+    assert_eq!(a.len(), 0);
+    a.insert(1, "a");
+    assert_eq!(a.len(), 1);
+    ```
+    */
+    pub fn len(&self) -> usize {
+        match self {
+            TinyHashMap::Stack(map) => map.len(),
+            TinyHashMap::Heap(map) => map.len(),
+        }
+    }
+    /**
+    Returns `true` if the map contains no elements
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut a = TinyHashMap::<[Inner<(i32, &str)>; 2]>::new();
+    assert!(a.is_empty());
+    a.insert(1, "a");
+    assert!(!a.is_empty());
+    ```
+    */
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /**
+    Returns `true` if the map has moved to the heap
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = TinyHashMap::<[Inner<(i32, &str)>; 2]>::new();
+    map.insert(1, "a");
+    assert!(!map.is_spilled());
+    map.extend(vec![(1, "a"), (2, "b"), (3, "c")]);
+    assert!(map.is_spilled());
+    ```
+    */
+    pub fn is_spilled(&self) -> bool {
+        matches!(self, TinyHashMap::Heap(_))
+    }
+    /**
+    Returns the maximum number of elements the map can contain on the stack
+
+    Like an ArrayHashMap, this is seven eighths of the array's capacity, rounded up.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut a = TinyHashMap::<[Inner<(i32, &str)>; 2]>::new();
+    assert_eq!(2, a.capacity());
+    ```
+    */
+    pub fn capacity(&self) -> usize {
+        array_hash_map::max_load(A::CAPACITY)
+    }
+    /**
+    Gets an iterator over the entries of the map, in no particular order
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = TinyHashMap::<[Inner<(i32, &str)>; 2]>::new();
+    map.insert(3, "c");
+    map.insert(2, "b");
+    map.insert(1, "a");
+
+    let mut entries = map.iter().collect::<Vec<_>>();
+    entries.sort();
+    assert_eq!(entries, [(&1, &"a"), (&2, &"b"), (&3, &"c")]);
+    ```
+    */
+    pub fn iter(&self) -> Iter<'_, A::Key, A::Value> {
+        match self {
+            TinyHashMap::Stack(map) => Iter::Stack(map.iter()),
+            TinyHashMap::Heap(map) => Iter::Heap(map.iter()),
+        }
+    }
+    /**
+    Gets a mutable iterator over the entries of the map, in no particular order
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = TinyHashMap::<[Inner<(&str, i32)>; 2]>::new();
+    map.insert("a", 1);
+    map.insert("b", 2);
+    map.insert("c", 3);
+
+    // add 10 to the value if the key isn't "a"
+    for (key, value) in map.iter_mut() {
+        if key != &"a" {
+            *value += 10;
+        }
+    }
+
+    assert_eq!(map[&"b"], 12);
+    ```
+    */
+    pub fn iter_mut(&mut self) -> IterMut<'_, A::Key, A::Value> {
+        match self {
+            TinyHashMap::Stack(map) => IterMut::Stack(map.iter_mut()),
+            TinyHashMap::Heap(map) => IterMut::Heap(map.iter_mut()),
+        }
+    }
+    /**
+    Gets an iterator over the keys of the map, in no particular order
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut a = TinyHashMap::<[Inner<(i32, &str)>; 2]>::new();
+    a.insert(2, "b");
+    a.insert(1, "a");
+
+    let mut keys = a.keys().copied().collect::<Vec<_>>();
+    keys.sort();
+    assert_eq!(keys, [1, 2]);
+    ```
+    */
+    pub fn keys(&self) -> Keys<'_, A::Key, A::Value> {
+        match self {
+            TinyHashMap::Stack(map) => Keys::Stack(map.keys()),
+            TinyHashMap::Heap(map) => Keys::Heap(map.keys()),
+        }
+    }
+    /**
+    Gets an iterator over the values of the map, in no particular order
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut a = TinyHashMap::<[Inner<(i32, &str)>; 2]>::new();
+    a.insert(1, "hello");
+    a.insert(2, "goodbye");
+
+    let mut values = a.values().copied().collect::<Vec<_>>();
+    values.sort();
+    assert_eq!(values, ["goodbye", "hello"]);
+    ```
+    */
+    pub fn values(&self) -> Values<'_, A::Key, A::Value> {
+        match self {
+            TinyHashMap::Stack(map) => Values::Stack(map.values()),
+            TinyHashMap::Heap(map) => Values::Heap(map.values()),
+        }
+    }
+    /**
+    Gets a mutable iterator over the values of the map, in no particular order
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut a = TinyHashMap::<[Inner<(i32, String)>; 2]>::new();
+    a.insert(1, String::from("hello"));
+    a.insert(2, String::from("goodbye"));
+
+    for value in a.values_mut() {
+        value.push_str("!");
+    }
+
+    assert_eq!(a[&1], "hello!");
+    assert_eq!(a[&2], "goodbye!");
+    ```
+    */
+    pub fn values_mut(&mut self) -> ValuesMut<'_, A::Key, A::Value> {
+        match self {
+            TinyHashMap::Stack(map) => ValuesMut::Stack(map.values_mut()),
+            TinyHashMap::Heap(map) => ValuesMut::Heap(map.values_mut()),
+        }
+    }
+}
+
+/// Moves the entries of an ArrayHashMap into a HashMap that uses the same hasher
+fn into_heap<A, S>(map: ArrayHashMap<A, S>) -> HashMap<A::Key, A::Value, S>
+where
+    A: MapArray,
+    A::Key: Hash + Eq,
+    S: BuildHasher,
+{
+    let (entries, hasher) = map.into_parts();
+    let mut heap = HashMap::with_capacity_and_hasher(A::CAPACITY * 2, hasher);
+    heap.extend(entries);
+    heap
+}
+
+impl<A, S> TinyHashMap<A, S>
+where
+    A: MapArray,
+    A::Key: Hash + Eq,
+    S: BuildHasher,
+{
+    /**
+    Inserts a key-value pair into the map
+
+    If the map did not have this key present, None is returned.
+
+    If the map did have this key present, the value is updated, and the old value is returned.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = TinyHashMap::<[Inner<(i32, &str)>; 2]>::new();
+    assert_eq!(map.insert(37, "a"), None);
+    assert_eq!(map.is_empty(), false);
+
+    map.insert(37, "b");
+    assert_eq!(map.insert(37, "c"), Some("b"));
+    assert_eq!(map[&37], "c");
+    ```
+    */
+    pub fn insert(&mut self, key: A::Key, value: A::Value) -> Option<A::Value> {
+        let res = match self {
+            TinyHashMap::Stack(map) => match map.try_insert(key, value) {
+                Ok(res) => res,
+                Err((key, value)) => self.heap_mut().insert(key, value),
+            },
+            TinyHashMap::Heap(map) => map.insert(key, value),
+        };
+        #[cfg(feature = "stats")]
+        if res.is_none() {
+            crate::stats::record_len::<Self>(A::CAPACITY, self.len());
+        }
+        res
+    }
+    /// Moves the map to the heap if it is not there already
+    fn heap_mut(&mut self) -> &mut HashMap<A::Key, A::Value, S> {
+        if let TinyHashMap::Stack(map) = self {
+            #[cfg(feature = "stats")]
+            crate::stats::record_spill::<Self>(A::CAPACITY);
+            // The hasher has no placeholder to leave behind, so the map is moved out and the
+            // heap map is written back before anything that could panic. The heap map is only
+            // allocated once it is in place.
+            let (entries, hasher) = unsafe { ptr::read(map) }.into_parts();
+            unsafe { ptr::write(self, TinyHashMap::Heap(HashMap::with_hasher(hasher))) };
+            if let TinyHashMap::Heap(heap) = self {
+                heap.reserve(A::CAPACITY * 2);
+                heap.extend(entries);
+            }
+        }
+        match self {
+            TinyHashMap::Heap(map) => map,
+            TinyHashMap::Stack(_) => unreachable!(),
+        }
+    }
+    /**
+    Moves the map to the heap if it is not there already
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = TinyHashMap::<[Inner<(i32, &str)>; 2]>::new();
+    map.insert(1, "a");
+    map.spill();
+    assert!(map.is_spilled());
+    assert_eq!(map.len(), 1);
+    ```
+    */
+    pub fn spill(&mut self) {
+        self.heap_mut();
+    }
+    /**
+    Returns a reference to the value corresponding to the key
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = TinyHashMap::<[Inner<(i32, &str)>; 2]>::new();
+    map.insert(1, "a");
+    assert_eq!(map.get(&1), Some(&"a"));
+    assert_eq!(map.get(&2), None);
+    ```
+    */
+    pub fn get<Q>(&self, key: &Q) -> Option<&A::Value>
+    where
+        A::Key: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self {
+            TinyHashMap::Stack(map) => map.get(key),
+            TinyHashMap::Heap(map) => map.get(key),
+        }
+    }
+    /**
+    Returns the key-value pair corresponding to the supplied key
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = TinyHashMap::<[Inner<(i32, &str)>; 2]>::new();
+    map.insert(1, "a");
+    assert_eq!(map.get_key_value(&1), Some((&1, &"a")));
+    assert_eq!(map.get_key_value(&2), None);
+    ```
+    */
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&A::Key, &A::Value)>
+    where
+        A::Key: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self {
+            TinyHashMap::Stack(map) => map.get_key_value(key),
+            TinyHashMap::Heap(map) => map.get_key_value(key),
+        }
+    }
+    /**
+    Returns `true` if the map contains a value for the specified key
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = TinyHashMap::<[Inner<(i32, &str)>; 2]>::new();
+    map.insert(1, "a");
+    assert_eq!(map.contains_key(&1), true);
+    assert_eq!(map.contains_key(&2), false);
+    ```
+    */
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        A::Key: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self {
+            TinyHashMap::Stack(map) => map.contains_key(key),
+            TinyHashMap::Heap(map) => map.contains_key(key),
+        }
+    }
+    /**
+    Returns a mutable reference to the value corresponding to the key
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = TinyHashMap::<[Inner<(i32, &str)>; 2]>::new();
+    map.insert(1, "a");
+    if let Some(x) = map.get_mut(&1) {
+        *x = "b";
+    }
+    assert_eq!(map[&1], "b");
+    ```
+    */
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut A::Value>
+    where
+        A::Key: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self {
+            TinyHashMap::Stack(map) => map.get_mut(key),
+            TinyHashMap::Heap(map) => map.get_mut(key),
+        }
+    }
+    /**
+    Removes a key from the map, returning the value at the key if the key was previously in the map
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = TinyHashMap::<[Inner<(i32, &str)>; 2]>::new();
+    map.insert(1, "a");
+    assert_eq!(map.remove(&1), Some("a"));
+    assert_eq!(map.remove(&1), None);
+    ```
+    */
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<A::Value>
+    where
+        A::Key: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self {
+            TinyHashMap::Stack(map) => map.remove(key),
+            TinyHashMap::Heap(map) => map.remove(key),
+        }
+    }
+    /**
+    Removes a key from the map, returning the stored key and value if the key was previously in the map
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = TinyHashMap::<[Inner<(i32, &str)>; 2]>::new();
+    map.insert(1, "a");
+    assert_eq!(map.remove_entry(&1), Some((1, "a")));
+    assert_eq!(map.remove_entry(&1), None);
+    ```
+    */
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(A::Key, A::Value)>
+    where
+        A::Key: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self {
+            TinyHashMap::Stack(map) => map.remove_entry(key),
+            TinyHashMap::Heap(map) => map.remove_entry(key),
+        }
+    }
+    /**
+    Retains only the elements specified by the predicate
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map: TinyHashMap<[Inner<(i32, i32)>; 2]> = (0..8).map(|x| (x, x * 10)).collect();
+    map.retain(|&k, _| k % 2 == 0);
+    assert_eq!(map.len(), 4);
+    assert!(map.keys().all(|k| k % 2 == 0));
+    ```
+    */
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&A::Key, &mut A::Value) -> bool,
+    {
+        match self {
+            TinyHashMap::Stack(map) => map.retain(f),
+            TinyHashMap::Heap(map) => map.retain(f),
+        }
+    }
+    /**
+    Moves the entries into a HashMap that uses the same hasher
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = TinyHashMap::<[Inner<(i32, &str)>; 2]>::new();
+    map.insert(1, "a");
+    let hash_map = map.into_hash_map();
+    assert_eq!(hash_map[&1], "a");
+    ```
+    */
+    pub fn into_hash_map(self) -> HashMap<A::Key, A::Value, S> {
+        match self {
+            TinyHashMap::Stack(map) => into_heap(map),
+            TinyHashMap::Heap(map) => map,
+        }
+    }
+}
+
+impl<A, S, Q> Index<&Q> for TinyHashMap<A, S>
+where
+    A: MapArray,
+    A::Key: Hash + Eq + Borrow<Q>,
+    Q: Hash + Eq + ?Sized,
+    S: BuildHasher,
+{
+    type Output = A::Value;
+    fn index(&self, key: &Q) -> &Self::Output {
+        self.get(key)
+            .unwrap_or_else(|| panic!("No entry found for key"))
+    }
+}
+
+impl<A, S> fmt::Debug for TinyHashMap<A, S>
+where
+    A: MapArray,
+    A::Key: fmt::Debug,
+    A::Value: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<A, S> From<ArrayHashMap<A, S>> for TinyHashMap<A, S>
+where
+    A: MapArray,
+{
+    fn from(map: ArrayHashMap<A, S>) -> Self {
+        TinyHashMap::Stack(map)
+    }
+}
+
+impl<A, S> From<HashMap<A::Key, A::Value, S>> for TinyHashMap<A, S>
+where
+    A: MapArray,
+{
+    fn from(map: HashMap<A::Key, A::Value, S>) -> Self {
+        TinyHashMap::Heap(map)
+    }
+}
+
+impl<A, S> From<TinyHashMap<A, S>> for HashMap<A::Key, A::Value, S>
+where
+    A: MapArray,
+    A::Key: Hash + Eq,
+    S: BuildHasher,
+{
+    fn from(map: TinyHashMap<A, S>) -> Self {
+        map.into_hash_map()
+    }
+}
+
+impl<A, S> PartialEq for TinyHashMap<A, S>
+where
+    A: MapArray,
+    A::Key: Hash + Eq,
+    A::Value: PartialEq,
+    S: BuildHasher,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(key, value)| other.get(key) == Some(value))
+    }
+}
+
+impl<A, S> Eq for TinyHashMap<A, S>
+where
+    A: MapArray,
+    A::Key: Hash + Eq,
+    A::Value: Eq,
+    S: BuildHasher,
+{
+}
+
+impl<'a, A, S> IntoIterator for &'a TinyHashMap<A, S>
+where
+    A: MapArray,
+{
+    type Item = (&'a A::Key, &'a A::Value);
+    type IntoIter = Iter<'a, A::Key, A::Value>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, A, S> IntoIterator for &'a mut TinyHashMap<A, S>
+where
+    A: MapArray,
+{
+    type Item = (&'a A::Key, &'a mut A::Value);
+    type IntoIter = IterMut<'a, A::Key, A::Value>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<A, S> IntoIterator for TinyHashMap<A, S>
+where
+    A: MapArray,
+{
+    type Item = (A::Key, A::Value);
+    type IntoIter = IntoIter<A>;
+    fn into_iter(self) -> Self::IntoIter {
+        match self {
+            TinyHashMap::Stack(map) => IntoIter::Stack(map.into_iter()),
+            TinyHashMap::Heap(map) => IntoIter::Heap(map.into_iter()),
+        }
+    }
+}
+
+impl<A, S> FromIterator<(A::Key, A::Value)> for TinyHashMap<A, S>
+where
+    A: MapArray,
+    A::Key: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (A::Key, A::Value)>,
+    {
+        let iter = iter.into_iter();
+        let mut map = TinyHashMap::default();
+        if iter.size_hint().0 > array_hash_map::max_load(A::CAPACITY) {
+            map.spill();
+        }
+        map.extend(iter);
+        map
+    }
+}
+
+impl<A, S> Extend<(A::Key, A::Value)> for TinyHashMap<A, S>
+where
+    A: MapArray,
+    A::Key: Hash + Eq,
+    S: BuildHasher,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = (A::Key, A::Value)>,
+    {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+/// A consuming iterator over the key-value pairs in a TinyHashMap
+pub enum IntoIter<A>
+where
+    A: MapArray,
+{
+    #[doc(hidden)]
+    Stack(array_hash_map::IntoIter<A>),
+    #[doc(hidden)]
+    Heap(hash_map::IntoIter<A::Key, A::Value>),
+}
+
+impl<A> Iterator for IntoIter<A>
+where
+    A: MapArray,
+{
+    type Item = (A::Key, A::Value);
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            IntoIter::Stack(iter) => iter.next(),
+            IntoIter::Heap(iter) => iter.next(),
+        }
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            IntoIter::Stack(iter) => iter.size_hint(),
+            IntoIter::Heap(iter) => iter.size_hint(),
+        }
+    }
+}
+
+/// An iterator over references to the key-value pairs in a TinyHashMap
+pub enum Iter<'a, K, V> {
+    #[doc(hidden)]
+    Stack(array_hash_map::Iter<'a, K, V>),
+    #[doc(hidden)]
+    Heap(hash_map::Iter<'a, K, V>),
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Iter::Stack(iter) => iter.next(),
+            Iter::Heap(iter) => iter.next(),
+        }
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            Iter::Stack(iter) => iter.size_hint(),
+            Iter::Heap(iter) => iter.size_hint(),
+        }
+    }
+}
+
+/// An iterator over references to keys and mutable references to values in a TinyHashMap
+pub enum IterMut<'a, K, V> {
+    #[doc(hidden)]
+    Stack(array_hash_map::IterMut<'a, K, V>),
+    #[doc(hidden)]
+    Heap(hash_map::IterMut<'a, K, V>),
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            IterMut::Stack(iter) => iter.next(),
+            IterMut::Heap(iter) => iter.next(),
+        }
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            IterMut::Stack(iter) => iter.size_hint(),
+            IterMut::Heap(iter) => iter.size_hint(),
+        }
+    }
+}
+
+/// An iterator over references to the keys in a TinyHashMap
+pub enum Keys<'a, K, V> {
+    #[doc(hidden)]
+    Stack(array_hash_map::Keys<'a, K, V>),
+    #[doc(hidden)]
+    Heap(hash_map::Keys<'a, K, V>),
+}
+
+impl<'a, K, V> Iterator for Keys<'a, K, V> {
+    type Item = &'a K;
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Keys::Stack(iter) => iter.next(),
+            Keys::Heap(iter) => iter.next(),
+        }
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            Keys::Stack(iter) => iter.size_hint(),
+            Keys::Heap(iter) => iter.size_hint(),
+        }
+    }
+}
+
+/// An iterator over references to the values in a TinyHashMap
+pub enum Values<'a, K, V> {
+    #[doc(hidden)]
+    Stack(array_hash_map::Values<'a, K, V>),
+    #[doc(hidden)]
+    Heap(hash_map::Values<'a, K, V>),
+}
+
+impl<'a, K, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Values::Stack(iter) => iter.next(),
+            Values::Heap(iter) => iter.next(),
+        }
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            Values::Stack(iter) => iter.size_hint(),
+            Values::Heap(iter) => iter.size_hint(),
+        }
+    }
+}
+
+/// An iterator over mutable references to the values in a TinyHashMap
+pub enum ValuesMut<'a, K, V> {
+    #[doc(hidden)]
+    Stack(array_hash_map::ValuesMut<'a, K, V>),
+    #[doc(hidden)]
+    Heap(hash_map::ValuesMut<'a, K, V>),
+}
+
+impl<'a, K, V> Iterator for ValuesMut<'a, K, V> {
+    type Item = &'a mut V;
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            ValuesMut::Stack(iter) => iter.next(),
+            ValuesMut::Heap(iter) => iter.next(),
+        }
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            ValuesMut::Stack(iter) => iter.size_hint(),
+            ValuesMut::Heap(iter) => iter.size_hint(),
+        }
+    }
+}
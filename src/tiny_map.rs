@@ -1,439 +1,2380 @@
 //! A map that starts on the stack but can automatically move to the heap
 
-use core::{borrow::Borrow, fmt, iter::FromIterator, mem::swap, ops::Index};
+use core::{
+    borrow::Borrow,
+    cmp::Ordering,
+    convert::Infallible,
+    fmt,
+    iter::FromIterator,
+    marker::PhantomData,
+    mem::{size_of, swap, take},
+    ops::{
+        Bound::{self, Excluded, Included, Unbounded},
+        Deref, DerefMut, Index,
+    },
+};
+#[cfg(feature = "allocator_api")]
+use std::alloc::Allocator;
 use std::collections::{btree_map, BTreeMap};
 
-use crate::{ArrayMap, MapArray};
+use crate::{
+    array_map::VecMap,
+    compare::{Comparable, Compare, Natural, Search, TreeOrder},
+    diff::Diff,
+    heap_size::btree_nodes,
+    join::{Join, JoinItem, LeftJoin, OuterJoin},
+    sealed::Sealed,
+    ArrayMap, Inner, MapArray, MapStorage,
+};
 
 /**
 A map that starts on the stack but can automatically move to the heap
+
+Keys are ordered by the comparator `C`, which defaults to their `Ord` implementation.
+Once the entries no longer fit in the array, they move into the heap `H`, which defaults to a BTreeMap.
+A tree can only keep the order of comparators that implement [`TreeOrder`](../compare/trait.TreeOrder.html),
+so a TinyMap with any other comparator, such as one that carries state, moves into a
+[`VecMap`](../array_map/type.VecMap.html) instead. See [`MapHeap`](trait.MapHeap.html) and the
+[`compare`](../compare/index.html) module for details.
+
+A [`TinyVecMap`](../array_map/type.TinyVecMap.html) also moves into a sorted `Vec`, but keeps the whole
+API of an [`ArrayMap`](../array_map/struct.ArrayMap.html) on both sides of the move.
+
+Equality lookups such as [`get`](#method.get) and [`remove`](#method.remove) accept any
+[`Comparable`](../compare/trait.Comparable.html) query. Once the map has moved to a `BTreeMap`, a query
+that the keys cannot be borrowed as is found by scanning the tree in O(n). Lookups by position, such as
+[`rank`](#method.rank) and [`lower_bound`](#method.lower_bound), need borrowed queries on a tree.
+
+With the `allocator_api` feature, the heap can also be a `BTreeMap` in another allocator.
+See [`new_in`](#method.new_in).
+
+# Example
+
+```
+use std::cmp::Ordering;
+use tinymap::{compare::{from_fn, FromFn}, *};
+
+type ByLen = FromFn<fn(&&str, &&str) -> Ordering>;
+
+let by_len: ByLen = from_fn(|a, b| a.len().cmp(&b.len()));
+let mut map = TinyMap::<[Inner<(&str, i32)>; 2], _, VecMap<_, _, _>>::with_comparator(by_len);
+map.insert("ccc", 3);
+map.insert("a", 1);
+map.insert("bb", 2);
+assert!(map.is_spilled());
+assert_eq!(map.keys().copied().collect::<Vec<_>>(), ["a", "bb", "ccc"]);
+assert_eq!(map.get(&"zz"), Some(&2));
+```
 */
-pub enum TinyMap<A>
+pub enum TinyMap<A, C = Natural, H = BTreeMap<<A as MapStorage>::Key, <A as MapStorage>::Value>>
 where
     A: MapArray,
 {
     /// An map with items on the stack
-    Stack(ArrayMap<A>),
+    Stack(ArrayMap<A, C>),
     /// A map with items on the heap
-    Heap(BTreeMap<A::Key, A::Value>),
+    Heap(H),
+}
+
+/**
+The storage that a TinyMap moves its entries into once they no longer fit in its array
+
+This is implemented for `BTreeMap`, which is the default, and for [`VecMap`](../array_map/type.VecMap.html).
+With the `allocator_api` feature, it is implemented for trees in any allocator that implements `Default`,
+which is where a map that moves from the stack creates its tree.
+A tree orders its keys with their `Ord` implementation and has no room for a comparator, so it only
+works with comparators that implement [`TreeOrder`](../compare/trait.TreeOrder.html). A VecMap keeps
+the map's comparator alongside its entries, so it works with any comparator that can be cloned.
+
+Lookups by position, such as [`TinyMap::get_index`](enum.TinyMap.html#method.get_index) and
+[`TinyMap::rank`](enum.TinyMap.html#method.rank), are O(n) in a tree and O(log n) or O(1) in a VecMap.
+Insertions and removals are O(log n) in a tree and O(n) in a VecMap.
+
+This trait is sealed and cannot be implemented outside of this crate.
+*/
+#[allow(clippy::len_without_is_empty)]
+pub trait MapHeap<C>: Sealed + Sized {
+    /// The type of the keys
+    type Key;
+    /// The type of the values
+    type Value;
+    #[doc(hidden)]
+    type IntoIter: Iterator<Item = (Self::Key, Self::Value)>;
+    #[doc(hidden)]
+    type IntoKeys: Iterator<Item = Self::Key>;
+    #[doc(hidden)]
+    type IntoValues: Iterator<Item = Self::Value>;
+    #[doc(hidden)]
+    type Entry<'a>
+    where
+        Self: 'a;
+    #[doc(hidden)]
+    fn from_stack<A>(map: &mut ArrayMap<A, C>) -> Self
+    where
+        A: MapArray<Key = Self::Key, Value = Self::Value>;
+    #[doc(hidden)]
+    fn comparator(&self) -> C;
+    #[doc(hidden)]
+    fn allocation_size(&self) -> usize;
+    #[doc(hidden)]
+    fn len(&self) -> usize;
+    #[doc(hidden)]
+    fn insert(&mut self, key: Self::Key, value: Self::Value) -> Option<Self::Value>;
+    #[doc(hidden)]
+    fn append(&mut self, other: &mut Self);
+    #[doc(hidden)]
+    fn iter(&self) -> Iter<'_, Self::Key, Self::Value, C>;
+    #[doc(hidden)]
+    fn iter_mut(&mut self) -> IterMut<'_, Self::Key, Self::Value, C>;
+    #[doc(hidden)]
+    fn keys(&self) -> Keys<'_, Self::Key, Self::Value, C>;
+    #[doc(hidden)]
+    fn values(&self) -> Values<'_, Self::Key, Self::Value, C>;
+    #[doc(hidden)]
+    fn values_mut(&mut self) -> ValuesMut<'_, Self::Key, Self::Value, C>;
+    #[doc(hidden)]
+    fn into_entries(self) -> Self::IntoIter;
+    #[doc(hidden)]
+    fn into_keys(self) -> Self::IntoKeys;
+    #[doc(hidden)]
+    fn into_values(self) -> Self::IntoValues;
+    #[doc(hidden)]
+    fn get_index(&self, index: usize) -> Option<(&Self::Key, &Self::Value)>;
+    #[doc(hidden)]
+    fn get_index_mut(&mut self, index: usize) -> Option<(&Self::Key, &mut Self::Value)>;
+    #[doc(hidden)]
+    fn partition_point<P>(&self, pred: P) -> usize
+    where
+        P: FnMut(&Self::Key, &Self::Value) -> bool;
+    #[doc(hidden)]
+    fn remove_index(&mut self, index: usize) -> Option<(Self::Key, Self::Value)>
+    where
+        Self::Key: Clone;
+    #[doc(hidden)]
+    fn entry(&mut self, key: Self::Key) -> Self::Entry<'_>;
+    #[doc(hidden)]
+    fn entry_key<'a, 'b>(entry: &'b Self::Entry<'a>) -> &'b Self::Key;
+    #[doc(hidden)]
+    fn entry_is_vacant(entry: &Self::Entry<'_>) -> bool;
+    #[doc(hidden)]
+    fn entry_or_insert_with<'a, F>(entry: Self::Entry<'a>, default: F) -> &'a mut Self::Value
+    where
+        F: FnOnce() -> Self::Value;
+    #[doc(hidden)]
+    fn entry_and_modify<'a, F>(entry: Self::Entry<'a>, f: F) -> Self::Entry<'a>
+    where
+        F: FnOnce(&mut Self::Value);
+}
+
+/**
+A [`MapHeap`](trait.MapHeap.html) that can find the key that is equal to a query of type `Q`
+
+A `BTreeMap` accepts every [`Comparable`](../compare/trait.Comparable.html) query. It finds queries that
+its keys can be borrowed as in O(log n) and scans its entries in O(n) for any other query. A
+[`VecMap`](../array_map/type.VecMap.html) accepts every query that the map's comparator can compare with its keys.
+
+This trait is sealed and cannot be implemented outside of this crate.
+*/
+pub trait MapGet<Q: ?Sized, C>: MapHeap<C> {
+    #[doc(hidden)]
+    fn get_key_value(&self, key: &Q) -> Option<(&Self::Key, &Self::Value)>;
+    #[doc(hidden)]
+    fn get_key_value_mut(&mut self, key: &Q) -> Option<(&Self::Key, &mut Self::Value)>;
+    #[doc(hidden)]
+    fn remove_entry(&mut self, key: &Q) -> Option<(Self::Key, Self::Value)>;
+}
+
+/**
+A [`MapGet`](trait.MapGet.html) that can also look up keys by their position relative to a query of type `Q`
+
+A `BTreeMap` accepts the queries that its keys can be borrowed as. A
+[`VecMap`](../array_map/type.VecMap.html) accepts every query that the map's comparator can compare with its keys.
+
+This trait is sealed and cannot be implemented outside of this crate.
+*/
+pub trait MapLookup<Q: ?Sized, C>: MapGet<Q, C> {
+    /// Returns the first entry in the comparator's order whose key is above the bound
+    #[doc(hidden)]
+    fn first_above(&self, bound: Bound<&Q>) -> Option<(&Self::Key, &Self::Value)>;
+    #[doc(hidden)]
+    fn first_above_mut(&mut self, bound: Bound<&Q>) -> Option<(&Self::Key, &mut Self::Value)>;
+    /// Returns the last entry in the comparator's order whose key is below the bound
+    #[doc(hidden)]
+    fn last_below(&self, bound: Bound<&Q>) -> Option<(&Self::Key, &Self::Value)>;
+    /// Counts the keys that are below the bound in the comparator's order
+    #[doc(hidden)]
+    fn count_below(&self, bound: Bound<&Q>) -> usize;
+    /// Splits off the entries whose keys are not below the given key in the comparator's order
+    #[doc(hidden)]
+    fn split_off(&mut self, key: &Q) -> Self;
+    #[doc(hidden)]
+    fn get_many_mut<const N: usize>(&mut self, keys: [&Q; N]) -> Option<[&mut Self::Value; N]>;
+}
+
+/// Builds a tree from entries that are already sorted
+#[cfg(not(feature = "allocator_api"))]
+fn sorted_tree<K: Ord, V>(entries: Vec<(K, V)>) -> BTreeMap<K, V> {
+    // The tree is built in linear time without searching
+    entries.into_iter().collect()
+}
+
+/// Builds a tree in the default allocator from entries that are already sorted
+#[cfg(feature = "allocator_api")]
+fn sorted_tree<K, V, Al>(entries: Vec<(K, V)>) -> BTreeMap<K, V, Al>
+where
+    K: Ord,
+    Al: Allocator + Clone + Default,
+{
+    let mut tree = BTreeMap::new_in(Al::default());
+    tree.extend(entries);
+    tree
+}
+
+/// Merges two trees in a single pass, keeping the entries from `theirs` for keys in both
+#[cfg(not(feature = "allocator_api"))]
+fn append_trees<K: Ord, V>(ours: &mut BTreeMap<K, V>, theirs: &mut BTreeMap<K, V>) {
+    let mut ours_iter = take(ours).into_iter().peekable();
+    let mut theirs_iter = take(theirs).into_iter().peekable();
+    // Both sides are sorted, so the tree is built in linear time without searching
+    *ours = core::iter::from_fn(|| match (ours_iter.peek(), theirs_iter.peek()) {
+        (Some((a, _)), Some((b, _))) => match a.cmp(b) {
+            Ordering::Less => ours_iter.next(),
+            Ordering::Greater => theirs_iter.next(),
+            Ordering::Equal => {
+                ours_iter.next();
+                theirs_iter.next()
+            }
+        },
+        (Some(_), None) => ours_iter.next(),
+        (None, _) => theirs_iter.next(),
+    })
+    .collect();
+}
+
+/// Moves the entries of `theirs` into `ours`, replacing the entries for keys in both
+///
+/// Trees in an allocator cannot be collected into, so the entries are moved one at a time.
+#[cfg(feature = "allocator_api")]
+fn append_trees<K, V, Al>(ours: &mut BTreeMap<K, V, Al>, theirs: &mut BTreeMap<K, V, Al>)
+where
+    K: Ord,
+    Al: Allocator + Clone,
+{
+    while let Some((key, value)) = theirs.pop_first() {
+        ours.remove(&key);
+        ours.insert(key, value);
+    }
+}
+
+/// Finds the entry of a tree whose key is equal to a query
+struct GetKeyValue<'a, T>(&'a T);
+
+/// Finds the entry of a tree whose key is equal to a query, with a mutable reference to its value
+struct GetKeyValueMut<'a, T>(&'a mut T);
+
+/// Removes the entry of a tree whose key is equal to a query
+struct RemoveEntry<'a, T>(&'a mut T);
+
+/// Implements the heap traits for `BTreeMap`, in any allocator with the `allocator_api` feature
+macro_rules! btree_map_heap {
+    ($($al:ident)?) => {
+        impl<K, V $(, $al: Allocator + Clone)?> Sealed for BTreeMap<K, V $(, $al)?> {}
+
+        impl<K, V, C $(, $al: Allocator + Clone + Default)?> MapHeap<C> for BTreeMap<K, V $(, $al)?>
+        where
+            K: Ord,
+            C: TreeOrder<K>,
+        {
+            type Key = K;
+            type Value = V;
+            type IntoIter = Ordered<btree_map::IntoIter<K, V $(, $al)?>>;
+            type IntoKeys = Ordered<btree_map::IntoKeys<K, V $(, $al)?>>;
+            type IntoValues = Ordered<btree_map::IntoValues<K, V $(, $al)?>>;
+            type Entry<'a>
+                = btree_map::Entry<'a, K, V $(, $al)?>
+            where
+                Self: 'a;
+
+            fn from_stack<A>(map: &mut ArrayMap<A, C>) -> Self
+            where
+                A: MapArray<Key = K, Value = V>,
+            {
+                let mut entries = Vec::from(take(map));
+                if C::REVERSED {
+                    entries.reverse();
+                }
+                sorted_tree(entries)
+            }
+            fn comparator(&self) -> C {
+                C::default()
+            }
+            fn allocation_size(&self) -> usize {
+                btree_nodes::<K, V>(BTreeMap::len(self))
+            }
+            fn len(&self) -> usize {
+                BTreeMap::len(self)
+            }
+            fn insert(&mut self, key: K, value: V) -> Option<V> {
+                BTreeMap::insert(self, key, value)
+            }
+            fn append(&mut self, other: &mut Self) {
+                append_trees(self, other);
+            }
+            fn iter(&self) -> Iter<'_, K, V, C> {
+                Iter::Heap(Ordered::new(BTreeMap::iter(self), C::REVERSED), PhantomData)
+            }
+            fn iter_mut(&mut self) -> IterMut<'_, K, V, C> {
+                IterMut::Heap(
+                    Ordered::new(BTreeMap::iter_mut(self), C::REVERSED),
+                    PhantomData,
+                )
+            }
+            fn keys(&self) -> Keys<'_, K, V, C> {
+                Keys::Heap(Ordered::new(BTreeMap::keys(self), C::REVERSED), PhantomData)
+            }
+            fn values(&self) -> Values<'_, K, V, C> {
+                Values::Heap(
+                    Ordered::new(BTreeMap::values(self), C::REVERSED),
+                    PhantomData,
+                )
+            }
+            fn values_mut(&mut self) -> ValuesMut<'_, K, V, C> {
+                ValuesMut::Heap(
+                    Ordered::new(BTreeMap::values_mut(self), C::REVERSED),
+                    PhantomData,
+                )
+            }
+            fn into_entries(self) -> Self::IntoIter {
+                Ordered::new(IntoIterator::into_iter(self), C::REVERSED)
+            }
+            fn into_keys(self) -> Self::IntoKeys {
+                Ordered::new(BTreeMap::into_keys(self), C::REVERSED)
+            }
+            fn into_values(self) -> Self::IntoValues {
+                Ordered::new(BTreeMap::into_values(self), C::REVERSED)
+            }
+            fn get_index(&self, index: usize) -> Option<(&K, &V)> {
+                Ordered::new(BTreeMap::iter(self), C::REVERSED).nth(index)
+            }
+            fn get_index_mut(&mut self, index: usize) -> Option<(&K, &mut V)> {
+                Ordered::new(BTreeMap::iter_mut(self), C::REVERSED).nth(index)
+            }
+            fn partition_point<P>(&self, mut pred: P) -> usize
+            where
+                P: FnMut(&K, &V) -> bool,
+            {
+                Ordered::new(BTreeMap::iter(self), C::REVERSED)
+                    .take_while(|(k, v)| pred(k, v))
+                    .count()
+            }
+            fn remove_index(&mut self, index: usize) -> Option<(K, V)>
+            where
+                K: Clone,
+            {
+                let key = Ordered::new(BTreeMap::keys(self), C::REVERSED)
+                    .nth(index)?
+                    .clone();
+                BTreeMap::remove_entry(self, &key)
+            }
+            fn entry(&mut self, key: K) -> Self::Entry<'_> {
+                BTreeMap::entry(self, key)
+            }
+            fn entry_key<'a, 'b>(entry: &'b Self::Entry<'a>) -> &'b K {
+                entry.key()
+            }
+            fn entry_is_vacant(entry: &Self::Entry<'_>) -> bool {
+                matches!(entry, btree_map::Entry::Vacant(_))
+            }
+            fn entry_or_insert_with<'a, F>(entry: Self::Entry<'a>, default: F) -> &'a mut V
+            where
+                F: FnOnce() -> V,
+            {
+                entry.or_insert_with(default)
+            }
+            fn entry_and_modify<'a, F>(entry: Self::Entry<'a>, f: F) -> Self::Entry<'a>
+            where
+                F: FnOnce(&mut V),
+            {
+                entry.and_modify(f)
+            }
+        }
+
+        impl<'a, K, V $(, $al: Allocator + Clone)?> Search<K> for GetKeyValue<'a, BTreeMap<K, V $(, $al)?>>
+        where
+            K: Ord,
+        {
+            type Output = Option<(&'a K, &'a V)>;
+            fn find<T>(self, query: &T) -> Self::Output
+            where
+                T: Ord + ?Sized,
+                K: Borrow<T>,
+            {
+                BTreeMap::get_key_value(self.0, query)
+            }
+            fn scan<Q>(self, query: &Q) -> Self::Output
+            where
+                Q: Comparable<K> + ?Sized,
+            {
+                // The query agrees with the keys' order, so the scan stops at the first key that is not below it
+                BTreeMap::iter(self.0)
+                    .find(|(key, _)| query.compare(key) != Ordering::Greater)
+                    .filter(|(key, _)| query.compare(key) == Ordering::Equal)
+            }
+        }
+
+        impl<'a, K, V $(, $al: Allocator + Clone)?> Search<K> for GetKeyValueMut<'a, BTreeMap<K, V $(, $al)?>>
+        where
+            K: Ord,
+        {
+            type Output = Option<(&'a K, &'a mut V)>;
+            fn find<T>(self, query: &T) -> Self::Output
+            where
+                T: Ord + ?Sized,
+                K: Borrow<T>,
+            {
+                self.0
+                    .range_mut::<T, _>((Included(query), Included(query)))
+                    .next()
+            }
+            fn scan<Q>(self, query: &Q) -> Self::Output
+            where
+                Q: Comparable<K> + ?Sized,
+            {
+                BTreeMap::iter_mut(self.0)
+                    .find(|(key, _)| query.compare(key) != Ordering::Greater)
+                    .filter(|(key, _)| query.compare(key) == Ordering::Equal)
+            }
+        }
+
+        impl<'a, K, V $(, $al: Allocator + Clone)?> Search<K> for RemoveEntry<'a, BTreeMap<K, V $(, $al)?>>
+        where
+            K: Ord,
+        {
+            type Output = Option<(K, V)>;
+            fn find<T>(self, query: &T) -> Self::Output
+            where
+                T: Ord + ?Sized,
+                K: Borrow<T>,
+            {
+                BTreeMap::remove_entry(self.0, query)
+            }
+            fn scan<Q>(self, query: &Q) -> Self::Output
+            where
+                Q: Comparable<K> + ?Sized,
+            {
+                // A tree can only remove the keys it is given, so the entries below the query are
+                // taken off its front until the query is reached and put back afterwards
+                let mut below = Vec::new();
+                let mut found = None;
+                while let Some((key, value)) = self.0.pop_first() {
+                    match query.compare(&key) {
+                        Ordering::Greater => below.push((key, value)),
+                        Ordering::Equal => {
+                            found = Some((key, value));
+                            break;
+                        }
+                        Ordering::Less => {
+                            below.push((key, value));
+                            break;
+                        }
+                    }
+                }
+                self.0.extend(below);
+                found
+            }
+        }
+
+        impl<K, V, Q, C $(, $al: Allocator + Clone + Default)?> MapGet<Q, C> for BTreeMap<K, V $(, $al)?>
+        where
+            K: Ord,
+            Q: Comparable<K> + ?Sized,
+            C: TreeOrder<K>,
+        {
+            fn get_key_value(&self, key: &Q) -> Option<(&K, &V)> {
+                key.search(GetKeyValue(self))
+            }
+            fn get_key_value_mut(&mut self, key: &Q) -> Option<(&K, &mut V)> {
+                key.search(GetKeyValueMut(self))
+            }
+            fn remove_entry(&mut self, key: &Q) -> Option<(K, V)> {
+                key.search(RemoveEntry(self))
+            }
+        }
+
+        impl<K, V, Q, C $(, $al: Allocator + Clone + Default)?> MapLookup<Q, C> for BTreeMap<K, V $(, $al)?>
+        where
+            K: Borrow<Q> + Ord,
+            Q: Ord + ?Sized,
+            C: TreeOrder<K>,
+        {
+            fn first_above(&self, bound: Bound<&Q>) -> Option<(&K, &V)> {
+                if C::REVERSED {
+                    self.range::<Q, _>((Unbounded, bound)).next_back()
+                } else {
+                    self.range::<Q, _>((bound, Unbounded)).next()
+                }
+            }
+            fn first_above_mut(&mut self, bound: Bound<&Q>) -> Option<(&K, &mut V)> {
+                if C::REVERSED {
+                    self.range_mut::<Q, _>((Unbounded, bound)).next_back()
+                } else {
+                    self.range_mut::<Q, _>((bound, Unbounded)).next()
+                }
+            }
+            fn last_below(&self, bound: Bound<&Q>) -> Option<(&K, &V)> {
+                if C::REVERSED {
+                    self.range::<Q, _>((bound, Unbounded)).next()
+                } else {
+                    self.range::<Q, _>((Unbounded, bound)).next_back()
+                }
+            }
+            fn count_below(&self, bound: Bound<&Q>) -> usize {
+                if C::REVERSED {
+                    self.range::<Q, _>((bound, Unbounded)).count()
+                } else {
+                    self.range::<Q, _>((Unbounded, bound)).count()
+                }
+            }
+            fn split_off(&mut self, key: &Q) -> Self {
+                let mut other = BTreeMap::split_off(self, key);
+                if C::REVERSED {
+                    // In a reversed order, the given key and the ones below it are split off
+                    if let Some((key, value)) = other.remove_entry(key) {
+                        BTreeMap::insert(self, key, value);
+                    }
+                    swap(self, &mut other);
+                }
+                other
+            }
+            fn get_many_mut<const N: usize>(&mut self, keys: [&Q; N]) -> Option<[&mut V; N]> {
+                // Visit the keys in the tree's order so they can be matched in one walk
+                let mut order: [usize; N] = core::array::from_fn(|i| i);
+                order.sort_unstable_by(|&a, &b| keys[a].cmp(keys[b]));
+                if order
+                    .windows(2)
+                    .any(|pair| keys[pair[0]].cmp(keys[pair[1]]) == Ordering::Equal)
+                {
+                    return None;
+                }
+                let mut values = [(); N].map(|_| None);
+                let mut wanted = order.iter().peekable();
+                for (key, value) in BTreeMap::iter_mut(self) {
+                    let i = match wanted.peek() {
+                        Some(&&i) => i,
+                        None => break,
+                    };
+                    match keys[i].cmp(key.borrow()) {
+                        Ordering::Less => return None,
+                        Ordering::Equal => {
+                            values[i] = Some(value);
+                            wanted.next();
+                        }
+                        Ordering::Greater => {}
+                    }
+                }
+                if wanted.next().is_some() {
+                    return None;
+                }
+                Some(values.map(Option::unwrap))
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "allocator_api"))]
+btree_map_heap!();
+#[cfg(feature = "allocator_api")]
+btree_map_heap!(Al);
+
+impl<K, V, C> Sealed for VecMap<K, V, C> {}
+
+impl<K, V, C> MapHeap<C> for VecMap<K, V, C>
+where
+    C: Compare<K> + Clone,
+{
+    type Key = K;
+    type Value = V;
+    type IntoIter = crate::array_map::IntoIter<K, V>;
+    type IntoKeys = crate::array_map::IntoKeys<Vec<Inner<(K, V)>>>;
+    type IntoValues = crate::array_map::IntoValues<Vec<Inner<(K, V)>>>;
+    type Entry<'a>
+        = crate::array_map::Entry<'a, Vec<Inner<(K, V)>>, C>
+    where
+        Self: 'a;
+
+    fn from_stack<A>(map: &mut ArrayMap<A, C>) -> Self
+    where
+        A: MapArray<Key = K, Value = V>,
+    {
+        VecMap::take_from(map)
+    }
+    fn comparator(&self) -> C {
+        ArrayMap::comparator(self).clone()
+    }
+    fn allocation_size(&self) -> usize {
+        MapStorage::heap_capacity(self.storage()) * size_of::<Inner<(K, V)>>()
+    }
+    fn len(&self) -> usize {
+        ArrayMap::len(self)
+    }
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        ArrayMap::insert(self, key, value)
+    }
+    fn append(&mut self, other: &mut Self) {
+        ArrayMap::append(self, other)
+    }
+    fn iter(&self) -> Iter<'_, K, V, C> {
+        Iter::Stack(ArrayMap::iter(self))
+    }
+    fn iter_mut(&mut self) -> IterMut<'_, K, V, C> {
+        IterMut::Stack(ArrayMap::iter_mut(self))
+    }
+    fn keys(&self) -> Keys<'_, K, V, C> {
+        Keys::Stack(ArrayMap::keys(self))
+    }
+    fn values(&self) -> Values<'_, K, V, C> {
+        Values::Stack(ArrayMap::values(self))
+    }
+    fn values_mut(&mut self) -> ValuesMut<'_, K, V, C> {
+        ValuesMut::Stack(ArrayMap::values_mut(self))
+    }
+    fn into_entries(self) -> Self::IntoIter {
+        IntoIterator::into_iter(self)
+    }
+    fn into_keys(self) -> Self::IntoKeys {
+        ArrayMap::into_keys(self)
+    }
+    fn into_values(self) -> Self::IntoValues {
+        ArrayMap::into_values(self)
+    }
+    fn get_index(&self, index: usize) -> Option<(&K, &V)> {
+        ArrayMap::get_index(self, index)
+    }
+    fn get_index_mut(&mut self, index: usize) -> Option<(&K, &mut V)> {
+        ArrayMap::get_index_mut(self, index)
+    }
+    fn partition_point<P>(&self, pred: P) -> usize
+    where
+        P: FnMut(&K, &V) -> bool,
+    {
+        ArrayMap::partition_point(self, pred)
+    }
+    fn remove_index(&mut self, index: usize) -> Option<(K, V)>
+    where
+        K: Clone,
+    {
+        ArrayMap::remove_index(self, index)
+    }
+    fn entry(&mut self, key: K) -> Self::Entry<'_> {
+        ArrayMap::entry(self, key)
+    }
+    fn entry_key<'a, 'b>(entry: &'b Self::Entry<'a>) -> &'b K {
+        entry.key()
+    }
+    fn entry_is_vacant(entry: &Self::Entry<'_>) -> bool {
+        matches!(entry, crate::array_map::Entry::Vacant(_))
+    }
+    fn entry_or_insert_with<'a, F>(entry: Self::Entry<'a>, default: F) -> &'a mut V
+    where
+        F: FnOnce() -> V,
+    {
+        entry.or_insert_with(default)
+    }
+    fn entry_and_modify<'a, F>(entry: Self::Entry<'a>, f: F) -> Self::Entry<'a>
+    where
+        F: FnOnce(&mut V),
+    {
+        entry.and_modify(f)
+    }
+}
+
+impl<K, V, Q, C> MapGet<Q, C> for VecMap<K, V, C>
+where
+    Q: ?Sized,
+    C: Compare<K> + Compare<Q, K> + Clone,
+{
+    fn get_key_value(&self, key: &Q) -> Option<(&K, &V)> {
+        ArrayMap::get_key_value(self, key)
+    }
+    fn get_key_value_mut(&mut self, key: &Q) -> Option<(&K, &mut V)> {
+        let index = self.index_of(key)?;
+        ArrayMap::get_index_mut(self, index)
+    }
+    fn remove_entry(&mut self, key: &Q) -> Option<(K, V)> {
+        ArrayMap::remove_entry(self, key)
+    }
+}
+
+impl<K, V, Q, C> MapLookup<Q, C> for VecMap<K, V, C>
+where
+    Q: ?Sized,
+    C: Compare<K> + Compare<Q, K> + Clone,
+{
+    fn first_above(&self, bound: Bound<&Q>) -> Option<(&K, &V)> {
+        self.lower_bound(bound).peek_next()
+    }
+    fn first_above_mut(&mut self, bound: Bound<&Q>) -> Option<(&K, &mut V)> {
+        let index = self.lower_bound(bound).index;
+        ArrayMap::get_index_mut(self, index)
+    }
+    fn last_below(&self, bound: Bound<&Q>) -> Option<(&K, &V)> {
+        self.upper_bound(bound).peek_prev()
+    }
+    fn count_below(&self, bound: Bound<&Q>) -> usize {
+        self.upper_bound(bound).index
+    }
+    fn split_off(&mut self, key: &Q) -> Self {
+        ArrayMap::split_off(self, key)
+    }
+    fn get_many_mut<const N: usize>(&mut self, keys: [&Q; N]) -> Option<[&mut V; N]> {
+        ArrayMap::get_many_mut(self, keys)
+    }
 }
 
-impl<A> Default for TinyMap<A>
+impl<A, C, H> Default for TinyMap<A, C, H>
 where
     A: MapArray,
+    C: Default,
 {
     fn default() -> Self {
         TinyMap::Stack(ArrayMap::default())
     }
 }
 
-impl<A> Clone for TinyMap<A>
+impl<A, C, H> Clone for TinyMap<A, C, H>
 where
     A: MapArray,
     A::Key: Clone,
     A::Value: Clone,
+    C: Clone,
+    H: Clone,
 {
     fn clone(&self) -> Self {
         match self {
-            TinyMap::Stack(map) => TinyMap::Stack(map.clone()),
-            TinyMap::Heap(map) => TinyMap::Heap(map.clone()),
+            TinyMap::Stack(map) => TinyMap::Stack(map.clone()),
+            TinyMap::Heap(map) => TinyMap::Heap(map.clone()),
+        }
+    }
+}
+
+impl<A> TinyMap<A>
+where
+    A: MapArray,
+{
+    /**
+    Creates a new empty TinyMap
+
+    # Example
+    ```
+    use tinymap::*;
+
+    let mut map = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
+
+    // entries can now be inserted into the empty map
+    map.insert(1, "a");
+    ```
+    */
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<A, C, H> TinyMap<A, C, H>
+where
+    A: MapArray,
+{
+    /**
+    Creates a new empty TinyMap that orders its keys with the given comparator
+
+    A comparator that implements [`TreeOrder`](../compare/trait.TreeOrder.html) can be used with the
+    default heap. Any other comparator needs a [`VecMap`](../array_map/type.VecMap.html) heap.
+
+    # Example
+    ```
+    use tinymap::{compare::{from_fn, FromFn}, *};
+
+    let by_abs = from_fn(|a: &i32, b: &i32| a.abs().cmp(&b.abs()));
+    let mut map = TinyMap::<[Inner<(i32, &str)>; 2], _, VecMap<_, _, _>>::with_comparator(by_abs);
+    map.insert(-3, "c");
+    map.insert(2, "b");
+    map.insert(-1, "a");
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [-1, 2, -3]);
+    ```
+    */
+    pub fn with_comparator(cmp: C) -> Self {
+        TinyMap::Stack(ArrayMap::with_comparator(cmp))
+    }
+    /**
+    Creates a new empty TinyMap that starts on the heap
+
+    This skips moving the entries from the stack to the heap when the map is expected to grow beyond its capacity.
+
+    # Example
+    ```
+    use tinymap::*;
+
+    let mut map = TinyMap::<[Inner<(i32, &str)>; 2]>::with_heap();
+    assert!(map.is_spilled());
+    map.insert(1, "a");
+    assert!(map.is_spilled());
+    ```
+    */
+    pub fn with_heap() -> Self
+    where
+        H: Default,
+    {
+        TinyMap::Heap(H::default())
+    }
+}
+
+impl<A, C, H> TinyMap<A, C, H>
+where
+    A: MapArray,
+    H: MapHeap<C, Key = A::Key, Value = A::Value>,
+{
+    /**
+    Clears the map, removing all elements
+
+    # Example
+    ```
+    use tinymap::*;
+
+    let mut a = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
+    a.insert(1, "a");
+    a.clear();
+    assert!(a.is_empty());
+    ```
+    */
+    pub fn clear(&mut self)
+    where
+        C: Clone,
+    {
+        *self = TinyMap::Stack(ArrayMap::with_comparator(self.comparator()));
+    }
+    /**
+    Returns the number of elements in the map
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut a = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
+    assert_eq!(a.len(), 0);
+    a.insert(1, "a");
+    assert_eq!(a.len(), 1);
+    ```
+    */
+    pub fn len(&self) -> usize {
+        match self {
+            TinyMap::Stack(map) => map.len(),
+            TinyMap::Heap(map) => map.len(),
+        }
+    }
+    /// Returns a clone of the map's comparator, wherever its entries are
+    fn comparator(&self) -> C
+    where
+        C: Clone,
+    {
+        match self {
+            TinyMap::Stack(map) => map.comparator().clone(),
+            TinyMap::Heap(map) => map.comparator(),
+        }
+    }
+    /**
+    Returns `true` if the map contains no elements
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut a = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
+    assert!(a.is_empty());
+    a.insert(1, "a");
+    assert!(!a.is_empty());
+    ```
+    */
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /**
+    Returns `true` if the map has moved to the heap
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
+    map.insert(1, "a");
+    assert!(!map.is_spilled());
+    map.extend(vec![(1, "a"), (2, "b"), (3, "c")]);
+    assert!(map.is_spilled());
+    ```
+    */
+    pub fn is_spilled(&self) -> bool {
+        matches!(self, TinyMap::Heap(_))
+    }
+    /**
+    Returns the maximum number of elements the map can contain on the stack
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut a = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
+    assert_eq!(2, a.capacity());
+    ```
+    */
+    pub fn capacity(&self) -> usize {
+        A::CAPACITY
+    }
+    /**
+    Gets an iterator over the entries of the map, sorted by key
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
+    map.insert(3, "c");
+    map.insert(2, "b");
+    map.insert(1, "a");
+
+    for (key, value) in map.iter() {
+        println!("{}: {}", key, value);
+    }
+
+    let (first_key, first_value) = map.iter().next().unwrap();
+    assert_eq!((*first_key, *first_value), (1, "a"));
+    ```
+    */
+    pub fn iter(&self) -> Iter<'_, A::Key, A::Value, C> {
+        match self {
+            TinyMap::Stack(map) => Iter::Stack(map.iter()),
+            TinyMap::Heap(map) => map.iter(),
+        }
+    }
+    /**
+    Creates a consuming iterator over the keys of the map, sorted
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut a = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
+    a.insert(3, "c");
+    a.insert(2, "b");
+    a.insert(1, "a");
+
+    let keys: Vec<i32> = a.into_keys().collect();
+    assert_eq!(keys, [1, 2, 3]);
+    ```
+    */
+    pub fn into_keys(self) -> IntoKeys<A, C, H> {
+        match self {
+            TinyMap::Stack(map) => IntoKeys::Stack(map.into_keys()),
+            TinyMap::Heap(map) => IntoKeys::Heap(map.into_keys()),
+        }
+    }
+    /**
+    Creates a consuming iterator over the values of the map, sorted by key
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut a = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
+    a.insert(1, "hello");
+    a.insert(2, "goodbye");
+
+    let values: Vec<&str> = a.into_values().collect();
+    assert_eq!(values, ["hello", "goodbye"]);
+    ```
+    */
+    pub fn into_values(self) -> IntoValues<A, C, H> {
+        match self {
+            TinyMap::Stack(map) => IntoValues::Stack(map.into_values()),
+            TinyMap::Heap(map) => IntoValues::Heap(map.into_values()),
+        }
+    }
+    /**
+    Gets the map's entries as a slice of key-value pairs sorted by key, if the map is on the stack
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
+    map.insert(2, "b");
+    map.insert(1, "a");
+    assert_eq!(map.as_stack_slice(), Some(&[(1, "a"), (2, "b")][..]));
+
+    map.insert(3, "c");
+    assert_eq!(map.as_stack_slice(), None);
+    ```
+    */
+    pub fn as_stack_slice(&self) -> Option<&[(A::Key, A::Value)]> {
+        match self {
+            TinyMap::Stack(map) => Some(map.as_slice()),
+            TinyMap::Heap(_) => None,
+        }
+    }
+    /**
+    Returns a reference to the key-value pair at the given position in the map's sort order
+
+    This is O(1) while the map is on the stack or in a VecMap and O(n) once it has moved to a BTreeMap.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
+    map.insert(3, "c");
+    map.insert(1, "a");
+    map.insert(2, "b");
+    assert_eq!(map.get_index(0), Some((&1, &"a")));
+    assert_eq!(map.get_index(2), Some((&3, &"c")));
+    assert_eq!(map.get_index(3), None);
+    ```
+    */
+    pub fn get_index(&self, index: usize) -> Option<(&A::Key, &A::Value)> {
+        match self {
+            TinyMap::Stack(map) => map.get_index(index),
+            TinyMap::Heap(map) => map.get_index(index),
+        }
+    }
+    /**
+    Returns a reference to the key and a mutable reference to the value at the given position
+    in the map's sort order
+
+    This is O(1) while the map is on the stack or in a VecMap and O(n) once it has moved to a BTreeMap.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
+    map.insert(2, "b");
+    map.insert(1, "a");
+    if let Some((_, value)) = map.get_index_mut(1) {
+        *value = "c";
+    }
+    assert_eq!(map[&2], "c");
+    ```
+    */
+    pub fn get_index_mut(&mut self, index: usize) -> Option<(&A::Key, &mut A::Value)> {
+        match self {
+            TinyMap::Stack(map) => map.get_index_mut(index),
+            TinyMap::Heap(map) => map.get_index_mut(index),
+        }
+    }
+    /**
+    Returns the position of the first entry for which the predicate returns `false`
+
+    The map is assumed to be partitioned by the predicate, meaning that it returns `true`
+    for every entry before some position and `false` for every entry from that position onward.
+
+    This is O(log n) while the map is on the stack or in a VecMap and O(n) once it has moved to a BTreeMap.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
+    map.insert(1, "a");
+    map.insert(4, "b");
+    map.insert(9, "c");
+    assert_eq!(map.partition_point(|key, _| key * key < 20), 2);
+    ```
+    */
+    pub fn partition_point<P>(&self, pred: P) -> usize
+    where
+        P: FnMut(&A::Key, &A::Value) -> bool,
+    {
+        match self {
+            TinyMap::Stack(map) => map.partition_point(pred),
+            TinyMap::Heap(map) => map.partition_point(pred),
+        }
+    }
+}
+
+#[cfg(feature = "allocator_api")]
+impl<A, C, Al> TinyMap<A, C, BTreeMap<A::Key, A::Value, Al>>
+where
+    A: MapArray,
+    Al: Allocator + Clone,
+{
+    /**
+    Creates a new empty TinyMap that starts on the heap in the given allocator
+
+    A map on the stack has nowhere to keep an allocator, so this map starts on the heap to use the given one.
+    A map that moves to the heap from the stack, for example after [`clear`](#method.clear),
+    creates its tree in `Al::default()`.
+
+    # Example
+    ```
+    #![feature(allocator_api, btreemap_alloc)]
+    use std::{alloc::System, collections::BTreeMap};
+    use tinymap::{compare::Natural, *};
+
+    let mut map =
+        TinyMap::<[Inner<(i32, &str)>; 2], Natural, BTreeMap<i32, &str, System>>::new_in(System);
+    assert!(map.is_spilled());
+    map.insert(1, "a");
+    assert_eq!(map[&1], "a");
+    ```
+    */
+    pub fn new_in(alloc: Al) -> Self {
+        TinyMap::Heap(BTreeMap::new_in(alloc))
+    }
+}
+
+impl<A, C> TinyMap<A, C>
+where
+    A: MapArray,
+{
+    /**
+    Converts the map into a `BTreeMap`
+
+    The tree is ordered by the keys' `Ord` implementation, whatever the map's comparator.
+    Once the map has moved to the heap, its tree is handed over as it is.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
+    map.insert(2, "b");
+    map.insert(1, "a");
+    map.insert(3, "c");
+
+    let tree = map.into_btree_map();
+    assert_eq!(tree.keys().copied().collect::<Vec<_>>(), [1, 2, 3]);
+    ```
+    */
+    pub fn into_btree_map(self) -> BTreeMap<A::Key, A::Value>
+    where
+        A::Key: Ord,
+    {
+        self.into()
+    }
+}
+
+impl<A, C, H> TinyMap<A, C, H>
+where
+    A: MapArray,
+    C: Compare<A::Key>,
+    H: MapHeap<C, Key = A::Key, Value = A::Value>,
+{
+    /**
+    Inserts a key-value pair into the map
+
+    If the map did not have this key present, None is returned.
+
+    If the map did have this key present, the key and value are updated, and the old value is returned.
+
+    # Panics
+
+    Panics if insertion would cause the map to excede its capacity.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
+    assert_eq!(map.insert(37, "a"), None);
+    assert_eq!(map.is_empty(), false);
+
+    map.insert(37, "b");
+    assert_eq!(map.insert(37, "c"), Some("b"));
+    assert_eq!(map[&37], "c");
+    ```
+    */
+    pub fn insert(&mut self, key: A::Key, value: A::Value) -> Option<A::Value> {
+        let res = match self {
+            TinyMap::Stack(map) => match map.try_insert(key, value) {
+                Ok(res) => res,
+                Err((key, value)) => self.heap_mut().insert(key, value),
+            },
+            TinyMap::Heap(map) => map.insert(key, value),
+        };
+        #[cfg(feature = "stats")]
+        if res.is_none() {
+            crate::stats::record_len::<Self>(A::CAPACITY, self.len());
+        }
+        res
+    }
+    /// Records the length that the map grew to from `len` in one operation
+    #[cfg(feature = "stats")]
+    fn record_growth(&self, len: usize) {
+        if self.len() > len {
+            crate::stats::record_len::<Self>(A::CAPACITY, self.len());
+        }
+    }
+    /// Moves the map to the heap if it is not there already
+    fn heap_mut(&mut self) -> &mut H {
+        if let TinyMap::Stack(map) = self {
+            #[cfg(feature = "stats")]
+            crate::stats::record_spill::<Self>(A::CAPACITY);
+            let heap = H::from_stack(map);
+            *self = TinyMap::Heap(heap);
+        }
+        match self {
+            TinyMap::Heap(map) => map,
+            TinyMap::Stack(_) => unreachable!(),
+        }
+    }
+    /**
+    Moves the map to the heap if it is not there already
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
+    map.insert(1, "a");
+    map.spill();
+    assert!(map.is_spilled());
+    assert_eq!(map.len(), 1);
+    ```
+    */
+    pub fn spill(&mut self) {
+        self.heap_mut();
+    }
+    /**
+    Moves the entries into a map with a different array type
+
+    A map on the stack stays there if its entries fit in the new array and moves to the heap otherwise.
+    A map on the heap stays there.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = TinyMap::<[Inner<(i32, &str)>; 4]>::new();
+    map.insert(1, "a");
+    map.insert(2, "b");
+
+    let map = map.resize_into::<[Inner<(i32, &str)>; 2]>();
+    assert!(matches!(map, TinyMap::Stack(_)));
+    let map = map.resize_into::<[Inner<(i32, &str)>; 1]>();
+    assert!(matches!(map, TinyMap::Heap(_)));
+    assert_eq!(map.len(), 2);
+
+    // An ArrayMap can be moved into any TinyMap, which spills only if it has to
+    let mut array = ArrayMap::<[Inner<(i32, &str)>; 4]>::new();
+    array.insert(1, "a");
+    array.insert(2, "b");
+    array.insert(3, "c");
+    let tiny = TinyMap::from(array).resize_into::<[Inner<(i32, &str)>; 2]>();
+    assert!(matches!(tiny, TinyMap::Heap(_)));
+    ```
+    */
+    pub fn resize_into<B>(self) -> TinyMap<B, C, H>
+    where
+        B: MapArray<Key = A::Key, Value = A::Value>,
+    {
+        match self {
+            TinyMap::Stack(map) => match map.try_convert() {
+                Ok(map) => TinyMap::Stack(map),
+                Err(mut map) => TinyMap::Heap(H::from_stack(&mut map)),
+            },
+            TinyMap::Heap(map) => TinyMap::Heap(map),
+        }
+    }
+    /**
+    Returns a reference to the value corresponding to the key
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
+    map.insert(1, "a");
+    assert_eq!(map.get(&1), Some(&"a"));
+    assert_eq!(map.get(&2), None);
+    ```
+    */
+    pub fn get<Q>(&self, key: &Q) -> Option<&A::Value>
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Key>,
+        H: MapGet<Q, C>,
+    {
+        match self {
+            TinyMap::Stack(map) => map.get(key),
+            TinyMap::Heap(map) => map.get_key_value(key).map(|(_, v)| v),
+        }
+    }
+    /**
+    Returns the stored key and a reference to the value corresponding to the key
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
+    map.insert(1, "a");
+    assert_eq!(map.get_key_value(&1), Some((&1, &"a")));
+    assert_eq!(map.get_key_value(&2), None);
+    ```
+    */
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&A::Key, &A::Value)>
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Key>,
+        H: MapGet<Q, C>,
+    {
+        match self {
+            TinyMap::Stack(map) => map.get_key_value(key),
+            TinyMap::Heap(map) => map.get_key_value(key),
+        }
+    }
+    /**
+    Returns true if the map contains a value for the specified key
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
+    map.insert(1, "a");
+    assert_eq!(map.contains_key(&1), true);
+    assert_eq!(map.contains_key(&2), false);
+    ```
+    */
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Key>,
+        H: MapGet<Q, C>,
+    {
+        match self {
+            TinyMap::Stack(map) => map.contains_key(key),
+            TinyMap::Heap(map) => map.get_key_value(key).is_some(),
+        }
+    }
+    /**
+    Returns a mutable reference to the value corresponding to the key
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
+    map.insert(1, "a");
+    if let Some(x) = map.get_mut(&1) {
+        *x = "b";
+    }
+    assert_eq!(map[&1], "b");
+    ```
+    */
+    pub fn get_mut<'a, Q>(&'a mut self, key: &Q) -> Option<&'a mut A::Value>
+    where
+        A::Key: 'a,
+        Q: ?Sized,
+        C: Compare<Q, A::Key>,
+        H: MapGet<Q, C>,
+    {
+        match self {
+            TinyMap::Stack(map) => map.get_mut(key),
+            TinyMap::Heap(map) => map.get_key_value_mut(key).map(|(_, v)| v),
+        }
+    }
+    /**
+    Returns mutable references to the values corresponding to several keys at once
+
+    Returns `None` if any of the keys is missing or if two of them refer to the same entry.
+
+    This takes O(log n) per key while the map is on the stack. Once the map has moved to the heap,
+    the keys are sorted and matched against the entries in a single walk over the map, which takes
+    O(N log N + n).
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut balances = TinyMap::<[Inner<(&str, u32)>; 2]>::new();
+    balances.insert("alice", 100);
+    balances.insert("bob", 20);
+    balances.insert("carol", 0);
+
+    if let Some([from, to]) = balances.get_many_mut([&"alice", &"carol"]) {
+        *from -= 30;
+        *to += 30;
+    }
+    assert_eq!(balances["alice"], 70);
+    assert_eq!(balances["carol"], 30);
+
+    assert!(balances.get_many_mut([&"bob", &"bob"]).is_none());
+    assert!(balances.get_many_mut([&"bob", &"dave"]).is_none());
+    ```
+    */
+    pub fn get_many_mut<Q, const N: usize>(&mut self, keys: [&Q; N]) -> Option<[&mut A::Value; N]>
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Key>,
+        H: MapLookup<Q, C>,
+    {
+        match self {
+            TinyMap::Stack(map) => map.get_many_mut(keys),
+            TinyMap::Heap(map) => map.get_many_mut(keys),
+        }
+    }
+    /**
+    Returns mutable references to the values corresponding to several keys at once,
+    without checking that the keys refer to different entries
+
+    Returns `None` if any of the keys is missing. Once the map has moved to the heap,
+    this is the same as [`get_many_mut`](#method.get_many_mut).
+
+    # Safety
+
+    Calling this with two keys that refer to the same entry is undefined behavior,
+    even if the returned references are not used.
+    */
+    pub unsafe fn get_many_unchecked_mut<Q, const N: usize>(
+        &mut self,
+        keys: [&Q; N],
+    ) -> Option<[&mut A::Value; N]>
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Key>,
+        H: MapLookup<Q, C>,
+    {
+        match self {
+            TinyMap::Stack(map) => map.get_many_unchecked_mut(keys),
+            TinyMap::Heap(_) => self.get_many_mut(keys),
+        }
+    }
+    /**
+    Gets a mutable iterator over the entries of the map, sorted by key
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = TinyMap::<[Inner<(&str, i32)>; 2]>::new();
+    map.insert("a", 1);
+    map.insert("b", 2);
+    map.insert("c", 3);
+
+    // add 10 to the value if the key isn't "a"
+    for (key, value) in map.iter_mut() {
+        if key != &"a" {
+            *value += 10;
+        }
+    }
+    ```
+    */
+    pub fn iter_mut(&mut self) -> IterMut<'_, A::Key, A::Value, C> {
+        match self {
+            TinyMap::Stack(map) => IterMut::Stack(map.iter_mut()),
+            TinyMap::Heap(map) => map.iter_mut(),
+        }
+    }
+    /**
+    Gets an iterator over the keys of the map, sorted
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut a = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
+    a.insert(2, "b");
+    a.insert(1, "a");
+
+    let keys: Vec<_> = a.keys().cloned().collect();
+    assert_eq!(keys, [1, 2]);
+    ```
+    */
+    pub fn keys(&self) -> Keys<'_, A::Key, A::Value, C> {
+        match self {
+            TinyMap::Stack(map) => Keys::Stack(map.keys()),
+            TinyMap::Heap(map) => map.keys(),
+        }
+    }
+    /**
+    Gets an iterator over the values of the map, sorted
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut a = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
+    a.insert(1, "hello");
+    a.insert(2, "goodbye");
+
+    let values: Vec<&str> = a.values().cloned().collect();
+    assert_eq!(values, ["hello", "goodbye"]);
+    ```
+    */
+    pub fn values(&self) -> Values<'_, A::Key, A::Value, C> {
+        match self {
+            TinyMap::Stack(map) => Values::Stack(map.values()),
+            TinyMap::Heap(map) => map.values(),
+        }
+    }
+    /**
+    Gets a mutable iterator over the values of the map, sorted
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut a = TinyMap::<[Inner<(i32, String)>; 2]>::new();
+    a.insert(1, String::from("hello"));
+    a.insert(2, String::from("goodbye"));
+
+    for value in a.values_mut() {
+        value.push_str("!");
+    }
+
+    let values: Vec<String> = a.values().cloned().collect();
+    assert_eq!(values, [String::from("hello!"),
+                        String::from("goodbye!")]);
+    ```
+    */
+    pub fn values_mut(&mut self) -> ValuesMut<'_, A::Key, A::Value, C> {
+        match self {
+            TinyMap::Stack(map) => ValuesMut::Stack(map.values_mut()),
+            TinyMap::Heap(map) => map.values_mut(),
+        }
+    }
+    /**
+    Removes a key from the map, returning the value at the key if the key was previously in the map
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
+    map.insert(1, "a");
+    assert_eq!(map.remove(&1), Some("a"));
+    assert_eq!(map.remove(&1), None);
+    ```
+    */
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<A::Value>
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Key>,
+        H: MapGet<Q, C>,
+    {
+        match self {
+            TinyMap::Stack(map) => map.remove(key),
+            TinyMap::Heap(map) => map.remove_entry(key).map(|(_, v)| v),
+        }
+    }
+    /**
+    Removes a key from the map, returning the stored key and value if the key was previously in the map
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
+    map.insert(1, "a");
+    assert_eq!(map.remove_entry(&1), Some((1, "a")));
+    assert_eq!(map.remove_entry(&1), None);
+    ```
+    */
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(A::Key, A::Value)>
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Key>,
+        H: MapGet<Q, C>,
+    {
+        match self {
+            TinyMap::Stack(map) => map.remove_entry(key),
+            TinyMap::Heap(map) => map.remove_entry(key),
+        }
+    }
+    /**
+    Returns a guard that gives mutable access to the stored key corresponding to the key
+
+    When the guard is dropped, the entry is moved to wherever its key now sorts. If the key
+    has become equal to another key in the map, the entry with the changed key replaces the
+    other entry, as [`insert`](#method.insert) would. Use [`KeyMut::finish`](enum.KeyMut.html#method.finish)
+    to get the entry back instead.
+
+    Once the map has moved to the heap, the entry is taken out of the heap while the guard is
+    alive and inserted again when it is dropped.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
+    map.insert(1, "a");
+    map.insert(2, "b");
+    map.insert(3, "c");
+
+    if let Some(mut key) = map.get_key_mut(&1) {
+        *key = 5;
+    }
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [2, 3, 5]);
+    assert_eq!(map[&5], "a");
+    ```
+    */
+    pub fn get_key_mut<Q>(&mut self, key: &Q) -> Option<KeyMut<'_, A, C, H>>
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Key>,
+        H: MapGet<Q, C> + MapLookup<A::Key, C>,
+    {
+        match self {
+            TinyMap::Stack(map) => map.get_key_mut(key).map(KeyMut::Stack),
+            TinyMap::Heap(map) => {
+                let entry = MapGet::<Q, C>::remove_entry(map, key)?;
+                Some(KeyMut::Heap(HeapKeyMut {
+                    map,
+                    entry: Some(entry),
+                    order: PhantomData,
+                }))
+            }
+        }
+    }
+    /**
+    Gets the given key's corresponding entry in the map for in-place manipulation
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut count = TinyMap::<[Inner<(&str, i32)>; 10]>::new();
+
+    // count the number of occurrences of letters in the vec
+    for x in vec!["a","b","a","c","a","b"] {
+        *count.entry(x).or_insert(0) += 1;
+    }
+
+    assert_eq!(count["a"], 3);
+    ```
+    */
+    pub fn entry(&mut self, key: A::Key) -> Entry<'_, A, C, H> {
+        match self {
+            TinyMap::Stack(map) => Entry::Stack(map.entry(key)),
+            TinyMap::Heap(map) => {
+                let len = map.len();
+                Entry::Heap(map.entry(key), len)
+            }
         }
     }
-}
-
-impl<A> TinyMap<A>
-where
-    A: MapArray,
-{
     /**
-    Creates a new empty TinyMap
+    Returns a cursor pointing at the gap before the smallest key above the given bound
+
+    Passing `Bound::Included(x)` points the cursor at the gap before the smallest key
+    greater than or equal to `x`, and `Bound::Excluded(x)` at the gap before the smallest key
+    greater than `x`. `Bound::Unbounded` points the cursor at the gap before the smallest key in the map.
 
     # Example
+
     ```
+    use core::ops::Bound;
     use tinymap::*;
 
     let mut map = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
-
-    // entries can now be inserted into the empty map
     map.insert(1, "a");
+    map.insert(2, "b");
+    map.insert(3, "c");
+
+    let mut cursor = map.lower_bound(Bound::Included(&2));
+    assert_eq!(cursor.peek_prev(), Some((&1, &"a")));
+    assert_eq!(cursor.next(), Some((&2, &"b")));
+    assert_eq!(cursor.next(), Some((&3, &"c")));
+    assert_eq!(cursor.next(), None);
     ```
     */
-    pub fn new() -> Self {
-        Self::default()
+    pub fn lower_bound<Q>(&self, bound: Bound<&Q>) -> Cursor<'_, A::Key, A::Value, C, H>
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Key>,
+        H: MapLookup<Q, C>,
+    {
+        match self {
+            TinyMap::Stack(map) => Cursor::Stack(map.lower_bound(bound)),
+            TinyMap::Heap(map) => {
+                let prev = match bound {
+                    Included(key) => map.last_below(Excluded(key)),
+                    Excluded(key) => map.last_below(Included(key)),
+                    Unbounded => None,
+                };
+                Cursor::Heap {
+                    map,
+                    prev: prev.map(|(k, _)| k),
+                    order: PhantomData,
+                }
+            }
+        }
     }
     /**
-    Clears the map, removing all elements
+    Returns a cursor pointing at the gap after the greatest key below the given bound
+
+    Passing `Bound::Included(x)` points the cursor at the gap after the greatest key
+    less than or equal to `x`, and `Bound::Excluded(x)` at the gap after the greatest key
+    less than `x`. `Bound::Unbounded` points the cursor at the gap after the greatest key in the map.
 
     # Example
+
     ```
+    use core::ops::Bound;
     use tinymap::*;
 
-    let mut a = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
-    a.insert(1, "a");
-    a.clear();
-    assert!(a.is_empty());
+    let mut map = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
+    map.insert(1, "a");
+    map.insert(2, "b");
+    map.insert(3, "c");
+
+    let mut cursor = map.upper_bound(Bound::Included(&2));
+    assert_eq!(cursor.peek_next(), Some((&3, &"c")));
+    assert_eq!(cursor.prev(), Some((&2, &"b")));
+    assert_eq!(cursor.prev(), Some((&1, &"a")));
+    assert_eq!(cursor.prev(), None);
     ```
     */
-    pub fn clear(&mut self) {
-        *self = Self::new();
+    pub fn upper_bound<Q>(&self, bound: Bound<&Q>) -> Cursor<'_, A::Key, A::Value, C, H>
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Key>,
+        H: MapLookup<Q, C>,
+    {
+        match self {
+            TinyMap::Stack(map) => Cursor::Stack(map.upper_bound(bound)),
+            TinyMap::Heap(map) => {
+                let prev = map.last_below(bound);
+                Cursor::Heap {
+                    map,
+                    prev: prev.map(|(k, _)| k),
+                    order: PhantomData,
+                }
+            }
+        }
     }
     /**
-    Returns the number of elements in the map
+    Returns a mutable cursor pointing at the gap before the smallest key above the given bound
+
+    See [`lower_bound`](#method.lower_bound) for how the bound is interpreted.
 
     # Example
 
     ```
+    use core::ops::Bound;
     use tinymap::*;
 
-    let mut a = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
-    assert_eq!(a.len(), 0);
-    a.insert(1, "a");
-    assert_eq!(a.len(), 1);
+    let mut map = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
+    map.insert(1, "a");
+    map.insert(3, "c");
+
+    let mut cursor = map.lower_bound_mut(Bound::Excluded(&1));
+    assert!(cursor.insert_after(2, "b").is_ok());
+    assert_eq!(cursor.next(), Some((&2, &mut "b")));
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [1, 2, 3]);
     ```
     */
-    pub fn len(&self) -> usize {
-        match self {
-            TinyMap::Stack(map) => map.len(),
-            TinyMap::Heap(map) => map.len(),
+    pub fn lower_bound_mut<Q>(&mut self, bound: Bound<&Q>) -> CursorMut<'_, A, C, H>
+    where
+        A::Key: Clone,
+        Q: ?Sized,
+        C: Compare<Q, A::Key>,
+        H: MapLookup<Q, C> + MapLookup<A::Key, C>,
+    {
+        let (index, prev) = match self.lower_bound(bound) {
+            Cursor::Stack(cursor) => (cursor.index, None),
+            Cursor::Heap { prev, .. } => (0, prev.cloned()),
+        };
+        CursorMut {
+            map: self,
+            index,
+            prev,
         }
     }
     /**
-    Returns `true` if the map contains no elements
+    Returns a mutable cursor pointing at the gap after the greatest key below the given bound
+
+    See [`upper_bound`](#method.upper_bound) for how the bound is interpreted.
 
     # Example
 
     ```
+    use core::ops::Bound;
     use tinymap::*;
 
-    let mut a = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
-    assert!(a.is_empty());
-    a.insert(1, "a");
-    assert!(!a.is_empty());
+    let mut map = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
+    map.insert(1, "a");
+    map.insert(2, "b");
+    map.insert(3, "c");
+
+    let mut cursor = map.upper_bound_mut(Bound::<&i32>::Unbounded);
+    assert_eq!(cursor.remove_prev(), Some((3, "c")));
+    assert_eq!(cursor.peek_prev(), Some((&2, &mut "b")));
+    assert_eq!(map.len(), 2);
     ```
     */
-    pub fn is_empty(&self) -> bool {
-        self.len() == 0
+    pub fn upper_bound_mut<Q>(&mut self, bound: Bound<&Q>) -> CursorMut<'_, A, C, H>
+    where
+        A::Key: Clone,
+        Q: ?Sized,
+        C: Compare<Q, A::Key>,
+        H: MapLookup<Q, C> + MapLookup<A::Key, C>,
+    {
+        let (index, prev) = match self.upper_bound(bound) {
+            Cursor::Stack(cursor) => (cursor.index, None),
+            Cursor::Heap { prev, .. } => (0, prev.cloned()),
+        };
+        CursorMut {
+            map: self,
+            index,
+            prev,
+        }
     }
     /**
-    Returns the maximum number of elements the map can contain on the stack
+    Splits the map in two at the given key
+
+    Returns a map containing every entry with a key greater than or equal to the given key.
+    The entries with smaller keys stay in this map.
+
+    A map on the heap is split into two maps on the heap.
 
     # Example
 
     ```
     use tinymap::*;
 
-    let mut a = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
-    assert_eq!(2, a.capacity());
+    let mut a = TinyMap::<[Inner<(i32, &str)>; 10]>::new();
+    a.insert(1, "a");
+    a.insert(2, "b");
+    a.insert(3, "c");
+    a.insert(17, "d");
+    a.insert(41, "e");
+
+    let b = a.split_off(&3);
+
+    assert_eq!(a.len(), 2);
+    assert_eq!(b.len(), 3);
+    assert_eq!(b[&17], "d");
     ```
     */
-    pub fn capacity(&self) -> usize {
-        A::CAPACITY
+    pub fn split_off<Q>(&mut self, key: &Q) -> Self
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Key> + Clone,
+        H: MapLookup<Q, C>,
+    {
+        match self {
+            TinyMap::Stack(map) => TinyMap::Stack(map.split_off(key)),
+            TinyMap::Heap(map) => TinyMap::Heap(map.split_off(key)),
+        }
     }
     /**
-    Gets an iterator over the entries of the map, sorted by key
+    Moves all entries from `other` into this map, leaving `other` empty
+
+    If a key from `other` is already present in this map, the entry from `other` replaces it.
+
+    If both maps are on the stack and their entries fit in one array, they are merged in two
+    linear passes, the first of which counts the merged entries. Otherwise, this map moves to the heap.
 
     # Example
 
     ```
     use tinymap::*;
 
-    let mut map = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
-    map.insert(3, "c");
-    map.insert(2, "b");
-    map.insert(1, "a");
+    let mut a = TinyMap::<[Inner<(i32, &str)>; 4]>::new();
+    a.insert(1, "a");
+    a.insert(2, "b");
+    a.insert(3, "c");
 
-    for (key, value) in map.iter() {
-        println!("{}: {}", key, value);
-    }
+    let mut b = TinyMap::<[Inner<(i32, &str)>; 4]>::new();
+    b.insert(3, "d");
+    b.insert(4, "e");
+    b.insert(5, "f");
 
-    let (first_key, first_value) = map.iter().next().unwrap();
-    assert_eq!((*first_key, *first_value), (1, "a"));
+    a.append(&mut b);
+
+    assert_eq!(a.len(), 5);
+    assert_eq!(b.len(), 0);
+    assert_eq!(a[&3], "d");
     ```
     */
-    pub fn iter(&self) -> Iter<'_, A::Key, A::Value> {
-        match self {
-            TinyMap::Stack(map) => Iter::Stack(map.iter()),
-            TinyMap::Heap(map) => Iter::Heap(map.iter()),
+    pub fn append(&mut self, other: &mut Self) {
+        #[cfg(feature = "stats")]
+        let len = self.len();
+        let appended = match (&mut *self, &mut *other) {
+            (TinyMap::Stack(ours), TinyMap::Stack(theirs)) => ours.try_append(theirs).is_ok(),
+            _ => false,
+        };
+        if !appended {
+            match other {
+                TinyMap::Stack(theirs) => self.heap_mut().append(&mut H::from_stack(theirs)),
+                TinyMap::Heap(theirs) => self.heap_mut().append(theirs),
+            }
         }
+        #[cfg(feature = "stats")]
+        self.record_growth(len);
     }
-}
-
-impl<A> TinyMap<A>
-where
-    A: MapArray,
-    A::Key: Ord,
-{
     /**
-    Inserts a key-value pair into the map
-
-    If the map did not have this key present, None is returned.
-
-    If the map did have this key present, the key and value are updated, and the old value is returned.
+    Moves all entries from `other` into this map, combining the values of keys present in both
 
-    # Panics
+    `f` is called with the key and the values from this map and from `other`, in that order,
+    and its result becomes the key's new value. The key from this map is kept.
 
-    Panics if insertion would cause the map to excede its capacity.
+    If both maps are on the stack and their entries fit in one array, they are merged in two
+    linear passes, the first of which counts the merged entries. Otherwise, this map moves to the heap.
 
     # Example
 
     ```
     use tinymap::*;
 
-    let mut map = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
-    assert_eq!(map.insert(37, "a"), None);
-    assert_eq!(map.is_empty(), false);
+    let mut a = TinyMap::<[Inner<(&str, i32)>; 2]>::new();
+    a.insert("apples", 3);
+    a.insert("pears", 1);
 
-    map.insert(37, "b");
-    assert_eq!(map.insert(37, "c"), Some("b"));
-    assert_eq!(map[&37], "c");
+    let mut b = TinyMap::<[Inner<(&str, i32)>; 2]>::new();
+    b.insert("pears", 4);
+    b.insert("plums", 2);
+
+    a.merge_with(b, |_, x, y| x + y);
+
+    assert_eq!(a["apples"], 3);
+    assert_eq!(a["pears"], 5);
+    assert_eq!(a["plums"], 2);
     ```
     */
-    pub fn insert(&mut self, key: A::Key, value: A::Value) -> Option<A::Value> {
-        match self {
-            TinyMap::Stack(map) => match map.try_insert(key, value) {
-                Ok(res) => res,
-                Err((k, v)) => {
-                    let mut replacement_map = ArrayMap::default();
-                    swap(&mut replacement_map, map);
-                    let mut btree_map = BTreeMap::new();
-                    for (k, v) in replacement_map {
-                        btree_map.insert(k, v);
+    pub fn merge_with<F>(&mut self, mut other: Self, mut f: F)
+    where
+        F: FnMut(&A::Key, A::Value, A::Value) -> A::Value,
+        H: MapLookup<A::Key, C>,
+    {
+        #[cfg(feature = "stats")]
+        let len = self.len();
+        let merged = match (&mut *self, &mut other) {
+            (TinyMap::Stack(ours), TinyMap::Stack(theirs)) => ours
+                .merge(theirs, |(key, ours), (_, theirs)| {
+                    let value = f(&key, ours, theirs);
+                    (key, value)
+                })
+                .is_ok(),
+            _ => false,
+        };
+        if !merged {
+            let map = self.heap_mut();
+            for (key, value) in other {
+                match map.remove_entry(&key) {
+                    Some((key, old)) => {
+                        let value = f(&key, old, value);
+                        map.insert(key, value);
+                    }
+                    None => {
+                        map.insert(key, value);
                     }
-                    let res = btree_map.insert(k, v);
-                    *self = TinyMap::Heap(btree_map);
-                    res
                 }
-            },
-            TinyMap::Heap(map) => map.insert(key, value),
+            }
         }
+        #[cfg(feature = "stats")]
+        self.record_growth(len);
     }
     /**
-    Returns a reference to the value corresponding to the key
+    Gets an iterator over the differences between this map and `other`, sorted by key
+
+    Keys only in `other` are [`Added`](../diff/enum.DiffItem.html#variant.Added), keys only in this map
+    are [`Removed`](../diff/enum.DiffItem.html#variant.Removed), and keys in both maps with
+    different values are [`Changed`](../diff/enum.DiffItem.html#variant.Changed).
+
+    Both maps are walked in a single linear pass, whether they are on the stack or on the heap.
 
     # Example
 
     ```
-    use tinymap::*;
-
-    let mut map = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
-    map.insert(1, "a");
-    assert_eq!(map.get(&1), Some(&"a"));
-    assert_eq!(map.get(&2), None);
+    use tinymap::{diff::DiffItem, *};
+
+    let mut old = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
+    old.insert(1, "a");
+    old.insert(2, "b");
+    old.insert(3, "c");
+
+    let mut new = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
+    new.insert(2, "b");
+    new.insert(3, "d");
+
+    let changes: Vec<_> = old.diff(&new).collect();
+    assert_eq!(changes, [
+        DiffItem::Removed(&1, &"a"),
+        DiffItem::Changed(&3, &"c", &"d"),
+    ]);
     ```
     */
-    pub fn get<Q>(&self, key: &Q) -> Option<&A::Value>
+    pub fn diff<'a>(&'a self, other: &'a Self) -> Diff<Iter<'a, A::Key, A::Value, C>, C>
     where
-        A::Key: Borrow<Q>,
-        Q: Ord + ?Sized,
+        A::Value: PartialEq,
+        C: Clone,
     {
-        match self {
-            TinyMap::Stack(map) => map.get(key),
-            TinyMap::Heap(map) => map.get(key),
-        }
+        Diff::new(self.iter(), other.iter(), self.comparator())
     }
     /**
-    Returns true if the map contains a value for the specified key
+    Gets an iterator over the keys present in both this map and `other`, along with their values from each
+
+    `other` can be anything that iterates over references to a map or set in ascending key order,
+    such as another map or set from this crate. Both sides are walked in a single linear pass.
+    See the [`join`](../join/index.html) module for details.
 
     # Example
 
     ```
     use tinymap::*;
 
-    let mut map = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
-    map.insert(1, "a");
-    assert_eq!(map.contains_key(&1), true);
-    assert_eq!(map.contains_key(&2), false);
+    let mut prices = TinyMap::<[Inner<(&str, u32)>; 4]>::new();
+    prices.insert("apple", 3);
+    prices.insert("pear", 4);
+    prices.insert("plum", 2);
+
+    let mut stock = TinyMap::<[Inner<(&str, u32)>; 4]>::new();
+    stock.insert("pear", 10);
+    stock.insert("plum", 0);
+    stock.insert("quince", 5);
+
+    let joined: Vec<_> = prices.join(&stock).collect();
+    assert_eq!(joined, [(&"pear", &4, &10), (&"plum", &2, &0)]);
     ```
     */
-    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    pub fn join<'a, I>(&'a self, other: I) -> Join<Iter<'a, A::Key, A::Value, C>, I::IntoIter, C>
     where
-        A::Key: Borrow<Q>,
-        Q: Ord + ?Sized,
+        I: IntoIterator,
+        I::Item: JoinItem<Key = &'a A::Key>,
+        C: Clone,
     {
-        match self {
-            TinyMap::Stack(map) => map.contains_key(key),
-            TinyMap::Heap(map) => map.contains_key(key),
-        }
+        Join::new(self.iter(), other.into_iter(), self.comparator())
     }
     /**
-    Returns a mutable reference to the value corresponding to the key
+    Gets an iterator over the keys in this map, along with their values from this map and, if present, from `other`
+
+    `other` can be anything that iterates over references to a map or set in ascending key order,
+    such as another map or set from this crate. Both sides are walked in a single linear pass.
+    See the [`join`](../join/index.html) module for details.
 
     # Example
 
     ```
     use tinymap::*;
 
-    let mut map = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
-    map.insert(1, "a");
-    if let Some(x) = map.get_mut(&1) {
-        *x = "b";
+    let mut prices = TinyMap::<[Inner<(&str, u32)>; 4]>::new();
+    prices.insert("apple", 3);
+    prices.insert("pear", 4);
+    prices.insert("plum", 2);
+
+    let mut stock = TinyMap::<[Inner<(&str, u32)>; 4]>::new();
+    stock.insert("pear", 10);
+    stock.insert("plum", 0);
+    stock.insert("quince", 5);
+
+    let joined: Vec<_> = prices.left_join(&stock).collect();
+    assert_eq!(joined, [
+        (&"apple", &3, None),
+        (&"pear", &4, Some(&10)),
+        (&"plum", &2, Some(&0)),
+    ]);
+    ```
+    */
+    pub fn left_join<'a, I>(
+        &'a self,
+        other: I,
+    ) -> LeftJoin<Iter<'a, A::Key, A::Value, C>, I::IntoIter, C>
+    where
+        I: IntoIterator,
+        I::Item: JoinItem<Key = &'a A::Key>,
+        C: Clone,
+    {
+        LeftJoin::new(self.iter(), other.into_iter(), self.comparator())
     }
-    assert_eq!(map[&1], "b");
+    /**
+    Gets an iterator over the keys in either this map or `other`, along with the values from whichever sides have them
+
+    `other` can be anything that iterates over references to a map or set in ascending key order,
+    such as another map or set from this crate. Both sides are walked in a single linear pass.
+    See the [`join`](../join/index.html) module for details.
+
+    # Example
+
+    ```
+    use tinymap::{join::EitherOrBoth, *};
+
+    let mut prices = TinyMap::<[Inner<(&str, u32)>; 4]>::new();
+    prices.insert("apple", 3);
+    prices.insert("pear", 4);
+    prices.insert("plum", 2);
+
+    let mut stock = TinyMap::<[Inner<(&str, u32)>; 4]>::new();
+    stock.insert("pear", 10);
+    stock.insert("plum", 0);
+    stock.insert("quince", 5);
+
+    let mut discontinued = TinySet::<[Inner<&str>; 4]>::new();
+    discontinued.insert("apple");
+    discontinued.insert("fig");
+
+    // Sets join as if they had `()` values
+    let joined: Vec<_> = prices.outer_join(&discontinued).collect();
+    assert_eq!(joined, [
+        (&"apple", EitherOrBoth::Both(&3, ())),
+        (&"fig", EitherOrBoth::Right(())),
+        (&"pear", EitherOrBoth::Left(&4)),
+        (&"plum", EitherOrBoth::Left(&2)),
+    ]);
     ```
     */
-    pub fn get_mut<'a, Q>(&'a mut self, key: &Q) -> Option<&'a mut A::Value>
+    pub fn outer_join<'a, I>(
+        &'a self,
+        other: I,
+    ) -> OuterJoin<Iter<'a, A::Key, A::Value, C>, I::IntoIter, C>
     where
-        A::Key: Borrow<Q> + 'a,
-        Q: Ord + ?Sized,
+        I: IntoIterator,
+        I::Item: JoinItem<Key = &'a A::Key>,
+        C: Clone,
     {
-        match self {
-            TinyMap::Stack(map) => map.get_mut(key),
-            TinyMap::Heap(map) => map.get_mut(key),
-        }
+        OuterJoin::new(self.iter(), other.into_iter(), self.comparator())
     }
     /**
-    Gets a mutable iterator over the entries of the map, sorted by key
+    Removes the key-value pair at the given position in the map's sort order and returns it
+
+    This is O(n) both on the stack and on the heap. Once the map has moved to the heap,
+    the key at that position is cloned to look the entry up.
 
     # Example
 
     ```
     use tinymap::*;
 
-    let mut map = TinyMap::<[Inner<(&str, i32)>; 2]>::new();
-    map.insert("a", 1);
-    map.insert("b", 2);
-    map.insert("c", 3);
-
-    // add 10 to the value if the key isn't "a"
-    for (key, value) in map.iter_mut() {
-        if key != &"a" {
-            *value += 10;
-        }
-    }
+    let mut map = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
+    map.insert(2, "b");
+    map.insert(1, "a");
+    assert_eq!(map.remove_index(0), Some((1, "a")));
+    assert_eq!(map.remove_index(1), None);
+    assert_eq!(map.len(), 1);
     ```
     */
-    pub fn iter_mut(&mut self) -> IterMut<'_, A::Key, A::Value> {
+    pub fn remove_index(&mut self, index: usize) -> Option<(A::Key, A::Value)>
+    where
+        A::Key: Clone,
+    {
         match self {
-            TinyMap::Stack(map) => IterMut::Stack(map.iter_mut()),
-            TinyMap::Heap(map) => IterMut::Heap(map.iter_mut()),
+            TinyMap::Stack(map) => map.remove_index(index),
+            TinyMap::Heap(map) => map.remove_index(index),
         }
     }
     /**
-    Gets an iterator over the keys of the map, sorted
+    Returns the position of the key in the map's sort order, if it is present
+
+    This is O(log n) while the map is on the stack or in a VecMap and O(n) once it has moved to a BTreeMap.
 
     # Example
 
     ```
     use tinymap::*;
 
-    let mut a = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
-    a.insert(2, "b");
-    a.insert(1, "a");
-
-    let keys: Vec<_> = a.keys().cloned().collect();
-    assert_eq!(keys, [1, 2]);
+    let mut map = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
+    map.insert(10, "a");
+    map.insert(20, "b");
+    map.insert(30, "c");
+    assert_eq!(map.index_of(&20), Some(1));
+    assert_eq!(map.index_of(&15), None);
     ```
     */
-    pub fn keys(&self) -> Keys<'_, A::Key, A::Value> {
+    pub fn index_of<Q>(&self, key: &Q) -> Option<usize>
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Key>,
+        H: MapLookup<Q, C>,
+    {
         match self {
-            TinyMap::Stack(map) => Keys::Stack(map.keys()),
-            TinyMap::Heap(map) => Keys::Heap(map.keys()),
+            TinyMap::Stack(map) => map.index_of(key),
+            TinyMap::Heap(map) => {
+                map.get_key_value(key)?;
+                Some(map.count_below(Excluded(key)))
+            }
         }
     }
     /**
-    Gets an iterator over the values of the map, sorted
+    Returns the number of keys in the map that are less than the given key
+
+    The key does not have to be present in the map.
+
+    This is O(log n) while the map is on the stack or in a VecMap and O(n) once it has moved to a BTreeMap.
 
     # Example
 
     ```
     use tinymap::*;
 
-    let mut a = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
-    a.insert(1, "hello");
-    a.insert(2, "goodbye");
-
-    let values: Vec<&str> = a.values().cloned().collect();
-    assert_eq!(values, ["hello", "goodbye"]);
+    let mut map = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
+    map.insert(10, "a");
+    map.insert(20, "b");
+    map.insert(30, "c");
+    assert_eq!(map.rank(&5), 0);
+    assert_eq!(map.rank(&20), 1);
+    assert_eq!(map.rank(&25), 2);
     ```
     */
-    pub fn values(&self) -> Values<'_, A::Key, A::Value> {
+    pub fn rank<Q>(&self, key: &Q) -> usize
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Key>,
+        H: MapLookup<Q, C>,
+    {
         match self {
-            TinyMap::Stack(map) => Values::Stack(map.values()),
-            TinyMap::Heap(map) => Values::Heap(map.values()),
+            TinyMap::Stack(map) => map.rank(key),
+            TinyMap::Heap(map) => map.count_below(Excluded(key)),
         }
     }
+}
+
+impl<K, V, C, const N: usize> TinyMap<[Inner<(K, V)>; N], C>
+where
+    [Inner<(K, V)>; N]: MapArray<Key = K, Value = V>,
+    K: Ord,
+    C: TreeOrder<K>,
+{
     /**
-    Gets a mutable iterator over the values of the map, sorted
+    Converts the map into one with a different value type by calling a function on every entry
+
+    The keys and their order are kept, so no searching is needed.
+    The new map is on the stack if and only if this one is.
 
     # Example
 
     ```
     use tinymap::*;
 
-    let mut a = TinyMap::<[Inner<(i32, String)>; 2]>::new();
-    a.insert(1, String::from("hello"));
-    a.insert(2, String::from("goodbye"));
-
-    for value in a.values_mut() {
-        value.push_str("!");
-    }
+    let mut map = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
+    map.insert(1, "a");
+    map.insert(2, "bb");
+    map.insert(3, "ccc");
 
-    let values: Vec<String> = a.values().cloned().collect();
-    assert_eq!(values, [String::from("hello!"),
-                        String::from("goodbye!")]);
+    let lens = map.map_values(|_, v| v.len());
+    assert!(matches!(lens, TinyMap::Heap(_)));
+    assert_eq!(lens.values().copied().collect::<Vec<_>>(), [1, 2, 3]);
     ```
     */
-    pub fn values_mut(&mut self) -> ValuesMut<'_, A::Key, A::Value> {
-        match self {
-            TinyMap::Stack(map) => ValuesMut::Stack(map.values_mut()),
-            TinyMap::Heap(map) => ValuesMut::Heap(map.values_mut()),
+    pub fn map_values<U, F>(self, mut f: F) -> TinyMap<[Inner<(K, U)>; N], C>
+    where
+        [Inner<(K, U)>; N]: MapArray<Key = K, Value = U>,
+        F: FnMut(&K, V) -> U,
+    {
+        match self.transform_values(|k, v| Ok::<_, Infallible>(Some(f(k, v)))) {
+            Ok(map) => map,
+            Err(e) => match e {},
         }
     }
     /**
-    Removes a key from the map, returning the value at the key if the key was previously in the map
+    Converts the map into one with a different value type by calling a fallible function on every entry
+
+    Stops at the first error and returns it.
+    The new map is on the stack if and only if this one is.
 
     # Example
 
     ```
     use tinymap::*;
 
-    let mut map = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
-    map.insert(1, "a");
-    assert_eq!(map.remove(&1), Some("a"));
-    assert_eq!(map.remove(&1), None);
+    let mut map = TinyMap::<[Inner<(i32, String)>; 10]>::new();
+    map.insert(1, "1".into());
+    map.insert(2, "2".into());
+
+    let parsed = map.clone().try_map_values(|_, v| v.parse::<u8>()).unwrap();
+    assert_eq!(parsed[&2], 2);
+
+    map.insert(3, "x".into());
+    assert!(map.try_map_values(|_, v| v.parse::<u8>()).is_err());
     ```
     */
-    pub fn remove<Q>(&mut self, key: &Q) -> Option<A::Value>
+    pub fn try_map_values<U, E, F>(self, mut f: F) -> Result<TinyMap<[Inner<(K, U)>; N], C>, E>
     where
-        A::Key: Borrow<Q>,
-        Q: Ord + ?Sized,
+        [Inner<(K, U)>; N]: MapArray<Key = K, Value = U>,
+        F: FnMut(&K, V) -> Result<U, E>,
     {
-        match self {
-            TinyMap::Stack(map) => map.remove(key),
-            TinyMap::Heap(map) => map.remove(key),
-        }
+        self.transform_values(|k, v| f(k, v).map(Some))
     }
     /**
-    Gets the given key's corresponding entry in the map for in-place manipulation
+    Converts the map into one with a different value type, keeping only the entries
+    for which the function returns `Some`
+
+    The new map is on the stack if and only if this one is.
 
     # Example
 
     ```
     use tinymap::*;
 
-    let mut count = TinyMap::<[Inner<(&str, i32)>; 10]>::new();
-
-    // count the number of occurrences of letters in the vec
-    for x in vec!["a","b","a","c","a","b"] {
-        *count.entry(x).or_insert(0) += 1;
-    }
+    let mut map = TinyMap::<[Inner<(i32, &str)>; 10]>::new();
+    map.insert(1, "1");
+    map.insert(2, "x");
+    map.insert(3, "3");
 
-    assert_eq!(count["a"], 3);
+    let parsed = map.filter_map_values(|_, v| v.parse::<u8>().ok());
+    assert_eq!(parsed.iter().collect::<Vec<_>>(), [(&1, &1), (&3, &3)]);
     ```
     */
-    pub fn entry(&mut self, key: A::Key) -> Entry<'_, A> {
-        match self {
-            TinyMap::Stack(map) => Entry::Stack(map.entry(key)),
-            TinyMap::Heap(map) => Entry::Heap(map.entry(key)),
+    pub fn filter_map_values<U, F>(self, mut f: F) -> TinyMap<[Inner<(K, U)>; N], C>
+    where
+        [Inner<(K, U)>; N]: MapArray<Key = K, Value = U>,
+        F: FnMut(&K, V) -> Option<U>,
+    {
+        match self.transform_values(|k, v| Ok::<_, Infallible>(f(k, v))) {
+            Ok(map) => map,
+            Err(e) => match e {},
         }
     }
+    fn transform_values<U, E, F>(self, mut f: F) -> Result<TinyMap<[Inner<(K, U)>; N], C>, E>
+    where
+        [Inner<(K, U)>; N]: MapArray<Key = K, Value = U>,
+        F: FnMut(&K, V) -> Result<Option<U>, E>,
+    {
+        Ok(match self {
+            TinyMap::Stack(map) => TinyMap::Stack(map.transform_values(f)?),
+            map => TinyMap::Heap(
+                map.into_iter()
+                    .filter_map(|(key, value)| {
+                        f(&key, value)
+                            .transpose()
+                            .map(|value| value.map(|value| (key, value)))
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+        })
+    }
 }
 
-impl<A, Q> Index<&Q> for TinyMap<A>
+impl<A, C, H, Q> Index<&Q> for TinyMap<A, C, H>
 where
     A: MapArray,
-    A::Key: Ord + Borrow<Q>,
-    Q: Ord + ?Sized,
+    Q: ?Sized,
+    C: Compare<A::Key> + Compare<Q, A::Key>,
+    H: MapGet<Q, C, Key = A::Key, Value = A::Value>,
 {
     type Output = A::Value;
     fn index(&self, key: &Q) -> &Self::Output {
@@ -442,124 +2383,157 @@ where
     }
 }
 
-impl<A> fmt::Debug for TinyMap<A>
+impl<A, C, H> fmt::Debug for TinyMap<A, C, H>
 where
     A: MapArray,
     A::Key: fmt::Debug,
     A::Value: fmt::Debug,
+    H: MapHeap<C, Key = A::Key, Value = A::Value>,
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_map().entries(self.iter()).finish()
     }
 }
 
-impl<A> From<ArrayMap<A>> for TinyMap<A>
+impl<A, C> From<ArrayMap<A, C>> for TinyMap<A, C>
 where
     A: MapArray,
 {
-    fn from(map: ArrayMap<A>) -> Self {
+    fn from(map: ArrayMap<A, C>) -> Self {
         TinyMap::Stack(map)
     }
 }
 
-impl<A> From<BTreeMap<A::Key, A::Value>> for TinyMap<A>
+/// The tree becomes the heap of the map as it is, without moving its entries.
+impl<A, C> From<BTreeMap<A::Key, A::Value>> for TinyMap<A, C>
 where
     A: MapArray,
+    A::Key: Ord,
+    C: TreeOrder<A::Key>,
 {
     fn from(map: BTreeMap<A::Key, A::Value>) -> Self {
         TinyMap::Heap(map)
     }
 }
 
-impl<A> From<A> for TinyMap<A>
+/**
+The tree is ordered by the keys' `Ord` implementation, whatever the map's comparator.
+
+A map on the heap hands over its tree as it is, without moving its entries.
+A map on the stack builds a new tree from its entries.
+*/
+impl<A, C> From<TinyMap<A, C>> for BTreeMap<A::Key, A::Value>
 where
     A: MapArray,
     A::Key: Ord,
+{
+    fn from(map: TinyMap<A, C>) -> Self {
+        match map {
+            TinyMap::Stack(map) => map.into(),
+            TinyMap::Heap(map) => map,
+        }
+    }
+}
+
+impl<A, C> From<A> for TinyMap<A, C>
+where
+    A: MapArray,
+    C: Compare<A::Key> + Default,
 {
     fn from(array: A) -> Self {
         TinyMap::from(ArrayMap::from(array))
     }
 }
 
-impl<A> PartialEq for TinyMap<A>
+impl<A, C, H> PartialEq for TinyMap<A, C, H>
 where
     A: MapArray,
     A::Key: PartialEq,
     A::Value: PartialEq,
+    H: MapHeap<C, Key = A::Key, Value = A::Value>,
 {
     fn eq(&self, other: &Self) -> bool {
         self.iter().eq(other.iter())
     }
 }
 
-impl<A> Eq for TinyMap<A>
+impl<A, C, H> Eq for TinyMap<A, C, H>
 where
     A: MapArray,
     A::Key: Eq,
     A::Value: Eq,
+    H: MapHeap<C, Key = A::Key, Value = A::Value>,
 {
 }
 
-impl<'a, A> IntoIterator for &'a TinyMap<A>
+impl<'a, A, C, H> IntoIterator for &'a TinyMap<A, C, H>
 where
     A: MapArray,
+    H: MapHeap<C, Key = A::Key, Value = A::Value>,
 {
     type Item = (&'a A::Key, &'a A::Value);
-    type IntoIter = Iter<'a, A::Key, A::Value>;
+    type IntoIter = Iter<'a, A::Key, A::Value, C>;
     fn into_iter(self) -> Self::IntoIter {
         self.iter()
     }
 }
 
-impl<'a, A> IntoIterator for &'a mut TinyMap<A>
+impl<'a, A, C, H> IntoIterator for &'a mut TinyMap<A, C, H>
 where
     A: MapArray,
-    A::Key: Ord,
+    C: Compare<A::Key>,
+    H: MapHeap<C, Key = A::Key, Value = A::Value>,
 {
     type Item = (&'a A::Key, &'a mut A::Value);
-    type IntoIter = IterMut<'a, A::Key, A::Value>;
+    type IntoIter = IterMut<'a, A::Key, A::Value, C>;
     fn into_iter(self) -> Self::IntoIter {
         self.iter_mut()
     }
 }
 
 #[cfg(feature = "alloc")]
-impl<A> IntoIterator for TinyMap<A>
+impl<A, C, H> IntoIterator for TinyMap<A, C, H>
 where
     A: MapArray,
+    H: MapHeap<C, Key = A::Key, Value = A::Value>,
 {
     type Item = (A::Key, A::Value);
-    type IntoIter = IntoIter<A::Key, A::Value>;
+    type IntoIter = IntoIter<A::Key, A::Value, C, H>;
     fn into_iter(self) -> Self::IntoIter {
         match self {
             TinyMap::Stack(map) => IntoIter::Stack(map.into_iter()),
-            TinyMap::Heap(map) => IntoIter::Heap(map.into_iter()),
+            TinyMap::Heap(map) => IntoIter::Heap(map.into_entries()),
         }
     }
 }
 
-/// Elements from the iterator beyond the map's capacity will be discarded.
-impl<A> FromIterator<(A::Key, A::Value)> for TinyMap<A>
+/// The map starts on the heap if the iterator's size hint says that it will not fit on the stack.
+impl<A, C, H> FromIterator<(A::Key, A::Value)> for TinyMap<A, C, H>
 where
     A: MapArray,
-    A::Key: Ord,
+    C: Compare<A::Key> + Default,
+    H: MapHeap<C, Key = A::Key, Value = A::Value> + Default,
 {
     fn from_iter<I>(iter: I) -> Self
     where
         I: IntoIterator<Item = (A::Key, A::Value)>,
     {
-        let mut map = TinyMap::default();
-        for (key, value) in iter.into_iter().take(A::CAPACITY) {
-            map.insert(key, value);
-        }
+        let iter = iter.into_iter();
+        let mut map = if iter.size_hint().0 > A::CAPACITY {
+            TinyMap::with_heap()
+        } else {
+            TinyMap::default()
+        };
+        map.extend(iter);
         map
     }
 }
 
-impl<A> Extend<(A::Key, A::Value)> for TinyMap<A>
+impl<A, C, H> Extend<(A::Key, A::Value)> for TinyMap<A, C, H>
 where
     A: MapArray,
-    A::Key: Ord,
+    C: Compare<A::Key>,
+    H: MapHeap<C, Key = A::Key, Value = A::Value>,
 {
     fn extend<I>(&mut self, iter: I)
     where
@@ -572,14 +2546,20 @@ where
 }
 
 /// An consuming iterator over the values in an ArraySet
-pub enum IntoIter<K, V> {
+pub enum IntoIter<K, V, C = Natural, H = BTreeMap<K, V>>
+where
+    H: MapHeap<C, Key = K, Value = V>,
+{
     #[doc(hidden)]
     Stack(crate::array_map::IntoIter<K, V>),
     #[doc(hidden)]
-    Heap(btree_map::IntoIter<K, V>),
+    Heap(H::IntoIter),
 }
 
-impl<K, V> Iterator for IntoIter<K, V> {
+impl<K, V, C, H> Iterator for IntoIter<K, V, C, H>
+where
+    H: MapHeap<C, Key = K, Value = V>,
+{
     type Item = (K, V);
     fn next(&mut self) -> Option<Self::Item> {
         match self {
@@ -589,15 +2569,67 @@ impl<K, V> Iterator for IntoIter<K, V> {
     }
 }
 
+/// A consuming iterator over the keys in a TinyMap
+pub enum IntoKeys<A, C = Natural, H = BTreeMap<<A as MapStorage>::Key, <A as MapStorage>::Value>>
+where
+    A: MapArray,
+    H: MapHeap<C, Key = A::Key, Value = A::Value>,
+{
+    #[doc(hidden)]
+    Stack(crate::array_map::IntoKeys<A>),
+    #[doc(hidden)]
+    Heap(H::IntoKeys),
+}
+
+impl<A, C, H> Iterator for IntoKeys<A, C, H>
+where
+    A: MapArray,
+    H: MapHeap<C, Key = A::Key, Value = A::Value>,
+{
+    type Item = A::Key;
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            IntoKeys::Stack(iter) => iter.next(),
+            IntoKeys::Heap(iter) => iter.next(),
+        }
+    }
+}
+
+/// A consuming iterator over the values in a TinyMap
+pub enum IntoValues<A, C = Natural, H = BTreeMap<<A as MapStorage>::Key, <A as MapStorage>::Value>>
+where
+    A: MapArray,
+    H: MapHeap<C, Key = A::Key, Value = A::Value>,
+{
+    #[doc(hidden)]
+    Stack(crate::array_map::IntoValues<A>),
+    #[doc(hidden)]
+    Heap(H::IntoValues),
+}
+
+impl<A, C, H> Iterator for IntoValues<A, C, H>
+where
+    A: MapArray,
+    H: MapHeap<C, Key = A::Key, Value = A::Value>,
+{
+    type Item = A::Value;
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            IntoValues::Stack(iter) => iter.next(),
+            IntoValues::Heap(iter) => iter.next(),
+        }
+    }
+}
+
 /// An iterator over references to the key-value pairs in an TinyMap
-pub enum Iter<'a, K, V> {
+pub enum Iter<'a, K, V, C = Natural> {
     #[doc(hidden)]
     Stack(crate::array_map::Iter<'a, K, V>),
     #[doc(hidden)]
-    Heap(btree_map::Iter<'a, K, V>),
+    Heap(Ordered<btree_map::Iter<'a, K, V>>, PhantomData<fn() -> C>),
 }
 
-impl<'a, K, V> Iterator for Iter<'a, K, V>
+impl<'a, K, V, C> Iterator for Iter<'a, K, V, C>
 where
     K: 'a,
     V: 'a,
@@ -606,20 +2638,23 @@ where
     fn next(&mut self) -> Option<Self::Item> {
         match self {
             Iter::Stack(iter) => iter.next(),
-            Iter::Heap(iter) => iter.next(),
+            Iter::Heap(iter, _) => iter.next(),
         }
     }
 }
 
 /// An iterator over references to keys and mutable references to values in an TinyMap
-pub enum IterMut<'a, K, V> {
+pub enum IterMut<'a, K, V, C = Natural> {
     #[doc(hidden)]
     Stack(crate::array_map::IterMut<'a, K, V>),
     #[doc(hidden)]
-    Heap(btree_map::IterMut<'a, K, V>),
+    Heap(
+        Ordered<btree_map::IterMut<'a, K, V>>,
+        PhantomData<fn() -> C>,
+    ),
 }
 
-impl<'a, K, V> Iterator for IterMut<'a, K, V>
+impl<'a, K, V, C> Iterator for IterMut<'a, K, V, C>
 where
     K: 'a,
     V: 'a,
@@ -628,20 +2663,20 @@ where
     fn next(&mut self) -> Option<Self::Item> {
         match self {
             IterMut::Stack(iter) => iter.next(),
-            IterMut::Heap(iter) => iter.next(),
+            IterMut::Heap(iter, _) => iter.next(),
         }
     }
 }
 
 /// An iterator over references to the keys in an TinyMap
-pub enum Keys<'a, K, V> {
+pub enum Keys<'a, K, V, C = Natural> {
     #[doc(hidden)]
     Stack(crate::array_map::Keys<'a, K, V>),
     #[doc(hidden)]
-    Heap(btree_map::Keys<'a, K, V>),
+    Heap(Ordered<btree_map::Keys<'a, K, V>>, PhantomData<fn() -> C>),
 }
 
-impl<'a, K, V> Iterator for Keys<'a, K, V>
+impl<'a, K, V, C> Iterator for Keys<'a, K, V, C>
 where
     K: 'a,
 {
@@ -649,20 +2684,20 @@ where
     fn next(&mut self) -> Option<Self::Item> {
         match self {
             Keys::Stack(iter) => iter.next(),
-            Keys::Heap(iter) => iter.next(),
+            Keys::Heap(iter, _) => iter.next(),
         }
     }
 }
 
 /// An iterator over references to the values in an TinyMap
-pub enum Values<'a, K, V> {
+pub enum Values<'a, K, V, C = Natural> {
     #[doc(hidden)]
     Stack(crate::array_map::Values<'a, K, V>),
     #[doc(hidden)]
-    Heap(btree_map::Values<'a, K, V>),
+    Heap(Ordered<btree_map::Values<'a, K, V>>, PhantomData<fn() -> C>),
 }
 
-impl<'a, K, V> Iterator for Values<'a, K, V>
+impl<'a, K, V, C> Iterator for Values<'a, K, V, C>
 where
     V: 'a,
 {
@@ -670,20 +2705,23 @@ where
     fn next(&mut self) -> Option<Self::Item> {
         match self {
             Values::Stack(iter) => iter.next(),
-            Values::Heap(iter) => iter.next(),
+            Values::Heap(iter, _) => iter.next(),
         }
     }
 }
 
 /// An iterator over mutable references to the values in an TinyMap
-pub enum ValuesMut<'a, K, V> {
+pub enum ValuesMut<'a, K, V, C = Natural> {
     #[doc(hidden)]
     Stack(crate::array_map::ValuesMut<'a, K, V>),
     #[doc(hidden)]
-    Heap(btree_map::ValuesMut<'a, K, V>),
+    Heap(
+        Ordered<btree_map::ValuesMut<'a, K, V>>,
+        PhantomData<fn() -> C>,
+    ),
 }
 
-impl<'a, K, V> Iterator for ValuesMut<'a, K, V>
+impl<'a, K, V, C> Iterator for ValuesMut<'a, K, V, C>
 where
     V: 'a,
 {
@@ -691,26 +2729,362 @@ where
     fn next(&mut self) -> Option<Self::Item> {
         match self {
             ValuesMut::Stack(iter) => iter.next(),
-            ValuesMut::Heap(iter) => iter.next(),
+            ValuesMut::Heap(iter, _) => iter.next(),
+        }
+    }
+}
+
+/// An iterator over a tree that walks it from the back when the comparator reverses the keys' order
+#[doc(hidden)]
+pub struct Ordered<I> {
+    iter: I,
+    reversed: bool,
+}
+
+impl<I> Ordered<I> {
+    pub(crate) fn new(iter: I, reversed: bool) -> Self {
+        Ordered { iter, reversed }
+    }
+}
+
+impl<I> Iterator for Ordered<I>
+where
+    I: DoubleEndedIterator,
+{
+    type Item = I::Item;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.reversed {
+            self.iter.next_back()
+        } else {
+            self.iter.next()
+        }
+    }
+}
+
+/**
+A cursor over a TinyMap
+
+A cursor points at a gap between two entries of the map, or at the gap before the first entry
+or after the last one. It can move in either direction.
+
+Each step takes O(1) while the map is on the stack and O(log n) once it has moved to the heap.
+*/
+pub enum Cursor<'a, K, V, C = Natural, H = BTreeMap<K, V>> {
+    #[doc(hidden)]
+    Stack(crate::array_map::Cursor<'a, K, V>),
+    #[doc(hidden)]
+    Heap {
+        map: &'a H,
+        prev: Option<&'a K>,
+        order: PhantomData<fn() -> C>,
+    },
+}
+
+impl<'a, K, V, C, H> Clone for Cursor<'a, K, V, C, H> {
+    fn clone(&self) -> Self {
+        match self {
+            Cursor::Stack(cursor) => Cursor::Stack(cursor.clone()),
+            Cursor::Heap { map, prev, order } => Cursor::Heap {
+                map,
+                prev: *prev,
+                order: *order,
+            },
+        }
+    }
+}
+
+impl<'a, K, V, C, H> Cursor<'a, K, V, C, H>
+where
+    H: MapLookup<K, C, Key = K, Value = V>,
+{
+    /**
+    Advances the cursor to the next gap, returning the entry that it moved over
+
+    Returns `None` if the cursor is already at the end of the map.
+    */
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        match self {
+            Cursor::Stack(cursor) => cursor.next(),
+            Cursor::Heap { map, prev, .. } => {
+                let (key, value) = map.first_above(prev.map_or(Unbounded, Excluded))?;
+                *prev = Some(key);
+                Some((key, value))
+            }
+        }
+    }
+    /**
+    Moves the cursor to the previous gap, returning the entry that it moved over
+
+    Returns `None` if the cursor is already at the start of the map.
+    */
+    pub fn prev(&mut self) -> Option<(&'a K, &'a V)> {
+        match self {
+            Cursor::Stack(cursor) => cursor.prev(),
+            Cursor::Heap { map, prev, .. } => {
+                let (key, value) = map.get_key_value(prev.take()?)?;
+                *prev = map.last_below(Excluded(key)).map(|(k, _)| k);
+                Some((key, value))
+            }
+        }
+    }
+    /**
+    Returns the entry after the cursor without moving it
+    */
+    pub fn peek_next(&self) -> Option<(&'a K, &'a V)> {
+        match self {
+            Cursor::Stack(cursor) => cursor.peek_next(),
+            Cursor::Heap { map, prev, .. } => map.first_above(prev.map_or(Unbounded, Excluded)),
+        }
+    }
+    /**
+    Returns the entry before the cursor without moving it
+    */
+    pub fn peek_prev(&self) -> Option<(&'a K, &'a V)> {
+        match self {
+            Cursor::Stack(cursor) => cursor.peek_prev(),
+            Cursor::Heap { map, prev, .. } => map.get_key_value((*prev)?),
+        }
+    }
+}
+
+/**
+A cursor over a TinyMap that allows mutating values and inserting or removing entries
+
+A cursor points at a gap between two entries of the map, or at the gap before the first entry
+or after the last one. It can move in either direction.
+
+While the map is on the stack, the cursor keeps track of its position in the map's sort order,
+so each step is O(1) and each insertion or removal is O(n). Once the map has moved to the heap,
+the cursor keeps a clone of the key before it, and each operation is O(log n).
+Inserting through the cursor moves the map to the heap if it is full.
+*/
+pub struct CursorMut<
+    'a,
+    A,
+    C = Natural,
+    H = BTreeMap<<A as MapStorage>::Key, <A as MapStorage>::Value>,
+> where
+    A: MapArray,
+{
+    map: &'a mut TinyMap<A, C, H>,
+    // The position of the cursor while the map is on the stack
+    index: usize,
+    // The key before the cursor once the map is on the heap
+    prev: Option<A::Key>,
+}
+
+impl<'a, A, C, H> CursorMut<'a, A, C, H>
+where
+    A: MapArray,
+    A::Key: Clone,
+    C: Compare<A::Key>,
+    H: MapLookup<A::Key, C, Key = A::Key, Value = A::Value>,
+{
+    /**
+    Advances the cursor to the next gap, returning the entry that it moved over
+
+    Returns `None` if the cursor is already at the end of the map.
+    */
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<(&A::Key, &mut A::Value)> {
+        match self.map {
+            TinyMap::Stack(map) => {
+                let item = map.get_index_mut(self.index)?;
+                self.index += 1;
+                Some(item)
+            }
+            TinyMap::Heap(map) => {
+                let bound = self.prev.as_ref().map_or(Unbounded, Excluded);
+                let (key, value) = map.first_above_mut(bound)?;
+                self.prev = Some(key.clone());
+                Some((key, value))
+            }
+        }
+    }
+    /**
+    Moves the cursor to the previous gap, returning the entry that it moved over
+
+    Returns `None` if the cursor is already at the start of the map.
+    */
+    pub fn prev(&mut self) -> Option<(&A::Key, &mut A::Value)> {
+        match self.map {
+            TinyMap::Stack(map) => {
+                let index = self.index.checked_sub(1)?;
+                self.index = index;
+                map.get_index_mut(index)
+            }
+            TinyMap::Heap(map) => {
+                let key = self.prev.take()?;
+                self.prev = map.last_below(Excluded(&key)).map(|(k, _)| k.clone());
+                map.get_key_value_mut(&key)
+            }
+        }
+    }
+    /**
+    Returns the entry after the cursor without moving it
+    */
+    pub fn peek_next(&mut self) -> Option<(&A::Key, &mut A::Value)> {
+        match self.map {
+            TinyMap::Stack(map) => map.get_index_mut(self.index),
+            TinyMap::Heap(map) => {
+                map.first_above_mut(self.prev.as_ref().map_or(Unbounded, Excluded))
+            }
+        }
+    }
+    /**
+    Returns the entry before the cursor without moving it
+    */
+    pub fn peek_prev(&mut self) -> Option<(&A::Key, &mut A::Value)> {
+        match self.map {
+            TinyMap::Stack(map) => map.get_index_mut(self.index.checked_sub(1)?),
+            TinyMap::Heap(map) => map.get_key_value_mut(self.prev.as_ref()?),
+        }
+    }
+    /**
+    Returns a read-only cursor pointing at the same gap
+    */
+    pub fn as_cursor(&self) -> Cursor<'_, A::Key, A::Value, C, H> {
+        match &*self.map {
+            TinyMap::Stack(map) => Cursor::Stack(crate::array_map::Cursor {
+                entries: map.as_slice(),
+                index: self.index,
+            }),
+            TinyMap::Heap(map) => Cursor::Heap {
+                map,
+                prev: self
+                    .prev
+                    .as_ref()
+                    .and_then(|key| map.get_key_value(key))
+                    .map(|(k, _)| k),
+                order: PhantomData,
+            },
+        }
+    }
+    /**
+    Removes the entry after the cursor and returns it
+
+    The cursor stays in the same gap.
+    */
+    pub fn remove_next(&mut self) -> Option<(A::Key, A::Value)> {
+        match self.map {
+            TinyMap::Stack(map) => map.remove_index(self.index),
+            TinyMap::Heap(map) => {
+                let bound = self.prev.as_ref().map_or(Unbounded, Excluded);
+                let key = map.first_above(bound)?.0.clone();
+                map.remove_entry(&key)
+            }
+        }
+    }
+    /**
+    Removes the entry before the cursor and returns it
+
+    The cursor moves back to the gap that the removed entry used to follow.
+    */
+    pub fn remove_prev(&mut self) -> Option<(A::Key, A::Value)> {
+        match self.map {
+            TinyMap::Stack(map) => {
+                let index = self.index.checked_sub(1)?;
+                self.index = index;
+                map.remove_index(index)
+            }
+            TinyMap::Heap(map) => {
+                let key = self.prev.take()?;
+                self.prev = map.last_below(Excluded(&key)).map(|(k, _)| k.clone());
+                map.remove_entry(&key)
+            }
+        }
+    }
+    fn fits(&self, key: &A::Key) -> bool {
+        let heap_cmp;
+        let (prev, next, cmp) = match &*self.map {
+            TinyMap::Stack(map) => (
+                self.index
+                    .checked_sub(1)
+                    .and_then(|index| map.get_index(index))
+                    .map(|(k, _)| k),
+                map.get_index(self.index).map(|(k, _)| k),
+                map.comparator(),
+            ),
+            TinyMap::Heap(map) => {
+                heap_cmp = map.comparator();
+                (
+                    self.prev.as_ref(),
+                    map.first_above(self.prev.as_ref().map_or(Unbounded, Excluded))
+                        .map(|(k, _)| k),
+                    &heap_cmp,
+                )
+            }
+        };
+        prev.map_or(Ordering::Less, |prev| cmp.compare(prev, key)) == Ordering::Less
+            && next.map_or(Ordering::Less, |next| cmp.compare(key, next)) == Ordering::Less
+    }
+    /**
+    Inserts a key-value pair into the gap the cursor points at, leaving the cursor before it
+
+    # Errors
+
+    If the key does not sort strictly between the entries around the cursor, this function
+    returns an error containing the key-value pair that could not be inserted.
+    */
+    pub fn insert_after(&mut self, key: A::Key, value: A::Value) -> Result<(), (A::Key, A::Value)> {
+        if !self.fits(&key) {
+            return Err((key, value));
         }
+        let (key, value) = match &mut *self.map {
+            TinyMap::Stack(map) => match map.try_insert_index(key, value, Some(self.index)) {
+                Ok(_) => return Ok(()),
+                Err(pair) => {
+                    // The map is about to move to the heap, where the cursor tracks a key instead
+                    self.prev = self
+                        .index
+                        .checked_sub(1)
+                        .and_then(|index| map.get_index(index))
+                        .map(|(k, _)| k.clone());
+                    pair
+                }
+            },
+            TinyMap::Heap(_) => (key, value),
+        };
+        self.map.insert(key, value);
+        Ok(())
+    }
+    /**
+    Inserts a key-value pair into the gap the cursor points at, leaving the cursor after it
+
+    # Errors
+
+    If the key does not sort strictly between the entries around the cursor, this function
+    returns an error containing the key-value pair that could not be inserted.
+    */
+    pub fn insert_before(
+        &mut self,
+        key: A::Key,
+        value: A::Value,
+    ) -> Result<(), (A::Key, A::Value)> {
+        self.insert_after(key, value)?;
+        self.next();
+        Ok(())
     }
 }
 
 /// A view into a single entry in a map, which may either be vacant or occupied.
-pub enum Entry<'a, A>
+pub enum Entry<'a, A, C = Natural, H = BTreeMap<<A as MapStorage>::Key, <A as MapStorage>::Value>>
 where
     A: MapArray,
+    H: MapHeap<C, Key = A::Key, Value = A::Value> + 'a,
 {
     /// An entry into a map on the stack
-    Stack(crate::array_map::Entry<'a, A>),
-    /// An entry into a map on the heap
-    Heap(btree_map::Entry<'a, A::Key, A::Value>),
+    Stack(crate::array_map::Entry<'a, A, C>),
+    /// An entry into a map on the heap, and the length of the map
+    Heap(H::Entry<'a>, usize),
 }
 
-impl<'a, A> Entry<'a, A>
+impl<'a, A, C, H> Entry<'a, A, C, H>
 where
     A: MapArray,
-    A::Key: Ord,
+    C: Compare<A::Key>,
+    H: MapHeap<C, Key = A::Key, Value = A::Value> + 'a,
 {
     /**
     Ensures a value is in the entry by inserting the default if empty,
@@ -732,9 +3106,11 @@ where
     ```
     */
     pub fn or_insert(self, default: A::Value) -> &'a mut A::Value {
+        #[cfg(feature = "stats")]
+        self.record_insert();
         match self {
             Entry::Stack(entry) => entry.or_insert(default),
-            Entry::Heap(entry) => entry.or_insert(default),
+            Entry::Heap(entry, _) => H::entry_or_insert_with(entry, || default),
         }
     }
     /**
@@ -762,9 +3138,11 @@ where
     where
         F: FnOnce() -> A::Value,
     {
+        #[cfg(feature = "stats")]
+        self.record_insert();
         match self {
             Entry::Stack(entry) => entry.or_insert_with(default),
-            Entry::Heap(entry) => entry.or_insert_with(default),
+            Entry::Heap(entry, _) => H::entry_or_insert_with(entry, default),
         }
     }
     /**
@@ -782,7 +3160,7 @@ where
     pub fn key(&self) -> &A::Key {
         match self {
             Entry::Stack(entry) => entry.key(),
-            Entry::Heap(entry) => entry.key(),
+            Entry::Heap(entry, _) => H::entry_key(entry),
         }
     }
     /**
@@ -806,22 +3184,33 @@ where
     assert_eq!(map["poneyland"], 43);
     ```
     */
-    pub fn and_modify<F>(self, f: F) -> Entry<'a, A>
+    pub fn and_modify<F>(self, f: F) -> Entry<'a, A, C, H>
     where
         F: FnOnce(&mut A::Value),
     {
         match self {
             Entry::Stack(entry) => Entry::Stack(entry.and_modify(f)),
-            Entry::Heap(entry) => Entry::Heap(entry.and_modify(f)),
+            Entry::Heap(entry, len) => Entry::Heap(H::entry_and_modify(entry, f), len),
         }
     }
+    /// Records the length that inserting into the entry grows the map to, if the entry is vacant
+    #[cfg(feature = "stats")]
+    fn record_insert(&self) {
+        let len = match self {
+            Entry::Stack(crate::array_map::Entry::Vacant(entry)) => entry.map_len(),
+            Entry::Heap(entry, len) if H::entry_is_vacant(entry) => *len,
+            _ => return,
+        };
+        crate::stats::record_len::<TinyMap<A, C, H>>(A::CAPACITY, len + 1);
+    }
 }
 
-impl<'a, A> Entry<'a, A>
+impl<'a, A, C, H> Entry<'a, A, C, H>
 where
     A: MapArray,
-    A::Key: Ord,
     A::Value: Default,
+    C: Compare<A::Key>,
+    H: MapHeap<C, Key = A::Key, Value = A::Value> + 'a,
 {
     /**
     Ensures a value is in the entry by inserting the default value if empty,
@@ -843,3 +3232,135 @@ where
         self.or_insert_with(Default::default)
     }
 }
+
+/**
+A guard that gives mutable access to a key in a TinyMap
+
+Created with [`TinyMap::get_key_mut`](enum.TinyMap.html#method.get_key_mut).
+When the guard is dropped, the entry is moved to wherever its key now sorts, replacing
+any other entry whose key it has become equal to.
+*/
+pub enum KeyMut<'a, A, C = Natural, H = BTreeMap<<A as MapStorage>::Key, <A as MapStorage>::Value>>
+where
+    A: MapArray,
+    C: Compare<A::Key>,
+    H: MapLookup<A::Key, C, Key = A::Key, Value = A::Value>,
+{
+    #[doc(hidden)]
+    Stack(crate::array_map::KeyMut<'a, A, C>),
+    #[doc(hidden)]
+    Heap(HeapKeyMut<'a, A::Key, A::Value, C, H>),
+}
+
+impl<'a, A, C, H> KeyMut<'a, A, C, H>
+where
+    A: MapArray,
+    C: Compare<A::Key>,
+    H: MapLookup<A::Key, C, Key = A::Key, Value = A::Value>,
+{
+    /**
+    Moves the entry to wherever its key now sorts
+
+    # Errors
+
+    If the key has become equal to another key in the map, the entry with the changed key is
+    removed from the map and returned, leaving the other entry in place.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut map = TinyMap::<[Inner<(i32, &str)>; 2]>::new();
+    map.insert(1, "a");
+    map.insert(2, "b");
+    map.insert(3, "c");
+
+    let mut key = map.get_key_mut(&1).unwrap();
+    *key = 2;
+    assert_eq!(key.finish(), Err((2, "a")));
+    assert_eq!(map.len(), 2);
+    assert_eq!(map[&2], "b");
+    ```
+    */
+    pub fn finish(self) -> Result<(), (A::Key, A::Value)> {
+        match self {
+            KeyMut::Stack(key) => key.finish(),
+            KeyMut::Heap(key) => key.finish(),
+        }
+    }
+}
+
+#[doc(hidden)]
+pub struct HeapKeyMut<'a, K, V, C, H>
+where
+    H: MapLookup<K, C, Key = K, Value = V>,
+{
+    map: &'a mut H,
+    /// The entry taken out of the heap, until it is put back
+    entry: Option<(K, V)>,
+    order: PhantomData<fn() -> C>,
+}
+
+impl<K, V, C, H> HeapKeyMut<'_, K, V, C, H>
+where
+    H: MapLookup<K, C, Key = K, Value = V>,
+{
+    fn key(&self) -> &K {
+        &self.entry.as_ref().unwrap().0
+    }
+    fn key_mut(&mut self) -> &mut K {
+        &mut self.entry.as_mut().unwrap().0
+    }
+    fn finish(mut self) -> Result<(), (K, V)> {
+        let (key, value) = self.entry.take().unwrap();
+        if self.map.get_key_value(&key).is_some() {
+            return Err((key, value));
+        }
+        self.map.insert(key, value);
+        Ok(())
+    }
+}
+
+impl<K, V, C, H> Drop for HeapKeyMut<'_, K, V, C, H>
+where
+    H: MapLookup<K, C, Key = K, Value = V>,
+{
+    fn drop(&mut self) {
+        if let Some((key, value)) = self.entry.take() {
+            // Put the entry back before dropping the one it replaces
+            let replaced = self.map.remove_entry(&key);
+            self.map.insert(key, value);
+            drop(replaced);
+        }
+    }
+}
+
+impl<'a, A, C, H> Deref for KeyMut<'a, A, C, H>
+where
+    A: MapArray,
+    C: Compare<A::Key>,
+    H: MapLookup<A::Key, C, Key = A::Key, Value = A::Value>,
+{
+    type Target = A::Key;
+    fn deref(&self) -> &Self::Target {
+        match self {
+            KeyMut::Stack(key) => key,
+            KeyMut::Heap(key) => key.key(),
+        }
+    }
+}
+
+impl<'a, A, C, H> DerefMut for KeyMut<'a, A, C, H>
+where
+    A: MapArray,
+    C: Compare<A::Key>,
+    H: MapLookup<A::Key, C, Key = A::Key, Value = A::Value>,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self {
+            KeyMut::Stack(key) => key,
+            KeyMut::Heap(key) => key.key_mut(),
+        }
+    }
+}
@@ -1,36 +1,513 @@
 //! A set that starts on the stack but can automatically move to the heap
 
-use core::{borrow::Borrow, fmt, iter::FromIterator, mem::swap};
-use std::collections::BTreeSet;
+use core::{
+    borrow::Borrow,
+    cmp::Ordering,
+    fmt,
+    iter::FromIterator,
+    marker::PhantomData,
+    mem::{size_of, swap, take},
+    ops::{
+        Bound::{Excluded, Unbounded},
+        Deref, DerefMut,
+    },
+};
+#[cfg(feature = "allocator_api")]
+use std::alloc::Allocator;
+use std::collections::{btree_set, BTreeSet};
 
-use crate::{Array, ArraySet};
+use crate::{
+    array_set::VecSet,
+    compare::{Comparable, Compare, Natural, Search, TreeOrder},
+    heap_size::btree_nodes,
+    join::{Join, JoinItem, LeftJoin, OuterJoin},
+    sealed::Sealed,
+    tiny_map::Ordered,
+    Array, ArraySet, Inner, Storage,
+};
 
 /**
 A set that starts on the stack but can automatically move to the heap
+
+Values are ordered by the comparator `C`, which defaults to their `Ord` implementation.
+Once the values no longer fit in the array, they move into the heap `H`, which defaults to a BTreeSet.
+A tree can only keep the order of comparators that implement [`TreeOrder`](../compare/trait.TreeOrder.html),
+so a TinySet with any other comparator, such as one that carries state, moves into a
+[`VecSet`](../array_set/type.VecSet.html) instead. See [`SetHeap`](trait.SetHeap.html) and the
+[`compare`](../compare/index.html) module for details.
+
+A [`TinyVecSet`](../array_set/type.TinyVecSet.html) also moves into a sorted `Vec`, but keeps the whole
+API of an [`ArraySet`](../array_set/struct.ArraySet.html) on both sides of the move.
+
+Equality lookups such as [`contains`](#method.contains) and [`remove`](#method.remove) accept any
+[`Comparable`](../compare/trait.Comparable.html) query. Once the set has moved to a `BTreeSet`, a query
+that the values cannot be borrowed as is found by scanning the tree in O(n). Lookups by position, such as
+[`rank`](#method.rank), need borrowed queries on a tree.
+
+With the `allocator_api` feature, the heap can also be a `BTreeSet` in another allocator.
+See [`new_in`](#method.new_in).
+
+# Example
+
+```
+use std::cmp::Ordering;
+use tinymap::{compare::{from_fn, FromFn}, *};
+
+type ByLen = FromFn<fn(&&str, &&str) -> Ordering>;
+
+let by_len: ByLen = from_fn(|a, b| a.len().cmp(&b.len()));
+let mut set = TinySet::<[Inner<&str>; 2], _, VecSet<_, _>>::with_comparator(by_len);
+set.insert("ccc");
+set.insert("a");
+set.insert("bb");
+assert!(set.is_spilled());
+assert_eq!(set.iter().copied().collect::<Vec<_>>(), ["a", "bb", "ccc"]);
+assert!(set.contains(&"zz"));
+```
 */
-pub enum TinySet<A>
+pub enum TinySet<A, C = Natural, H = BTreeSet<<A as Storage>::Item>>
 where
     A: Array,
 {
     /// A set with items on the stack
-    Stack(ArraySet<A>),
+    Stack(ArraySet<A, C>),
     /// A set with items on the heap
-    Heap(BTreeSet<A::Item>),
+    Heap(H),
+}
+
+/**
+The storage that a TinySet moves its values into once they no longer fit in its array
+
+This is implemented for `BTreeSet`, which is the default, and for [`VecSet`](../array_set/type.VecSet.html).
+With the `allocator_api` feature, it is implemented for trees in any allocator that implements `Default`,
+which is where a set that moves from the stack creates its tree.
+A tree orders its values with their `Ord` implementation and has no room for a comparator, so it only
+works with comparators that implement [`TreeOrder`](../compare/trait.TreeOrder.html). A VecSet keeps
+the set's comparator alongside its values, so it works with any comparator that can be cloned.
+
+Lookups by position, such as [`TinySet::get_index`](enum.TinySet.html#method.get_index) and
+[`TinySet::rank`](enum.TinySet.html#method.rank), are O(n) in a tree and O(log n) or O(1) in a VecSet.
+Insertions and removals are O(log n) in a tree and O(n) in a VecSet.
+
+This trait is sealed and cannot be implemented outside of this crate.
+*/
+#[allow(clippy::len_without_is_empty)]
+pub trait SetHeap<C>: Sealed + Sized {
+    /// The type of the values
+    type Item;
+    #[doc(hidden)]
+    type IntoIter: Iterator<Item = Self::Item>;
+    #[doc(hidden)]
+    fn from_stack<A>(set: &mut ArraySet<A, C>) -> Self
+    where
+        A: Array<Item = Self::Item>;
+    #[doc(hidden)]
+    fn comparator(&self) -> C;
+    #[doc(hidden)]
+    fn allocation_size(&self) -> usize;
+    #[doc(hidden)]
+    fn len(&self) -> usize;
+    #[doc(hidden)]
+    fn insert(&mut self, value: Self::Item) -> bool;
+    #[doc(hidden)]
+    fn replace(&mut self, value: Self::Item) -> Option<Self::Item>;
+    /// Inserts a value that is not in the set yet and returns a reference to it
+    #[doc(hidden)]
+    fn insert_new(&mut self, value: Self::Item) -> &Self::Item;
+    #[doc(hidden)]
+    fn append(&mut self, other: &mut Self);
+    #[doc(hidden)]
+    fn iter(&self) -> Iter<'_, Self::Item, C>;
+    #[doc(hidden)]
+    fn into_values(self) -> Self::IntoIter;
+    #[doc(hidden)]
+    fn get_index(&self, index: usize) -> Option<&Self::Item>;
+    #[doc(hidden)]
+    fn partition_point<P>(&self, pred: P) -> usize
+    where
+        P: FnMut(&Self::Item) -> bool;
+    #[doc(hidden)]
+    fn remove_index(&mut self, index: usize) -> Option<Self::Item>
+    where
+        Self::Item: Clone;
+}
+
+/**
+A [`SetHeap`](trait.SetHeap.html) that can find the value that is equal to a query of type `Q`
+
+A `BTreeSet` accepts every [`Comparable`](../compare/trait.Comparable.html) query. It finds queries that
+its values can be borrowed as in O(log n) and scans its values in O(n) for any other query. A
+[`VecSet`](../array_set/type.VecSet.html) accepts every query that the set's comparator can compare with its values.
+
+This trait is sealed and cannot be implemented outside of this crate.
+*/
+pub trait SetGet<Q: ?Sized, C>: SetHeap<C> {
+    #[doc(hidden)]
+    fn get(&self, value: &Q) -> Option<&Self::Item>;
+    #[doc(hidden)]
+    fn take(&mut self, value: &Q) -> Option<Self::Item>;
+}
+
+/**
+A [`SetGet`](trait.SetGet.html) that can also look up values by their position relative to a query of type `Q`
+
+A `BTreeSet` accepts the queries that its values can be borrowed as. A
+[`VecSet`](../array_set/type.VecSet.html) accepts every query that the set's comparator can compare with its values.
+
+This trait is sealed and cannot be implemented outside of this crate.
+*/
+pub trait SetLookup<Q: ?Sized, C>: SetGet<Q, C> {
+    /// Counts the values that are below the given one in the comparator's order
+    #[doc(hidden)]
+    fn count_below(&self, value: &Q) -> usize;
+    /// Splits off the values that are not below the given one in the comparator's order
+    #[doc(hidden)]
+    fn split_off(&mut self, value: &Q) -> Self;
+}
+
+/// Builds a tree from values that are already sorted
+#[cfg(not(feature = "allocator_api"))]
+fn sorted_tree<T: Ord>(values: Vec<T>) -> BTreeSet<T> {
+    // The tree is built in linear time without searching
+    values.into_iter().collect()
+}
+
+/// Builds a tree in the default allocator from values that are already sorted
+#[cfg(feature = "allocator_api")]
+fn sorted_tree<T, Al>(values: Vec<T>) -> BTreeSet<T, Al>
+where
+    T: Ord,
+    Al: Allocator + Clone + Default,
+{
+    let mut tree = BTreeSet::new_in(Al::default());
+    tree.extend(values);
+    tree
+}
+
+/// Merges two trees in a single pass, keeping the values from `theirs` that are in both
+#[cfg(not(feature = "allocator_api"))]
+fn append_trees<T: Ord>(ours: &mut BTreeSet<T>, theirs: &mut BTreeSet<T>) {
+    let mut ours_iter = take(ours).into_iter().peekable();
+    let mut theirs_iter = take(theirs).into_iter().peekable();
+    // Both sides are sorted, so the tree is built in linear time without searching
+    *ours = core::iter::from_fn(|| match (ours_iter.peek(), theirs_iter.peek()) {
+        (Some(a), Some(b)) => match a.cmp(b) {
+            Ordering::Less => ours_iter.next(),
+            Ordering::Greater => theirs_iter.next(),
+            Ordering::Equal => {
+                ours_iter.next();
+                theirs_iter.next()
+            }
+        },
+        (Some(_), None) => ours_iter.next(),
+        (None, _) => theirs_iter.next(),
+    })
+    .collect();
+}
+
+/// Moves the values of `theirs` into `ours`, replacing the values that are in both
+///
+/// Trees in an allocator cannot be collected into, so the values are moved one at a time.
+#[cfg(feature = "allocator_api")]
+fn append_trees<T, Al>(ours: &mut BTreeSet<T, Al>, theirs: &mut BTreeSet<T, Al>)
+where
+    T: Ord,
+    Al: Allocator + Clone,
+{
+    while let Some(value) = theirs.pop_first() {
+        ours.replace(value);
+    }
+}
+
+/// Finds the value of a tree that is equal to a query
+struct Get<'a, T>(&'a T);
+
+/// Removes the value of a tree that is equal to a query
+struct Take<'a, T>(&'a mut T);
+
+/// Implements the heap traits for `BTreeSet`, in any allocator with the `allocator_api` feature
+macro_rules! btree_set_heap {
+    ($($al:ident)?) => {
+        impl<T $(, $al: Allocator + Clone)?> Sealed for BTreeSet<T $(, $al)?> {}
+
+        impl<T, C $(, $al: Allocator + Clone + Default)?> SetHeap<C> for BTreeSet<T $(, $al)?>
+        where
+            T: Ord,
+            C: TreeOrder<T>,
+        {
+            type Item = T;
+            type IntoIter = Ordered<btree_set::IntoIter<T $(, $al)?>>;
+
+            fn from_stack<A>(set: &mut ArraySet<A, C>) -> Self
+            where
+                A: Array<Item = T>,
+            {
+                let mut values = Vec::from(take(set));
+                if C::REVERSED {
+                    values.reverse();
+                }
+                sorted_tree(values)
+            }
+            fn comparator(&self) -> C {
+                C::default()
+            }
+            fn allocation_size(&self) -> usize {
+                btree_nodes::<T, ()>(BTreeSet::len(self))
+            }
+            fn len(&self) -> usize {
+                BTreeSet::len(self)
+            }
+            fn insert(&mut self, value: T) -> bool {
+                BTreeSet::insert(self, value)
+            }
+            fn replace(&mut self, value: T) -> Option<T> {
+                BTreeSet::replace(self, value)
+            }
+            fn insert_new(&mut self, value: T) -> &T {
+                let index = self.range((Unbounded, Excluded(&value))).count();
+                BTreeSet::insert(self, value);
+                BTreeSet::iter(self).nth(index).unwrap()
+            }
+            fn append(&mut self, other: &mut Self) {
+                append_trees(self, other);
+            }
+            fn iter(&self) -> Iter<'_, T, C> {
+                Iter::Heap(Ordered::new(BTreeSet::iter(self), C::REVERSED), PhantomData)
+            }
+            fn into_values(self) -> Self::IntoIter {
+                Ordered::new(IntoIterator::into_iter(self), C::REVERSED)
+            }
+            fn get_index(&self, index: usize) -> Option<&T> {
+                Ordered::new(BTreeSet::iter(self), C::REVERSED).nth(index)
+            }
+            fn partition_point<P>(&self, mut pred: P) -> usize
+            where
+                P: FnMut(&T) -> bool,
+            {
+                Ordered::new(BTreeSet::iter(self), C::REVERSED)
+                    .take_while(|value| pred(value))
+                    .count()
+            }
+            fn remove_index(&mut self, index: usize) -> Option<T>
+            where
+                T: Clone,
+            {
+                let value = Ordered::new(BTreeSet::iter(self), C::REVERSED)
+                    .nth(index)?
+                    .clone();
+                BTreeSet::take(self, &value)
+            }
+        }
+
+        impl<'a, T $(, $al: Allocator + Clone)?> Search<T> for Get<'a, BTreeSet<T $(, $al)?>>
+        where
+            T: Ord,
+        {
+            type Output = Option<&'a T>;
+            fn find<U>(self, query: &U) -> Self::Output
+            where
+                U: Ord + ?Sized,
+                T: Borrow<U>,
+            {
+                BTreeSet::get(self.0, query)
+            }
+            fn scan<Q>(self, query: &Q) -> Self::Output
+            where
+                Q: Comparable<T> + ?Sized,
+            {
+                // The query agrees with the values' order, so the scan stops at the first value that is not below it
+                BTreeSet::iter(self.0)
+                    .find(|value| query.compare(value) != Ordering::Greater)
+                    .filter(|value| query.compare(value) == Ordering::Equal)
+            }
+        }
+
+        impl<'a, T $(, $al: Allocator + Clone)?> Search<T> for Take<'a, BTreeSet<T $(, $al)?>>
+        where
+            T: Ord,
+        {
+            type Output = Option<T>;
+            fn find<U>(self, query: &U) -> Self::Output
+            where
+                U: Ord + ?Sized,
+                T: Borrow<U>,
+            {
+                BTreeSet::take(self.0, query)
+            }
+            fn scan<Q>(self, query: &Q) -> Self::Output
+            where
+                Q: Comparable<T> + ?Sized,
+            {
+                // A tree can only remove the values it is given, so the values below the query are
+                // taken off its front until the query is reached and put back afterwards
+                let mut below = Vec::new();
+                let mut found = None;
+                while let Some(value) = self.0.pop_first() {
+                    match query.compare(&value) {
+                        Ordering::Greater => below.push(value),
+                        Ordering::Equal => {
+                            found = Some(value);
+                            break;
+                        }
+                        Ordering::Less => {
+                            below.push(value);
+                            break;
+                        }
+                    }
+                }
+                self.0.extend(below);
+                found
+            }
+        }
+
+        impl<T, Q, C $(, $al: Allocator + Clone + Default)?> SetGet<Q, C> for BTreeSet<T $(, $al)?>
+        where
+            T: Ord,
+            Q: Comparable<T> + ?Sized,
+            C: TreeOrder<T>,
+        {
+            fn get(&self, value: &Q) -> Option<&T> {
+                value.search(Get(self))
+            }
+            fn take(&mut self, value: &Q) -> Option<T> {
+                value.search(Take(self))
+            }
+        }
+
+        impl<T, Q, C $(, $al: Allocator + Clone + Default)?> SetLookup<Q, C> for BTreeSet<T $(, $al)?>
+        where
+            T: Borrow<Q> + Ord,
+            Q: Ord + ?Sized,
+            C: TreeOrder<T>,
+        {
+            fn count_below(&self, value: &Q) -> usize {
+                if C::REVERSED {
+                    self.range::<Q, _>((Excluded(value), Unbounded)).count()
+                } else {
+                    self.range::<Q, _>((Unbounded, Excluded(value))).count()
+                }
+            }
+            fn split_off(&mut self, value: &Q) -> Self {
+                let mut other = BTreeSet::split_off(self, value);
+                if C::REVERSED {
+                    // In a reversed order, the given value and the ones below it are split off
+                    if let Some(value) = other.take(value) {
+                        BTreeSet::insert(self, value);
+                    }
+                    swap(self, &mut other);
+                }
+                other
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "allocator_api"))]
+btree_set_heap!();
+#[cfg(feature = "allocator_api")]
+btree_set_heap!(Al);
+
+impl<T, C> Sealed for VecSet<T, C> {}
+
+impl<T, C> SetHeap<C> for VecSet<T, C>
+where
+    C: Compare<T> + Clone,
+{
+    type Item = T;
+    type IntoIter = crate::array_set::IntoIter<T>;
+
+    fn from_stack<A>(set: &mut ArraySet<A, C>) -> Self
+    where
+        A: Array<Item = T>,
+    {
+        VecSet::take_from(set)
+    }
+    fn comparator(&self) -> C {
+        ArraySet::comparator(self).clone()
+    }
+    fn allocation_size(&self) -> usize {
+        Storage::heap_capacity(self.storage()) * size_of::<Inner<T>>()
+    }
+    fn len(&self) -> usize {
+        ArraySet::len(self)
+    }
+    fn insert(&mut self, value: T) -> bool {
+        ArraySet::insert(self, value)
+    }
+    fn replace(&mut self, value: T) -> Option<T> {
+        ArraySet::replace(self, value)
+    }
+    fn insert_new(&mut self, value: T) -> &T {
+        ArraySet::get_or_insert(self, value)
+    }
+    fn append(&mut self, other: &mut Self) {
+        ArraySet::append(self, other)
+    }
+    fn iter(&self) -> Iter<'_, T, C> {
+        Iter::Stack(ArraySet::iter(self))
+    }
+    fn into_values(self) -> Self::IntoIter {
+        IntoIterator::into_iter(self)
+    }
+    fn get_index(&self, index: usize) -> Option<&T> {
+        ArraySet::get_index(self, index)
+    }
+    fn partition_point<P>(&self, pred: P) -> usize
+    where
+        P: FnMut(&T) -> bool,
+    {
+        ArraySet::partition_point(self, pred)
+    }
+    fn remove_index(&mut self, index: usize) -> Option<T>
+    where
+        T: Clone,
+    {
+        ArraySet::remove_index(self, index)
+    }
+}
+
+impl<T, Q, C> SetGet<Q, C> for VecSet<T, C>
+where
+    Q: ?Sized,
+    C: Compare<T> + Compare<Q, T> + Clone,
+{
+    fn get(&self, value: &Q) -> Option<&T> {
+        ArraySet::get(self, value)
+    }
+    fn take(&mut self, value: &Q) -> Option<T> {
+        ArraySet::take(self, value)
+    }
+}
+
+impl<T, Q, C> SetLookup<Q, C> for VecSet<T, C>
+where
+    Q: ?Sized,
+    C: Compare<T> + Compare<Q, T> + Clone,
+{
+    fn count_below(&self, value: &Q) -> usize {
+        ArraySet::rank(self, value)
+    }
+    fn split_off(&mut self, value: &Q) -> Self {
+        ArraySet::split_off(self, value)
+    }
 }
 
-impl<A> Default for TinySet<A>
+impl<A, C, H> Default for TinySet<A, C, H>
 where
     A: Array,
+    C: Default,
 {
     fn default() -> Self {
         TinySet::Stack(ArraySet::default())
     }
 }
 
-impl<A> Clone for TinySet<A>
+impl<A, C, H> Clone for TinySet<A, C, H>
 where
     A: Array,
     A::Item: Clone,
+    C: Clone,
+    H: Clone,
 {
     fn clone(&self) -> Self {
         match self {
@@ -57,6 +534,61 @@ where
     pub fn new() -> Self {
         Self::default()
     }
+}
+
+impl<A, C, H> TinySet<A, C, H>
+where
+    A: Array,
+{
+    /**
+    Creates a new empty TinySet that orders its values with the given comparator
+
+    A comparator that implements [`TreeOrder`](../compare/trait.TreeOrder.html) can be used with the
+    default heap. Any other comparator needs a [`VecSet`](../array_set/type.VecSet.html) heap.
+
+    # Example
+    ```
+    use tinymap::{compare::from_fn, *};
+
+    let by_abs = from_fn(|a: &i32, b: &i32| a.abs().cmp(&b.abs()));
+    let mut set = TinySet::<[Inner<i32>; 2], _, VecSet<_, _>>::with_comparator(by_abs);
+    set.insert(-3);
+    set.insert(2);
+    set.insert(-1);
+    assert_eq!(set.iter().copied().collect::<Vec<_>>(), [-1, 2, -3]);
+    ```
+    */
+    pub fn with_comparator(cmp: C) -> Self {
+        TinySet::Stack(ArraySet::with_comparator(cmp))
+    }
+    /**
+    Creates a new empty TinySet that starts on the heap
+
+    This skips moving the values from the stack to the heap when the set is expected to grow beyond its capacity.
+
+    # Example
+    ```
+    use tinymap::*;
+
+    let mut set = TinySet::<[Inner<i32>; 2]>::with_heap();
+    assert!(set.is_spilled());
+    set.insert(1);
+    assert!(set.is_spilled());
+    ```
+    */
+    pub fn with_heap() -> Self
+    where
+        H: Default,
+    {
+        TinySet::Heap(H::default())
+    }
+}
+
+impl<A, C, H> TinySet<A, C, H>
+where
+    A: Array,
+    H: SetHeap<C, Item = A::Item>,
+{
     /**
     Clears the set, removing all elements
 
@@ -70,8 +602,11 @@ where
     assert!(v.is_empty());
     ```
     */
-    pub fn clear(&mut self) {
-        *self = Self::new();
+    pub fn clear(&mut self)
+    where
+        C: Clone,
+    {
+        *self = TinySet::Stack(ArraySet::with_comparator(self.comparator()));
     }
     /**
     Returns the number of elements in the set
@@ -93,6 +628,16 @@ where
             TinySet::Heap(set) => set.len(),
         }
     }
+    /// Returns a clone of the set's comparator, wherever its values are
+    fn comparator(&self) -> C
+    where
+        C: Clone,
+    {
+        match self {
+            TinySet::Stack(set) => set.comparator().clone(),
+            TinySet::Heap(set) => set.comparator(),
+        }
+    }
     /**
     Returns `true` if the set contains no elements
 
@@ -101,277 +646,1006 @@ where
     ```
     use tinymap::*;
 
-    let mut v = TinySet::<[Inner<i32>; 2]>::new();
-    assert!(v.is_empty());
-    v.insert(1);
-    assert!(!v.is_empty());
+    let mut v = TinySet::<[Inner<i32>; 2]>::new();
+    assert!(v.is_empty());
+    v.insert(1);
+    assert!(!v.is_empty());
+    ```
+    */
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /**
+    Returns `true` if the set has moved to the heap
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut set = TinySet::<[Inner<i32>; 2]>::new();
+    set.insert(1);
+    assert!(!set.is_spilled());
+    set.extend(vec![1, 2, 3]);
+    assert!(set.is_spilled());
+    ```
+    */
+    pub fn is_spilled(&self) -> bool {
+        matches!(self, TinySet::Heap(_))
+    }
+    /**
+    Returns the maximum number of elements the set can contain
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut a = TinySet::<[Inner<i32>; 2]>::new();
+    assert_eq!(2, a.capacity());
+    ```
+    */
+    pub fn capacity(&self) -> usize {
+        A::CAPACITY
+    }
+    /**
+    Gets an iterator over the entries of the set, sorted
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let set: TinySet<[Inner<i32>; 3]> = [3, 1, 2].iter().copied().collect();
+    let mut set_iter = set.iter();
+    assert_eq!(set_iter.next(), Some(&1));
+    assert_eq!(set_iter.next(), Some(&2));
+    assert_eq!(set_iter.next(), Some(&3));
+    assert_eq!(set_iter.next(), None);
+    ```
+    */
+    pub fn iter(&self) -> Iter<'_, A::Item, C> {
+        match self {
+            TinySet::Stack(set) => Iter::Stack(set.iter()),
+            TinySet::Heap(set) => set.iter(),
+        }
+    }
+    /**
+    Gets the set's values as a sorted slice, if the set is on the stack
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut set = TinySet::<[Inner<i32>; 2]>::new();
+    set.insert(2);
+    set.insert(1);
+    assert_eq!(set.as_stack_slice(), Some(&[1, 2][..]));
+
+    set.insert(3);
+    assert_eq!(set.as_stack_slice(), None);
+    ```
+    */
+    pub fn as_stack_slice(&self) -> Option<&[A::Item]> {
+        match self {
+            TinySet::Stack(set) => Some(set.as_slice()),
+            TinySet::Heap(_) => None,
+        }
+    }
+    /**
+    Returns a reference to the value at the given position in the set's sort order
+
+    This is O(1) while the set is on the stack or in a VecSet and O(n) once it has moved to a BTreeSet.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let set: TinySet<[Inner<_>; 3]> = [3, 1, 2].iter().copied().collect();
+    assert_eq!(set.get_index(0), Some(&1));
+    assert_eq!(set.get_index(2), Some(&3));
+    assert_eq!(set.get_index(3), None);
+    ```
+    */
+    pub fn get_index(&self, index: usize) -> Option<&A::Item> {
+        match self {
+            TinySet::Stack(set) => set.get_index(index),
+            TinySet::Heap(set) => set.get_index(index),
+        }
+    }
+    /**
+    Returns the position of the first value for which the predicate returns `false`
+
+    The set is assumed to be partitioned by the predicate, meaning that it returns `true`
+    for every value before some position and `false` for every value from that position onward.
+
+    This is O(log n) while the set is on the stack or in a VecSet and O(n) once it has moved to a BTreeSet.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let set: TinySet<[Inner<_>; 3]> = [1, 4, 9].iter().copied().collect();
+    assert_eq!(set.partition_point(|x| x * x < 20), 2);
+    ```
+    */
+    pub fn partition_point<P>(&self, pred: P) -> usize
+    where
+        P: FnMut(&A::Item) -> bool,
+    {
+        match self {
+            TinySet::Stack(set) => set.partition_point(pred),
+            TinySet::Heap(set) => set.partition_point(pred),
+        }
+    }
+}
+
+#[cfg(feature = "allocator_api")]
+impl<A, C, Al> TinySet<A, C, BTreeSet<A::Item, Al>>
+where
+    A: Array,
+    Al: Allocator + Clone,
+{
+    /**
+    Creates a new empty TinySet that starts on the heap in the given allocator
+
+    A set on the stack has nowhere to keep an allocator, so this set starts on the heap to use the given one.
+    A set that moves to the heap from the stack, for example after [`clear`](#method.clear),
+    creates its tree in `Al::default()`.
+
+    # Example
+    ```
+    #![feature(allocator_api, btreemap_alloc)]
+    use std::{alloc::System, collections::BTreeSet};
+    use tinymap::{compare::Natural, *};
+
+    let mut set = TinySet::<[Inner<i32>; 2], Natural, BTreeSet<i32, System>>::new_in(System);
+    assert!(set.is_spilled());
+    set.insert(1);
+    assert!(set.contains(&1));
+    ```
+    */
+    pub fn new_in(alloc: Al) -> Self {
+        TinySet::Heap(BTreeSet::new_in(alloc))
+    }
+}
+
+impl<A, C> TinySet<A, C>
+where
+    A: Array,
+{
+    /**
+    Converts the set into a `BTreeSet`
+
+    The tree is ordered by the values' `Ord` implementation, whatever the set's comparator.
+    Once the set has moved to the heap, its tree is handed over as it is.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut set = TinySet::<[Inner<i32>; 2]>::new();
+    set.insert(2);
+    set.insert(1);
+    set.insert(3);
+
+    let tree = set.into_btree_set();
+    assert_eq!(tree.iter().copied().collect::<Vec<_>>(), [1, 2, 3]);
+    ```
+    */
+    pub fn into_btree_set(self) -> BTreeSet<A::Item>
+    where
+        A::Item: Ord,
+    {
+        self.into()
+    }
+}
+
+impl<A, C, H> TinySet<A, C, H>
+where
+    A: Array,
+    C: Compare<A::Item>,
+    H: SetHeap<C, Item = A::Item>,
+{
+    /**
+    Inserts an value into the set
+
+    If the set did not have this value present, true is returned.
+
+    If the set did have this value present, `false` is returned, and the entry is not updated.
+
+    # Panics
+
+    Panics if insertion would cause the set to excede its capacity.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut set = TinySet::<[Inner<i32>; 2]>::new();
+
+    assert_eq!(set.insert(2), true);
+    assert_eq!(set.insert(2), false);
+    assert_eq!(set.len(), 1);
+    ```
+    */
+    pub fn insert(&mut self, value: A::Item) -> bool {
+        let res = match self {
+            TinySet::Stack(set) => match set.try_insert(value) {
+                Ok(res) => res,
+                Err(val) => self.heap_mut().insert(val),
+            },
+            TinySet::Heap(set) => set.insert(value),
+        };
+        #[cfg(feature = "stats")]
+        if res {
+            crate::stats::record_len::<Self>(A::CAPACITY, self.len());
+        }
+        res
+    }
+    /// Records the length that the set grew to from `len` in one operation
+    #[cfg(feature = "stats")]
+    fn record_growth(&self, len: usize) {
+        if self.len() > len {
+            crate::stats::record_len::<Self>(A::CAPACITY, self.len());
+        }
+    }
+    /// Moves the set to the heap if it is not there already
+    fn heap_mut(&mut self) -> &mut H {
+        if let TinySet::Stack(set) = self {
+            #[cfg(feature = "stats")]
+            crate::stats::record_spill::<Self>(A::CAPACITY);
+            let heap = H::from_stack(set);
+            *self = TinySet::Heap(heap);
+        }
+        match self {
+            TinySet::Heap(set) => set,
+            TinySet::Stack(_) => unreachable!(),
+        }
+    }
+    /**
+    Moves the set to the heap if it is not there already
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut set = TinySet::<[Inner<i32>; 2]>::new();
+    set.insert(1);
+    set.spill();
+    assert!(set.is_spilled());
+    assert_eq!(set.len(), 1);
+    ```
+    */
+    pub fn spill(&mut self) {
+        self.heap_mut();
+    }
+    /**
+    Moves the values into a set with a different array type
+
+    A set on the stack stays there if its values fit in the new array and moves to the heap otherwise.
+    A set on the heap stays there.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut set = TinySet::<[Inner<i32>; 4]>::new();
+    set.insert(1);
+    set.insert(2);
+
+    let set = set.resize_into::<[Inner<i32>; 2]>();
+    assert!(matches!(set, TinySet::Stack(_)));
+    let set = set.resize_into::<[Inner<i32>; 1]>();
+    assert!(matches!(set, TinySet::Heap(_)));
+    assert_eq!(set.len(), 2);
+
+    // An ArraySet can be moved into any TinySet, which spills only if it has to
+    let array: ArraySet<[Inner<_>; 4]> = [1, 2, 3].iter().copied().collect();
+    let tiny = TinySet::from(array).resize_into::<[Inner<i32>; 2]>();
+    assert!(matches!(tiny, TinySet::Heap(_)));
+    ```
+    */
+    pub fn resize_into<B>(self) -> TinySet<B, C, H>
+    where
+        B: Array<Item = A::Item>,
+    {
+        match self {
+            TinySet::Stack(set) => match set.try_convert() {
+                Ok(set) => TinySet::Stack(set),
+                Err(mut set) => TinySet::Heap(H::from_stack(&mut set)),
+            },
+            TinySet::Heap(set) => TinySet::Heap(set),
+        }
+    }
+    /**
+    Returns true if the set contains a value for the specified value
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let set: TinySet<[Inner<_>; 3]> = [1, 2, 3].iter().copied().collect();
+    assert_eq!(set.contains(&1), true);
+    assert_eq!(set.contains(&4), false);
+    ```
+    */
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Item>,
+        H: SetGet<Q, C>,
+    {
+        match self {
+            TinySet::Stack(set) => set.contains(value),
+            TinySet::Heap(set) => set.get(value).is_some(),
+        }
+    }
+    /**
+    Returns a reference to the value if it is present
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut set: TinySet<[Inner<_>; 3]> = [1, 2, 3].iter().copied().collect();
+    assert_eq!(set.get(&2), Some(&2));
+    assert_eq!(set.get(&4), None);
+    ```
+    */
+    pub fn get<Q>(&self, value: &Q) -> Option<&A::Item>
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Item>,
+        H: SetGet<Q, C>,
+    {
+        match self {
+            TinySet::Stack(set) => set.get(value),
+            TinySet::Heap(set) => set.get(value),
+        }
+    }
+    /**
+    Removes a value from the set. Returns whether the value was present in the set.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut set = TinySet::<[Inner<i32>; 2]>::new();
+
+    set.insert(2);
+    assert_eq!(set.remove(&2), true);
+    assert_eq!(set.remove(&2), false);
+    ```
+    */
+    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Item>,
+        H: SetGet<Q, C>,
+    {
+        match self {
+            TinySet::Stack(set) => set.remove(value),
+            TinySet::Heap(set) => set.take(value).is_some(),
+        }
+    }
+    /**
+    Removes and returns the value in the set, if any, that is equal to the given one
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut set: TinySet<[Inner<_>; 3]> = [1, 2, 3].iter().copied().collect();
+    assert_eq!(set.take(&2), Some(2));
+    assert_eq!(set.take(&2), None);
+    ```
+    */
+    pub fn take<Q>(&mut self, value: &Q) -> Option<A::Item>
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Item>,
+        H: SetGet<Q, C>,
+    {
+        match self {
+            TinySet::Stack(set) => set.take(value),
+            TinySet::Heap(set) => set.take(value),
+        }
+    }
+    /**
+    Returns a guard that gives mutable access to the value in the set that is equal to the given one
+
+    When the guard is dropped, the value is moved to wherever it now sorts. If it has become
+    equal to another value in the set, the changed value replaces the other one, as
+    [`replace`](#method.replace) would. Use [`KeyMut::finish`](enum.KeyMut.html#method.finish)
+    to get the value back instead.
+
+    Once the set has moved to the heap, the value is taken out of the tree while the guard is
+    alive and inserted again when it is dropped.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut set = TinySet::<[Inner<i32>; 2]>::new();
+    set.extend(vec![1, 2, 3]);
+
+    if let Some(mut value) = set.get_key_mut(&1) {
+        *value = 5;
+    }
+    assert_eq!(set.iter().copied().collect::<Vec<_>>(), [2, 3, 5]);
+    ```
+    */
+    pub fn get_key_mut<Q>(&mut self, value: &Q) -> Option<KeyMut<'_, A, C, H>>
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Item>,
+        H: SetGet<Q, C> + SetLookup<A::Item, C>,
+    {
+        match self {
+            TinySet::Stack(set) => set.get_key_mut(value).map(KeyMut::Stack),
+            TinySet::Heap(set) => {
+                let value = SetGet::<Q, C>::take(set, value)?;
+                Some(KeyMut::Heap(HeapKeyMut {
+                    set,
+                    value: Some(value),
+                    order: PhantomData,
+                }))
+            }
+        }
+    }
+    /**
+    Adds a value to the set, replacing the existing value, if any, that is equal to the given one
+
+    Returns the replaced value.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut set = TinySet::<[Inner<Vec<i32>>; 2]>::new();
+    set.insert(Vec::new());
+
+    assert_eq!(set.get(&[][..]).unwrap().capacity(), 0);
+    set.replace(Vec::with_capacity(10));
+    assert_eq!(set.get(&[][..]).unwrap().capacity(), 10);
+    ```
+    */
+    pub fn replace(&mut self, value: A::Item) -> Option<A::Item> {
+        match self {
+            TinySet::Stack(set) if set.contains(&value) => set.replace(value),
+            TinySet::Stack(_) => {
+                self.insert(value);
+                None
+            }
+            TinySet::Heap(set) => set.replace(value),
+        }
+    }
+    /**
+    Returns a reference to the value in the set that is equal to the given one,
+    inserting the result of `f` if there is none
+
+    # Panics
+
+    Panics if the value returned by `f` is not equal to the given one.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut set = TinySet::<[Inner<String>; 2]>::new();
+    assert_eq!(set.get_or_insert_with("pear", |s| s.to_owned()), "pear");
+    assert_eq!(set.len(), 1);
+    set.get_or_insert_with("pear", |_| unreachable!());
+    assert_eq!(set.len(), 1);
+    ```
+    */
+    pub fn get_or_insert_with<Q, F>(&mut self, value: &Q, f: F) -> &A::Item
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Item> + Clone,
+        H: SetGet<Q, C>,
+        F: FnOnce(&Q) -> A::Item,
+    {
+        if !self.contains(value) {
+            let new = f(value);
+            assert!(
+                self.comparator().compare(value, &new) == Ordering::Equal,
+                "New value is not equal to the given one"
+            );
+            self.insert(new);
+        }
+        self.get(value).unwrap()
+    }
+    /**
+    Returns a reference to the value in the set that is equal to the given one,
+    inserting the given one if there is none
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut set = TinySet::<[Inner<i32>; 2]>::new();
+    assert_eq!(set.get_or_insert(2), &2);
+    assert_eq!(set.get_or_insert(2), &2);
+    assert_eq!(set.len(), 1);
+    ```
+    */
+    pub fn get_or_insert(&mut self, value: A::Item) -> &A::Item
+    where
+        H: SetLookup<A::Item, C>,
+    {
+        self.entry(value).or_insert()
+    }
+    /**
+    Gets the given value's corresponding entry in the set for in-place manipulation
+
+    Inserting through a vacant entry moves the set to the heap if it is full.
+
+    # Example
+
+    ```
+    use tinymap::{tiny_set::Entry, *};
+
+    let mut set = TinySet::<[Inner<&str>; 2]>::new();
+
+    match set.entry("apple") {
+        Entry::Vacant(entry) => {
+            assert_eq!(entry.insert(), &"apple");
+        }
+        Entry::Occupied(_) => unreachable!(),
+    }
+    assert!(matches!(set.entry("apple"), Entry::Occupied(_)));
+
+    set.entry("pear").or_insert();
+    set.entry("plum").or_insert();
+    assert_eq!(set.len(), 3);
+    ```
+    */
+    pub fn entry(&mut self, value: A::Item) -> Entry<'_, A, C, H>
+    where
+        H: SetLookup<A::Item, C>,
+    {
+        let (occupied, index) = match self {
+            TinySet::Stack(set) => match set.index_of(&value) {
+                Some(index) => (true, index),
+                None => (false, set.rank(&value)),
+            },
+            TinySet::Heap(set) => (set.get(&value).is_some(), 0),
+        };
+        if occupied {
+            Entry::Occupied(OccupiedEntry {
+                set: self,
+                value,
+                index,
+            })
+        } else {
+            Entry::Vacant(VacantEntry {
+                set: self,
+                value,
+                index,
+            })
+        }
+    }
+    /**
+    Splits the set in two at the given value
+
+    Returns a set containing every value greater than or equal to the given value.
+    The smaller values stay in this set.
+
+    A set on the heap is split into two sets on the heap.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut a: TinySet<[Inner<_>; 5]> = [1, 2, 3, 17, 41].iter().copied().collect();
+    let b = a.split_off(&3);
+
+    assert_eq!(a.len(), 2);
+    assert_eq!(b.len(), 3);
+    assert!(b.contains(&17));
+    ```
+    */
+    pub fn split_off<Q>(&mut self, value: &Q) -> Self
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Item> + Clone,
+        H: SetLookup<Q, C>,
+    {
+        match self {
+            TinySet::Stack(set) => TinySet::Stack(set.split_off(value)),
+            TinySet::Heap(set) => TinySet::Heap(set.split_off(value)),
+        }
+    }
+    /**
+    Moves all values from `other` into this set, leaving `other` empty
+
+    If a value from `other` is already present in this set, the value from `other` replaces it.
+
+    If both sets are on the stack and their values fit in one array, they are merged in two
+    linear passes, the first of which counts the merged values. Otherwise, this set moves to the heap.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut a: TinySet<[Inner<_>; 4]> = [1, 2, 3].iter().copied().collect();
+    let mut b: TinySet<[Inner<_>; 4]> = [3, 4, 5].iter().copied().collect();
+
+    a.append(&mut b);
+
+    assert_eq!(a.iter().copied().collect::<Vec<_>>(), [1, 2, 3, 4, 5]);
+    assert!(b.is_empty());
     ```
     */
-    pub fn is_empty(&self) -> bool {
-        self.len() == 0
+    pub fn append(&mut self, other: &mut Self) {
+        #[cfg(feature = "stats")]
+        let len = self.len();
+        let appended = match (&mut *self, &mut *other) {
+            (TinySet::Stack(ours), TinySet::Stack(theirs)) => ours.try_append(theirs).is_ok(),
+            _ => false,
+        };
+        if !appended {
+            match other {
+                TinySet::Stack(theirs) => self.heap_mut().append(&mut H::from_stack(theirs)),
+                TinySet::Heap(theirs) => self.heap_mut().append(theirs),
+            }
+        }
+        #[cfg(feature = "stats")]
+        self.record_growth(len);
     }
     /**
-    Returns the maximum number of elements the set can contain
+    Removes the value at the given position in the set's sort order and returns it
+
+    This is O(n) both on the stack and on the heap. Once the set has moved to a BTreeSet,
+    the value at that position is cloned to look it up.
 
     # Example
 
     ```
     use tinymap::*;
 
-    let mut a = TinySet::<[Inner<i32>; 2]>::new();
-    assert_eq!(2, a.capacity());
+    let mut set: TinySet<[Inner<_>; 3]> = [3, 1, 2].iter().copied().collect();
+    assert_eq!(set.remove_index(1), Some(2));
+    assert_eq!(set.remove_index(2), None);
+    assert_eq!(set.len(), 2);
     ```
     */
-    pub fn capacity(&self) -> usize {
-        A::CAPACITY
+    pub fn remove_index(&mut self, index: usize) -> Option<A::Item>
+    where
+        A::Item: Clone,
+    {
+        match self {
+            TinySet::Stack(set) => set.remove_index(index),
+            TinySet::Heap(set) => set.remove_index(index),
+        }
     }
     /**
-    Gets an iterator over the entries of the set, sorted
+    Returns the position of the value in the set's sort order, if it is present
+
+    This is O(log n) while the set is on the stack or in a VecSet and O(n) once it has moved to a BTreeSet.
 
     # Example
 
     ```
     use tinymap::*;
 
-    let set: TinySet<[Inner<i32>; 3]> = [3, 1, 2].iter().copied().collect();
-    let mut set_iter = set.iter();
-    assert_eq!(set_iter.next(), Some(&1));
-    assert_eq!(set_iter.next(), Some(&2));
-    assert_eq!(set_iter.next(), Some(&3));
-    assert_eq!(set_iter.next(), None);
+    let set: TinySet<[Inner<_>; 3]> = [10, 20, 30].iter().copied().collect();
+    assert_eq!(set.index_of(&20), Some(1));
+    assert_eq!(set.index_of(&15), None);
     ```
     */
-    pub fn iter(&self) -> Iter<'_, A::Item> {
+    pub fn index_of<Q>(&self, value: &Q) -> Option<usize>
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Item>,
+        H: SetLookup<Q, C>,
+    {
         match self {
-            TinySet::Stack(set) => Iter::Stack(set.iter()),
-            TinySet::Heap(set) => Iter::Heap(set.iter()),
+            TinySet::Stack(set) => set.index_of(value),
+            TinySet::Heap(set) => {
+                set.get(value)?;
+                Some(set.count_below(value))
+            }
         }
     }
-}
-
-impl<A> TinySet<A>
-where
-    A: Array,
-    A::Item: Ord,
-{
     /**
-    Inserts an value into the set
-
-    If the set did not have this value present, true is returned.
-
-    If the set did have this value present, `false` is returned, and the entry is not updated.
+    Returns the number of values in the set that are less than the given value
 
-    # Panics
+    The value does not have to be present in the set.
 
-    Panics if insertion would cause the set to excede its capacity.
+    This is O(log n) while the set is on the stack or in a VecSet and O(n) once it has moved to a BTreeSet.
 
     # Example
 
     ```
     use tinymap::*;
 
-    let mut set = TinySet::<[Inner<i32>; 2]>::new();
-
-    assert_eq!(set.insert(2), true);
-    assert_eq!(set.insert(2), false);
-    assert_eq!(set.len(), 1);
+    let set: TinySet<[Inner<_>; 3]> = [10, 20, 30].iter().copied().collect();
+    assert_eq!(set.rank(&5), 0);
+    assert_eq!(set.rank(&20), 1);
+    assert_eq!(set.rank(&25), 2);
     ```
     */
-    pub fn insert(&mut self, value: A::Item) -> bool {
+    pub fn rank<Q>(&self, value: &Q) -> usize
+    where
+        Q: ?Sized,
+        C: Compare<Q, A::Item>,
+        H: SetLookup<Q, C>,
+    {
         match self {
-            TinySet::Stack(set) => match set.try_insert(value) {
-                Ok(res) => res,
-                Err(val) => {
-                    let mut replacement_set = ArraySet::default();
-                    swap(&mut replacement_set, set);
-                    let mut btree_set = BTreeSet::new();
-                    for val in replacement_set.into_iter() {
-                        btree_set.insert(val);
-                    }
-                    let res = btree_set.insert(val);
-                    *self = TinySet::Heap(btree_set);
-                    res
-                }
-            },
-            TinySet::Heap(set) => set.insert(value),
+            TinySet::Stack(set) => set.rank(value),
+            TinySet::Heap(set) => set.count_below(value),
         }
     }
     /**
-    Returns true if the set contains a value for the specified value
+    Gets an iterator over the values in this set that are also keys in `other`, along with `()` for this side and the value from `other`
+
+    `other` can be anything that iterates over references to a map or set in ascending key order,
+    such as another map or set from this crate. Both sides are walked in a single linear pass.
+    See the [`join`](../join/index.html) module for details.
 
     # Example
 
     ```
     use tinymap::*;
 
-    let set: TinySet<[Inner<_>; 3]> = [1, 2, 3].iter().copied().collect();
-    assert_eq!(set.contains(&1), true);
-    assert_eq!(set.contains(&4), false);
+    let mut fruit = TinySet::<[Inner<&str>; 4]>::new();
+    fruit.insert("apple");
+    fruit.insert("pear");
+    fruit.insert("plum");
+
+    let mut stock = TinyMap::<[Inner<(&str, u32)>; 4]>::new();
+    stock.insert("pear", 10);
+    stock.insert("plum", 0);
+    stock.insert("quince", 5);
+
+    // Sets join as if they had `()` values
+    let joined: Vec<_> = fruit.join(&stock).collect();
+    assert_eq!(joined, [(&"pear", (), &10), (&"plum", (), &0)]);
     ```
     */
-    pub fn contains<Q>(&self, value: &Q) -> bool
+    pub fn join<'a, I>(&'a self, other: I) -> Join<Iter<'a, A::Item, C>, I::IntoIter, C>
     where
-        A::Item: Borrow<Q>,
-        Q: Ord + ?Sized,
+        I: IntoIterator,
+        I::Item: JoinItem<Key = &'a A::Item>,
+        C: Clone,
     {
-        match self {
-            TinySet::Stack(set) => set.contains(value),
-            TinySet::Heap(set) => set.contains(value),
-        }
+        Join::new(self.iter(), other.into_iter(), self.comparator())
     }
     /**
-    Returns a reference to the value if it is present
+    Gets an iterator over the values in this set, along with `()` for this side and, if present, the value from `other`
+
+    `other` can be anything that iterates over references to a map or set in ascending key order,
+    such as another map or set from this crate. Both sides are walked in a single linear pass.
+    See the [`join`](../join/index.html) module for details.
 
     # Example
 
     ```
     use tinymap::*;
 
-    let mut set: TinySet<[Inner<_>; 3]> = [1, 2, 3].iter().copied().collect();
-    assert_eq!(set.get(&2), Some(&2));
-    assert_eq!(set.get(&4), None);
+    let mut fruit = TinySet::<[Inner<&str>; 4]>::new();
+    fruit.insert("apple");
+    fruit.insert("pear");
+    fruit.insert("plum");
+
+    let mut stock = TinyMap::<[Inner<(&str, u32)>; 4]>::new();
+    stock.insert("pear", 10);
+    stock.insert("plum", 0);
+    stock.insert("quince", 5);
+
+    let joined: Vec<_> = fruit.left_join(&stock).collect();
+    assert_eq!(joined, [
+        (&"apple", (), None),
+        (&"pear", (), Some(&10)),
+        (&"plum", (), Some(&0)),
+    ]);
     ```
     */
-    pub fn get<Q>(&self, value: &Q) -> Option<&A::Item>
+    pub fn left_join<'a, I>(&'a self, other: I) -> LeftJoin<Iter<'a, A::Item, C>, I::IntoIter, C>
     where
-        A::Item: Borrow<Q>,
-        Q: Ord + ?Sized,
+        I: IntoIterator,
+        I::Item: JoinItem<Key = &'a A::Item>,
+        C: Clone,
     {
-        match self {
-            TinySet::Stack(set) => set.get(value),
-            TinySet::Heap(set) => set.get(value),
-        }
+        LeftJoin::new(self.iter(), other.into_iter(), self.comparator())
     }
     /**
-    Removes a value from the set. Returns whether the value was present in the set.
+    Gets an iterator over the keys in either this set or `other`, along with `()` for this side if the key is in this set and the value from `other` if it is there
+
+    `other` can be anything that iterates over references to a map or set in ascending key order,
+    such as another map or set from this crate. Both sides are walked in a single linear pass.
+    See the [`join`](../join/index.html) module for details.
 
     # Example
 
     ```
-    use tinymap::*;
+    use tinymap::{join::EitherOrBoth, *};
 
-    let mut set = TinySet::<[Inner<i32>; 2]>::new();
+    let mut fruit = TinySet::<[Inner<&str>; 4]>::new();
+    fruit.insert("apple");
+    fruit.insert("pear");
+    fruit.insert("plum");
 
-    set.insert(2);
-    assert_eq!(set.remove(&2), true);
-    assert_eq!(set.remove(&2), false);
+    let mut stock = TinyMap::<[Inner<(&str, u32)>; 4]>::new();
+    stock.insert("pear", 10);
+    stock.insert("plum", 0);
+    stock.insert("quince", 5);
+
+    let joined: Vec<_> = fruit.outer_join(&stock).collect();
+    assert_eq!(joined, [
+        (&"apple", EitherOrBoth::Left(())),
+        (&"pear", EitherOrBoth::Both((), &10)),
+        (&"plum", EitherOrBoth::Both((), &0)),
+        (&"quince", EitherOrBoth::Right(&5)),
+    ]);
     ```
     */
-    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    pub fn outer_join<'a, I>(&'a self, other: I) -> OuterJoin<Iter<'a, A::Item, C>, I::IntoIter, C>
     where
-        A::Item: Borrow<Q>,
-        Q: Ord + ?Sized,
+        I: IntoIterator,
+        I::Item: JoinItem<Key = &'a A::Item>,
+        C: Clone,
     {
-        match self {
-            TinySet::Stack(set) => set.remove(value),
-            TinySet::Heap(set) => set.remove(value),
-        }
+        OuterJoin::new(self.iter(), other.into_iter(), self.comparator())
     }
 }
 
-impl<A> fmt::Debug for TinySet<A>
+impl<A, C, H> fmt::Debug for TinySet<A, C, H>
 where
     A: Array,
     A::Item: fmt::Debug,
+    H: SetHeap<C, Item = A::Item>,
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_list().entries(self.iter()).finish()
     }
 }
 
-impl<A> From<ArraySet<A>> for TinySet<A>
+impl<A, C> From<ArraySet<A, C>> for TinySet<A, C>
 where
     A: Array,
 {
-    fn from(set: ArraySet<A>) -> Self {
+    fn from(set: ArraySet<A, C>) -> Self {
         TinySet::Stack(set)
     }
 }
 
-impl<A> From<A> for TinySet<A>
+impl<A, C> From<A> for TinySet<A, C>
 where
     A: Array,
     A::Item: Ord,
+    C: TreeOrder<A::Item>,
 {
     fn from(array: A) -> Self {
         TinySet::from(ArraySet::from(array))
     }
 }
 
-impl<A> From<BTreeSet<A::Item>> for TinySet<A>
+/// The tree becomes the heap of the set as it is, without moving its values.
+impl<A, C> From<BTreeSet<A::Item>> for TinySet<A, C>
 where
     A: Array,
+    A::Item: Ord,
+    C: TreeOrder<A::Item>,
 {
     fn from(set: BTreeSet<A::Item>) -> Self {
         TinySet::Heap(set)
     }
 }
 
-impl<A> PartialEq for TinySet<A>
+/**
+The tree is ordered by the values' `Ord` implementation, whatever the set's comparator.
+
+A set on the heap hands over its tree as it is, without moving its values.
+A set on the stack builds a new tree from its values.
+*/
+impl<A, C> From<TinySet<A, C>> for BTreeSet<A::Item>
+where
+    A: Array,
+    A::Item: Ord,
+{
+    fn from(set: TinySet<A, C>) -> Self {
+        match set {
+            TinySet::Stack(set) => set.into(),
+            TinySet::Heap(set) => set,
+        }
+    }
+}
+
+impl<A, C, H> PartialEq for TinySet<A, C, H>
 where
     A: Array,
     A::Item: PartialEq,
+    H: SetHeap<C, Item = A::Item>,
 {
     fn eq(&self, other: &Self) -> bool {
         self.iter().eq(other.iter())
     }
 }
 
-impl<A> Eq for TinySet<A>
+impl<A, C, H> Eq for TinySet<A, C, H>
 where
     A: Array,
     A::Item: Eq,
+    H: SetHeap<C, Item = A::Item>,
 {
 }
 
-impl<'a, A> IntoIterator for &'a TinySet<A>
+impl<'a, A, C, H> IntoIterator for &'a TinySet<A, C, H>
 where
     A: Array,
+    H: SetHeap<C, Item = A::Item>,
 {
     type Item = &'a A::Item;
-    type IntoIter = Iter<'a, A::Item>;
+    type IntoIter = Iter<'a, A::Item, C>;
     fn into_iter(self) -> Self::IntoIter {
         self.iter()
     }
 }
 
 #[cfg(feature = "alloc")]
-impl<A> IntoIterator for TinySet<A>
+impl<A, C, H> IntoIterator for TinySet<A, C, H>
 where
     A: Array,
+    H: SetHeap<C, Item = A::Item>,
 {
     type Item = A::Item;
-    type IntoIter = IntoIter<A::Item>;
+    type IntoIter = IntoIter<A::Item, C, H>;
     fn into_iter(self) -> Self::IntoIter {
         match self {
             TinySet::Stack(set) => IntoIter::Stack(set.into_iter()),
-            TinySet::Heap(set) => IntoIter::Heap(set.into_iter()),
+            TinySet::Heap(set) => IntoIter::Heap(set.into_values()),
         }
     }
 }
 
-/// Elements from the iterator beyond the set's capacity will be discarded.
-impl<A> FromIterator<A::Item> for TinySet<A>
+/// The set starts on the heap if the iterator's size hint says that it will not fit on the stack.
+impl<A, C, H> FromIterator<A::Item> for TinySet<A, C, H>
 where
     A: Array,
-    A::Item: Ord,
+    C: Compare<A::Item> + Default,
+    H: SetHeap<C, Item = A::Item> + Default,
 {
     fn from_iter<I>(iter: I) -> Self
     where
         I: IntoIterator<Item = A::Item>,
     {
-        let mut set = TinySet::default();
-        for value in iter.into_iter().take(A::CAPACITY) {
-            set.insert(value);
-        }
+        let iter = iter.into_iter();
+        let mut set = if iter.size_hint().0 > A::CAPACITY {
+            TinySet::with_heap()
+        } else {
+            TinySet::default()
+        };
+        set.extend(iter);
         set
     }
 }
 
-impl<A> Extend<A::Item> for TinySet<A>
+impl<A, C, H> Extend<A::Item> for TinySet<A, C, H>
 where
     A: Array,
-    A::Item: Ord,
+    C: Compare<A::Item>,
+    H: SetHeap<C, Item = A::Item>,
 {
     fn extend<I>(&mut self, iter: I)
     where
@@ -384,14 +1658,20 @@ where
 }
 
 /// An consuming iterator over the values in an ArraySet
-pub enum IntoIter<T> {
+pub enum IntoIter<T, C = Natural, H = BTreeSet<T>>
+where
+    H: SetHeap<C, Item = T>,
+{
     #[doc(hidden)]
     Stack(crate::array_set::IntoIter<T>),
     #[doc(hidden)]
-    Heap(std::collections::btree_set::IntoIter<T>),
+    Heap(H::IntoIter),
 }
 
-impl<T> Iterator for IntoIter<T> {
+impl<T, C, H> Iterator for IntoIter<T, C, H>
+where
+    H: SetHeap<C, Item = T>,
+{
     type Item = T;
     fn next(&mut self) -> Option<Self::Item> {
         match self {
@@ -402,19 +1682,308 @@ impl<T> Iterator for IntoIter<T> {
 }
 
 /// An iterator over references to the values in an TinySet
-pub enum Iter<'a, T> {
+pub enum Iter<'a, T, C = Natural> {
     #[doc(hidden)]
     Stack(crate::array_set::Iter<'a, T>),
     #[doc(hidden)]
-    Heap(std::collections::btree_set::Iter<'a, T>),
+    Heap(Ordered<btree_set::Iter<'a, T>>, PhantomData<fn() -> C>),
 }
 
-impl<'a, T> Iterator for Iter<'a, T> {
+impl<'a, T, C> Iterator for Iter<'a, T, C>
+where
+    T: 'a,
+{
     type Item = &'a T;
     fn next(&mut self) -> Option<Self::Item> {
         match self {
             Iter::Stack(iter) => iter.next(),
-            Iter::Heap(iter) => iter.next(),
+            Iter::Heap(iter, _) => iter.next(),
+        }
+    }
+}
+
+/// A view into a single value in a set, which may either be vacant or occupied.
+pub enum Entry<'a, A, C = Natural, H = BTreeSet<<A as Storage>::Item>>
+where
+    A: Array,
+{
+    /// A vacant entry.
+    Vacant(VacantEntry<'a, A, C, H>),
+    /// An occupied entry.
+    Occupied(OccupiedEntry<'a, A, C, H>),
+}
+
+impl<'a, A, C, H> Entry<'a, A, C, H>
+where
+    A: Array,
+    C: Compare<A::Item>,
+    H: SetLookup<A::Item, C, Item = A::Item>,
+{
+    /**
+    Ensures the value is in the set by inserting it if the entry is vacant,
+    and returns a reference to the value in the set.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut set = TinySet::<[Inner<&str>; 10]>::new();
+    assert_eq!(set.entry("poneyland").or_insert(), &"poneyland");
+    assert!(set.contains("poneyland"));
+    ```
+    */
+    pub fn or_insert(self) -> &'a A::Item {
+        match self {
+            Entry::Vacant(entry) => entry.insert(),
+            Entry::Occupied(entry) => entry.into_ref(),
+        }
+    }
+    /**
+    Returns a reference to this entry's value
+
+    For a vacant entry, this is the value that would be inserted.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut set = TinySet::<[Inner<&str>; 10]>::new();
+    assert_eq!(set.entry("poneyland").get(), &"poneyland");
+    ```
+    */
+    pub fn get(&self) -> &A::Item {
+        match self {
+            Entry::Vacant(entry) => entry.get(),
+            Entry::Occupied(entry) => entry.get(),
+        }
+    }
+}
+
+/// An entry in a TinySet that is vacant
+pub struct VacantEntry<'a, A, C = Natural, H = BTreeSet<<A as Storage>::Item>>
+where
+    A: Array,
+{
+    set: &'a mut TinySet<A, C, H>,
+    value: A::Item,
+    index: usize,
+}
+
+impl<'a, A, C, H> VacantEntry<'a, A, C, H>
+where
+    A: Array + 'a,
+    C: Compare<A::Item>,
+    H: SetLookup<A::Item, C, Item = A::Item>,
+{
+    /**
+    Gets a reference to the value that would be inserted through the VacantEntry.
+    */
+    pub fn get(&self) -> &A::Item {
+        &self.value
+    }
+    /**
+    Take ownership of the value.
+    */
+    pub fn into_value(self) -> A::Item {
+        self.value
+    }
+    /**
+    Inserts the VacantEntry's value into the set and returns a reference to it.
+
+    If the set is on the stack and full, it moves to the heap. Once the set is in a BTreeSet,
+    finding the inserted value again takes O(n).
+    */
+    pub fn insert(self) -> &'a A::Item {
+        let VacantEntry { set, value, index } = self;
+        #[cfg(feature = "stats")]
+        crate::stats::record_len::<TinySet<A, C, H>>(A::CAPACITY, set.len() + 1);
+        if matches!(&*set, TinySet::Stack(stack) if stack.len() == A::CAPACITY) {
+            set.heap_mut();
+        }
+        match set {
+            TinySet::Stack(stack) => {
+                if stack.try_insert_at(index, value).is_err() {
+                    unreachable!()
+                }
+                stack.get_index(index).unwrap()
+            }
+            TinySet::Heap(heap) => heap.insert_new(value),
+        }
+    }
+}
+
+/// An entry in a TinySet that is occupied
+pub struct OccupiedEntry<'a, A, C = Natural, H = BTreeSet<<A as Storage>::Item>>
+where
+    A: Array,
+{
+    set: &'a mut TinySet<A, C, H>,
+    value: A::Item,
+    index: usize,
+}
+
+impl<'a, A, C, H> OccupiedEntry<'a, A, C, H>
+where
+    A: Array + 'a,
+    C: Compare<A::Item>,
+    H: SetLookup<A::Item, C, Item = A::Item>,
+{
+    /**
+    Gets a reference to the value in the set
+    */
+    pub fn get(&self) -> &A::Item {
+        match &*self.set {
+            TinySet::Stack(set) => set.get_index(self.index).unwrap(),
+            TinySet::Heap(set) => set.get(&self.value).unwrap(),
+        }
+    }
+    /**
+    Converts the entry into a reference to the value in the set.
+    */
+    pub fn into_ref(self) -> &'a A::Item {
+        let OccupiedEntry { set, value, index } = self;
+        match set {
+            TinySet::Stack(set) => set.get_index(index).unwrap(),
+            TinySet::Heap(set) => set.get(&value).unwrap(),
+        }
+    }
+    /**
+    Takes the value out of the set, and returns it.
+    */
+    pub fn remove(self) -> A::Item {
+        match self.set {
+            TinySet::Stack(set) => set.remove_index(self.index).unwrap(),
+            TinySet::Heap(set) => set.take(&self.value).unwrap(),
+        }
+    }
+}
+
+/**
+A guard that gives mutable access to a value in a TinySet
+
+Created with [`TinySet::get_key_mut`](enum.TinySet.html#method.get_key_mut).
+When the guard is dropped, the value is moved to wherever it now sorts, replacing any
+other value it has become equal to.
+*/
+pub enum KeyMut<'a, A, C = Natural, H = BTreeSet<<A as Storage>::Item>>
+where
+    A: Array,
+    C: Compare<A::Item>,
+    H: SetLookup<A::Item, C, Item = A::Item>,
+{
+    #[doc(hidden)]
+    Stack(crate::array_set::KeyMut<'a, A, C>),
+    #[doc(hidden)]
+    Heap(HeapKeyMut<'a, A::Item, C, H>),
+}
+
+impl<'a, A, C, H> KeyMut<'a, A, C, H>
+where
+    A: Array,
+    C: Compare<A::Item>,
+    H: SetLookup<A::Item, C, Item = A::Item>,
+{
+    /**
+    Moves the value to wherever it now sorts
+
+    # Errors
+
+    If the value has become equal to another value in the set, the changed value is removed
+    from the set and returned, leaving the other value in place.
+
+    # Example
+
+    ```
+    use tinymap::*;
+
+    let mut set = TinySet::<[Inner<i32>; 2]>::new();
+    set.extend(vec![1, 2, 3]);
+
+    let mut value = set.get_key_mut(&1).unwrap();
+    *value = 2;
+    assert_eq!(value.finish(), Err(2));
+    assert_eq!(set.len(), 2);
+    ```
+    */
+    pub fn finish(self) -> Result<(), A::Item> {
+        match self {
+            KeyMut::Stack(value) => value.finish(),
+            KeyMut::Heap(value) => value.finish(),
+        }
+    }
+}
+
+#[doc(hidden)]
+pub struct HeapKeyMut<'a, T, C, H>
+where
+    H: SetLookup<T, C, Item = T>,
+{
+    set: &'a mut H,
+    /// The value taken out of the heap, until it is put back
+    value: Option<T>,
+    order: PhantomData<fn() -> C>,
+}
+
+impl<T, C, H> HeapKeyMut<'_, T, C, H>
+where
+    H: SetLookup<T, C, Item = T>,
+{
+    fn value(&self) -> &T {
+        self.value.as_ref().unwrap()
+    }
+    fn value_mut(&mut self) -> &mut T {
+        self.value.as_mut().unwrap()
+    }
+    fn finish(mut self) -> Result<(), T> {
+        let value = self.value.take().unwrap();
+        if self.set.get(&value).is_some() {
+            return Err(value);
+        }
+        self.set.insert(value);
+        Ok(())
+    }
+}
+
+impl<T, C, H> Drop for HeapKeyMut<'_, T, C, H>
+where
+    H: SetLookup<T, C, Item = T>,
+{
+    fn drop(&mut self) {
+        if let Some(value) = self.value.take() {
+            // Put the value back before dropping the one it replaces
+            let replaced = self.set.replace(value);
+            drop(replaced);
+        }
+    }
+}
+
+impl<'a, A, C, H> Deref for KeyMut<'a, A, C, H>
+where
+    A: Array,
+    C: Compare<A::Item>,
+    H: SetLookup<A::Item, C, Item = A::Item>,
+{
+    type Target = A::Item;
+    fn deref(&self) -> &Self::Target {
+        match self {
+            KeyMut::Stack(value) => value,
+            KeyMut::Heap(value) => value.value(),
+        }
+    }
+}
+
+impl<'a, A, C, H> DerefMut for KeyMut<'a, A, C, H>
+where
+    A: Array,
+    C: Compare<A::Item>,
+    H: SetLookup<A::Item, C, Item = A::Item>,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self {
+            KeyMut::Stack(value) => value,
+            KeyMut::Heap(value) => value.value_mut(),
         }
     }
 }